@@ -1,13 +1,98 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-/// Trait to provide price of currency pairs. The price is multiplied by 1000 and rounded to make it integer
+extern crate alloc;
+
+use alloc::vec::Vec;
+use codec::{Decode, Encode};
+
+/// A pair of currency symbols like DOCK/USD. The price of the pair is the amount of currency `to`
+/// that one unit of currency `from` is worth.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CurrencySymbolPair {
+    pub from: Vec<u8>,
+    pub to: Vec<u8>,
+}
+
+impl CurrencySymbolPair {
+    pub fn new(from: &str, to: &str) -> Self {
+        CurrencySymbolPair {
+            from: from.as_bytes().to_vec(),
+            to: to.as_bytes().to_vec(),
+        }
+    }
+
+    /// The DOCK/USD pair
+    pub fn dock_usd() -> Self {
+        Self::new("DOCK", "USD")
+    }
+}
+
+/// A price as a fixed point number, i.e. the price is `amount / 10^decimals`. For example, a price
+/// of 0.0525 can be `FixedPrice { amount: 525, decimals: 4 }` or `FixedPrice { amount: 52500, decimals: 6 }`.
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FixedPrice {
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+impl FixedPrice {
+    pub fn new(amount: u64, decimals: u8) -> Self {
+        FixedPrice { amount, decimals }
+    }
+
+    /// The amount of this price when expressed with the given number of decimals. Extra decimals are
+    /// truncated. Returns `None` if the amount does not fit in a `u64`.
+    pub fn amount_with_decimals(&self, decimals: u8) -> Option<u64> {
+        if decimals >= self.decimals {
+            10u64
+                .checked_pow((decimals - self.decimals) as u32)
+                .and_then(|m| self.amount.checked_mul(m))
+        } else {
+            // Dividing by more than 10^19 always gives 0
+            Some(
+                10u64
+                    .checked_pow((self.decimals - decimals) as u32)
+                    .map_or(0, |d| self.amount / d),
+            )
+        }
+    }
+
+    /// This price expressed with the given number of decimals. Returns `None` on overflow.
+    pub fn with_decimals(&self, decimals: u8) -> Option<Self> {
+        self.amount_with_decimals(decimals)
+            .map(|amount| Self::new(amount, decimals))
+    }
+}
+
+/// Trait to provide price of currency pairs as fixed point numbers
 pub trait PriceProvider {
     // NOTE: Consider returning weight when None as well
 
+    /// Get the latest price of the given pair. Returns the price and consumed weight in this operation
+    fn get_price(pair: &CurrencySymbolPair) -> Option<(FixedPrice, u64)>;
+
+    /// Get the latest price of the given pair but it is an optimized call so it might not get the
+    /// latest price. Returns the price and consumed weight in this operation
+    fn optimized_get_price(pair: &CurrencySymbolPair) -> Option<(FixedPrice, u64)>;
+
+    /// Get the time weighted average price of the given pair over a recent window of blocks. Less
+    /// prone to manipulation than the latest price. Returns the price and consumed weight in this operation
+    fn get_twap_price(pair: &CurrencySymbolPair) -> Option<(FixedPrice, u64)>;
+
+    /// Get the median price of the given pair over a recent window of blocks. Less prone to
+    /// manipulation than the latest price. Returns the price and consumed weight in this operation
+    fn get_median_price(pair: &CurrencySymbolPair) -> Option<(FixedPrice, u64)>;
+
     /// Get the latest price of Dock in terms of USD. Returns the price and consumed weight in this operation
-    fn get_dock_usd_price() -> Option<(u32, u64)>;
+    fn get_dock_usd_price() -> Option<(FixedPrice, u64)> {
+        Self::get_price(&CurrencySymbolPair::dock_usd())
+    }
 
     /// Get the latest price of Dock in terms of USD but it is an optimized call so it might not get
     /// the latest price. Returns the price and consumed weight in this operation
-    fn optimized_get_dock_usd_price() -> Option<(u32, u64)>;
+    fn optimized_get_dock_usd_price() -> Option<(FixedPrice, u64)> {
+        Self::optimized_get_price(&CurrencySymbolPair::dock_usd())
+    }
 }
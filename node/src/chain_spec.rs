@@ -1,8 +1,8 @@
 use dock_runtime::{
     did::{self, Did, KeyDetail},
-    master::Membership,
+    master::{MemberDetail, Membership},
     opaque::SessionKeys,
-    price_feed::{util::ParamType, ContractConfig},
+    price_feed::{ContractConfig, CurrencySymbolPair, PriceDecimals},
     AccountId, AuraConfig, Balance, BalancesConfig, CouncilMembershipConfig, DIDModuleConfig,
     EVMConfig, EthereumConfig, GenesisConfig, GrandpaConfig, MasterConfig, PoAModuleConfig,
     PriceFeedModuleConfig, SessionConfig, Signature, SudoConfig, SystemConfig,
@@ -110,20 +110,16 @@ fn get_seed_vector_to_account_vector(seeds: Vec<&str>) -> Vec<AccountId> {
 }
 
 fn get_dev_chain_price_feed_contract() -> ContractConfig {
-    ContractConfig {
-        address: H160::from([
+    ContractConfig::from_signatures(
+        H160::from([
             102, 119, 174, 46, 44, 201, 133, 68, 109, 66, 165, 189, 238, 24, 39, 2, 232, 94, 133,
             135,
         ]),
-        query_abi_encoded: vec![254, 175, 150, 140],
-        return_val_abi: vec![
-            ParamType::Uint(80),
-            ParamType::Int(256),
-            ParamType::Uint(256),
-            ParamType::Uint(256),
-            ParamType::Uint(80),
-        ],
-    }
+        "latestRoundData()",
+        "(uint80,int256,uint256,uint256,uint80)",
+        PriceDecimals::Fixed(3),
+    )
+    .expect("Valid signatures")
 }
 
 pub fn development_config() -> ChainSpec {
@@ -151,8 +147,7 @@ pub fn development_config() -> ChainSpec {
                         b"Charlie\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0",
                     ]
                     .iter()
-                    .cloned()
-                    .cloned()
+                    .map(|did| (**did, MemberDetail::default()))
                     .collect(),
                     vote_requirement: 2,
                 },
@@ -227,8 +222,7 @@ pub fn local_testnet_config() -> ChainSpec {
                         b"Charlie\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0",
                     ]
                     .iter()
-                    .cloned()
-                    .cloned()
+                    .map(|did| (**did, MemberDetail::default()))
                     .collect(),
                     vote_requirement: 2,
                 },
@@ -323,8 +317,7 @@ pub fn testnet_config() -> ChainSpec {
                         b"ec\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0",
                     ]
                     .iter()
-                    .cloned()
-                    .cloned()
+                    .map(|did| (**did, MemberDetail::default()))
                     .collect(),
                     vote_requirement: 2,
                 },
@@ -441,8 +434,7 @@ pub fn mainnet_config() -> ChainSpec {
                         b"ec\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0",
                     ]
                     .iter()
-                    .cloned()
-                    .cloned()
+                    .map(|did| (**did, MemberDetail::default()))
                     .collect(),
                     vote_requirement: 2,
                 },
@@ -612,14 +604,14 @@ impl GenesisBuilder {
                 accounts: BTreeMap::new(),
             }),
             price_feed: Some(PriceFeedModuleConfig {
-                contract_config: self.contract_config,
+                contract_configs: vec![(CurrencySymbolPair::dock_usd(), self.contract_config)],
             }),
         }
     }
 
     fn validate(&self) -> Result<(), String> {
         // Every DID in master must be pre-declared
-        for did in self.master.members.iter() {
+        for did in self.master.members.keys() {
             if !self.dids.iter().any(|(k, _v)| k == did) {
                 return Err(format!(
                     "Master contains DID {:x?}.. that is not pre-declared",
@@ -8,7 +8,7 @@
 use std::{fmt, sync::Arc};
 
 use dock_runtime::{
-    opaque::Block, AccountId, Balance, BlockNumber, Hash, Index, TransactionConverter,
+    opaque::Block, AccountId, Balance, BlockNumber, Hash, Index, Moment, TransactionConverter,
 };
 use fc_rpc_core::types::{FilterPool, PendingTransactions};
 use jsonrpc_pubsub::manager::SubscriptionManager;
@@ -79,11 +79,14 @@ where
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
     C::Api: poa_rpc::PoARuntimeApi<Block, AccountId, Balance>,
     C::Api: price_feed_rpc::PriceFeedRuntimeApi<Block>,
+    C::Api: core_mods_rpc::AnchorRuntimeApi<Block, Hash, BlockNumber, Moment>,
+    C::Api: core_mods_rpc::MasterVoteRuntimeApi<Block>,
     C::Api: BlockBuilder<Block>,
     C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
     <C::Api as sp_api::ApiErrorExt>::Error: fmt::Debug,
     P: TransactionPool<Block = Block> + 'static,
 {
+    use core_mods_rpc::{CoreMods, CoreModsApi};
     use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
     use poa_rpc::{PoA, PoAApi};
     use price_feed_rpc::{PriceFeed, PriceFeedApi};
@@ -130,6 +133,9 @@ where
     // RPC calls for Price Feed pallet
     io.extend_with(PriceFeedApi::to_delegate(PriceFeed::new(client.clone())));
 
+    // RPC calls for core modules
+    io.extend_with(CoreModsApi::to_delegate(CoreMods::new(client.clone())));
+
     io.extend_with(sc_finality_grandpa_rpc::GrandpaApi::to_delegate(
         GrandpaRpcHandler::new(
             shared_authority_set,
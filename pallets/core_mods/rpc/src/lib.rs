@@ -0,0 +1,183 @@
+pub use self::gen_client::Client as CoreModsClient;
+use codec::Codec;
+pub use core_mods::anchor::AnchorQuery;
+use core_mods::did::{Did, DidSignature};
+pub use core_mods::runtime_api::AnchorApi as AnchorRuntimeApi;
+pub use core_mods::runtime_api::MasterVoteApi as MasterVoteRuntimeApi;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{
+    generic::BlockId,
+    traits::{Block as BlockT, MaybeDisplay, MaybeFromStr, NumberFor},
+};
+use std::sync::Arc;
+
+/// Details of the block an anchor was posted in.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorInfo<BlockNumber, BlockHash, Moment> {
+    /// Number of the block the anchor was posted in
+    pub block_number: BlockNumber,
+    /// Hash of the block the anchor was posted in
+    pub block_hash: BlockHash,
+    /// Timestamp (`pallet_timestamp`) of the block the anchor was posted in
+    pub timestamp: Moment,
+    /// DID that signed the anchor, `None` if the anchor was not posted on behalf of a DID
+    pub author: Option<Did>,
+}
+
+#[rpc]
+pub trait CoreModsApi<BlockHash, BlockNumber, Moment> {
+    /// Return the number, hash and timestamp of the block in which the anchor was posted and the
+    /// DID that signed the anchor if any. The anchor can be identified either by the anchored data
+    /// or its hash. Returns `None` if the anchor does not exist.
+    #[rpc(name = "core_mods_anchorLookup")]
+    fn anchor_lookup(
+        &self,
+        query: AnchorQuery<BlockHash>,
+        at: Option<BlockHash>,
+    ) -> Result<Option<AnchorInfo<BlockNumber, BlockHash, Moment>>>;
+
+    /// Return the bytes a Master member has to sign to vote for the SCALE encoded Call in the
+    /// current round, i.e. the SCALE encoding of `StateChange::MasterVote`. Fails if the bytes are
+    /// not exactly the encoding of a Call.
+    #[rpc(name = "core_mods_masterVotePayload")]
+    fn master_vote_payload(&self, proposal: Bytes, at: Option<BlockHash>) -> Result<Bytes>;
+
+    /// Check votes for the SCALE encoded Call against the current Master members and round, as
+    /// `master.execute` would. Votes are given as pairs of DID and signature. Returns the total
+    /// weight of the votes if they authorize the Call, an error explaining why they don't
+    /// otherwise.
+    #[rpc(name = "core_mods_masterVerifyAuth")]
+    fn master_verify_auth(
+        &self,
+        proposal: Bytes,
+        auth: Vec<(Did, DidSignature)>,
+        at: Option<BlockHash>,
+    ) -> Result<u64>;
+}
+
+/// A struct that implements the [`CoreModsApi`].
+pub struct CoreMods<C, P> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<P>,
+}
+
+impl<C, P> CoreMods<C, P> {
+    /// Create new `CoreMods` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        CoreMods {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, Block, Moment> CoreModsApi<<Block as BlockT>::Hash, NumberFor<Block>, Moment>
+    for CoreMods<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: AnchorRuntimeApi<Block, <Block as BlockT>::Hash, NumberFor<Block>, Moment>,
+    C::Api: MasterVoteRuntimeApi<Block>,
+    Moment: Codec + MaybeDisplay + MaybeFromStr,
+{
+    fn anchor_lookup(
+        &self,
+        query: AnchorQuery<<Block as BlockT>::Hash>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<AnchorInfo<NumberFor<Block>, <Block as BlockT>::Hash, Moment>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        let (block_number, author) = match api.anchor_lookup(&at, query).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(1),
+            message: "Unable to query anchor.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })? {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+
+        let block_hash = self
+            .client
+            .hash(block_number)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(2),
+                message: "Unable to query hash of the anchoring block.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })?
+            .ok_or_else(|| RpcError {
+                code: ErrorCode::ServerError(2),
+                message: "Unable to query hash of the anchoring block.".into(),
+                data: Some(format!("No block with number {:?}", block_number).into()),
+            })?;
+
+        // The timestamp has to be read from the state of the anchoring block.
+        let timestamp = api
+            .timestamp(&BlockId::hash(block_hash))
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(3),
+                message: "Unable to query timestamp of the anchoring block.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })?;
+
+        Ok(Some(AnchorInfo {
+            block_number,
+            block_hash,
+            timestamp,
+            author,
+        }))
+    }
+
+    fn master_vote_payload(
+        &self,
+        proposal: Bytes,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Bytes> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.vote_payload(&at, proposal.to_vec())
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(4),
+                message: "Unable to query vote payload.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })?
+            .map(Into::into)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(7),
+                message: "Proposal is not a valid Call.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn master_verify_auth(
+        &self,
+        proposal: Bytes,
+        auth: Vec<(Did, DidSignature)>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<u64> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.verify_auth(&at, proposal.to_vec(), auth.into_iter().collect())
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(5),
+                message: "Unable to verify votes.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })?
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(6),
+                message: "Votes do not authorize the proposal.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+}
@@ -3,8 +3,14 @@
 //! Anchors are hashed once before being added to storage. To check whether an anchor exists
 //! query the "Anchors" map for the hash of the anchor. If a corresponding value exists, then the
 //! anchor exists and the value represents the block number when it was first published.
+//!
+//! An anchor can also be posted on behalf of a DID with `deploy_signed`. The DID signs the anchored
+//! data and is recorded in the "AnchorAuthors" map so that a verifier can attribute the anchor to
+//! an issuer rather than just to the account that paid for it.
 
+use crate::did::{self, Did, DidSignature};
 use alloc::vec::Vec;
+use codec::{Decode, Encode};
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure,
     traits::Get,
@@ -12,7 +18,27 @@ use frame_support::{
 use frame_system::{self as system, ensure_signed};
 use sp_runtime::traits::Hash;
 
-pub trait Trait: system::Config {
+/// Identifies an anchor either by the anchored data or by the hash of that data.
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnchorQuery<Hash> {
+    /// The data that was anchored, it is hashed before the lookup.
+    Data(Vec<u8>),
+    /// Hash of the anchored data.
+    Hash(Hash),
+}
+
+/// An anchor posted on behalf of a DID. The DID signs this struct wrapped in `StateChange`.
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DidAnchor {
+    /// DID of the issuer anchoring the data
+    pub author: Did,
+    /// The data to anchor, hashed before being added to storage
+    pub data: Vec<u8>,
+}
+
+pub trait Trait: system::Config + did::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Config>::Event>;
 }
 
@@ -20,6 +46,8 @@ decl_error! {
     pub enum Error for Module<T: Trait> {
         /// The anchor being posted was already created in a previous block.
         AnchorExists,
+        /// Signature verification failed while posting a DID-signed anchor.
+        InvalidSig,
     }
 }
 
@@ -29,6 +57,8 @@ decl_storage! {
         // merkle-trie balancing effect as using a hash-prefix map.
         Anchors: map hasher(identity) <T as system::Config>::Hash =>
             Option<<T as system::Config>::BlockNumber>;
+        // The DID that signed the anchor, only present for anchors posted with `deploy_signed`.
+        AnchorAuthors: map hasher(identity) <T as system::Config>::Hash => Option<Did>;
     }
 }
 
@@ -41,6 +71,8 @@ decl_event! {
     {
         /// A new permanent anchor was posted.
         AnchorDeployed(Hash, AccountId, BlockNumber),
+        /// A new permanent anchor was posted on behalf of a DID.
+        AnchorDeployedByDid(Hash, Did, AccountId, BlockNumber),
     }
 }
 
@@ -56,6 +88,16 @@ decl_module! {
         ) -> DispatchResult {
             Module::<T>::deploy_(origin, dat)
         }
+
+        /// Drop a permanent anchor signed by a DID. The DID is stored alongside the anchor.
+        #[weight = T::DbWeight::get().reads_writes(2, 2) + signature.weight()]
+        pub fn deploy_signed(
+            origin,
+            anchor: DidAnchor,
+            signature: DidSignature,
+        ) -> DispatchResult {
+            Module::<T>::deploy_signed_(origin, anchor, signature)
+        }
     }
 }
 
@@ -74,6 +116,41 @@ impl<T: Trait> Module<T> {
 
         Ok(())
     }
+
+    fn deploy_signed_(
+        origin: <T as system::Config>::Origin,
+        anchor: DidAnchor,
+        signature: DidSignature,
+    ) -> DispatchResult {
+        let acct = ensure_signed(origin)?;
+
+        // check
+        let h = <T as system::Config>::Hashing::hash(&anchor.data);
+        ensure!(Anchors::<T>::get(&h).is_none(), Error::<T>::AnchorExists);
+        let payload = crate::StateChange::DidAnchor(anchor.clone()).encode();
+        let valid = did::Module::<T>::verify_sig_from_did(&signature, &payload, &anchor.author)?;
+        ensure!(valid, Error::<T>::InvalidSig);
+
+        // execute
+        let bn = <system::Module<T>>::block_number();
+        Anchors::<T>::insert(&h, &bn);
+        AnchorAuthors::<T>::insert(&h, &anchor.author);
+        Self::deposit_event(Event::<T>::AnchorDeployedByDid(h, anchor.author, acct, bn));
+
+        Ok(())
+    }
+
+    /// Return the block number in which the anchor identified by `query` was posted along with
+    /// the DID that signed it, if the anchor exists. The DID is `None` for unsigned anchors.
+    pub fn lookup(
+        query: AnchorQuery<<T as system::Config>::Hash>,
+    ) -> Option<(T::BlockNumber, Option<Did>)> {
+        let h = match query {
+            AnchorQuery::Data(dat) => <T as system::Config>::Hashing::hash(&dat),
+            AnchorQuery::Hash(h) => h,
+        };
+        Anchors::<T>::get(&h).map(|bn| (bn, AnchorAuthors::<T>::get(&h)))
+    }
 }
 
 #[cfg(test)]
@@ -104,6 +181,84 @@ mod tests {
         });
     }
 
+    #[test]
+    fn deploy_and_lookup() {
+        ext().execute_with(|| {
+            let bs = random_bytes(32);
+            let h = <Test as system::Config>::Hashing::hash(&bs);
+            assert_eq!(Mod::lookup(AnchorQuery::Data(bs.clone())), None);
+            assert_eq!(Mod::lookup(AnchorQuery::Hash(h)), None);
+            Mod::deploy(Origin::signed(ABBA), bs.clone()).unwrap();
+            let bn = <system::Module<Test>>::block_number();
+            assert_eq!(Mod::lookup(AnchorQuery::Data(bs)), Some((bn, None)));
+            assert_eq!(Mod::lookup(AnchorQuery::Hash(h)), Some((bn, None)));
+            // The hash of the anchored data is not itself anchored.
+            assert_eq!(Mod::lookup(AnchorQuery::Data(h.as_bytes().to_vec())), None);
+        });
+    }
+
+    #[test]
+    fn deploy_signed_and_lookup() {
+        ext().execute_with(|| {
+            let (author, author_kp) = newdid();
+            let anchor = DidAnchor {
+                author,
+                data: random_bytes(32),
+            };
+            let h = <Test as system::Config>::Hashing::hash(&anchor.data);
+            let sig = sign(&crate::StateChange::DidAnchor(anchor.clone()), &author_kp);
+            Mod::deploy_signed(Origin::signed(ABBA), anchor.clone(), sig).unwrap();
+            let bn = <system::Module<Test>>::block_number();
+            assert_eq!(AnchorAuthors::<Test>::get(h), Some(author));
+            assert_eq!(Mod::lookup(AnchorQuery::Hash(h)), Some((bn, Some(author))));
+            assert_eq!(
+                &anchor_events(),
+                &[Event::<Test>::AnchorDeployedByDid(h, author, ABBA, bn)]
+            );
+
+            // The same data can't be anchored again, signed or not.
+            let sig = sign(&crate::StateChange::DidAnchor(anchor.clone()), &author_kp);
+            let err = Mod::deploy_signed(Origin::signed(ABBA), anchor.clone(), sig).unwrap_err();
+            assert_eq!(err, Error::<Test>::AnchorExists.into());
+            let err = Mod::deploy(Origin::signed(ABBA), anchor.data).unwrap_err();
+            assert_eq!(err, Error::<Test>::AnchorExists.into());
+        });
+    }
+
+    #[test]
+    fn deploy_signed_invalid_sig() {
+        ext().execute_with(|| {
+            // Signature by a key that doesn't control the author DID
+            let (author, _) = newdid();
+            let (_, other_kp) = newdid();
+            let anchor = DidAnchor {
+                author,
+                data: random_bytes(32),
+            };
+            let h = <Test as system::Config>::Hashing::hash(&anchor.data);
+            let sig = sign(&crate::StateChange::DidAnchor(anchor.clone()), &other_kp);
+            let err = Mod::deploy_signed(Origin::signed(ABBA), anchor, sig).unwrap_err();
+            assert_eq!(err, Error::<Test>::InvalidSig.into());
+            assert!(Anchors::<Test>::get(h).is_none());
+            assert!(AnchorAuthors::<Test>::get(h).is_none());
+
+            // Signature over a different state change
+            let (author, author_kp) = newdid();
+            let anchor = DidAnchor {
+                author,
+                data: random_bytes(32),
+            };
+            let blob = crate::blob::Blob {
+                id: rand::random(),
+                blob: anchor.data.clone(),
+                author,
+            };
+            let sig = sign(&crate::StateChange::Blob(blob), &author_kp);
+            let err = Mod::deploy_signed(Origin::signed(ABBA), anchor, sig).unwrap_err();
+            assert_eq!(err, Error::<Test>::InvalidSig.into());
+        });
+    }
+
     #[test]
     fn deploy_and_observe_event() {
         ext().execute_with(|| {
@@ -1,23 +1,59 @@
 //! This module allows DIDs to publically attests to arbirary (and arbitrarily large) RDF
 //! claimgraphs. These attestations are not stored on-chain; rather, the attester chooses a storage
 //! method by specifying an Iri.
+//!
+//! A DID can keep separate attestations for separate topics, each with its own priority sequence.
+//! Only the latest attestation of a DID on a topic is authoritative but a bounded history of the
+//! attestations accepted for each DID and topic is kept so that past claims can be audited.
+//! `set_claim` sets the attestation for the `DEFAULT_TOPIC`.
+//!
+//! An Iri must be utf-8, use one of the schemes in `AllowedIriSchemes` and be no longer than
+//! `MaxIriSize` bytes. `data:` Iris carry the claimgraph inline and are bounded by
+//! `MaxDataIriSize` instead.
 
 use crate::did::{self, Did, DidSignature};
 use crate::StateChange;
 use alloc::vec::Vec;
 use codec::{Decode, Encode};
 use frame_support::{
-    decl_error, decl_module, decl_storage, dispatch::DispatchResult, ensure, traits::Get,
+    decl_error, decl_event, decl_module, decl_storage,
+    dispatch::DispatchResult,
+    ensure,
+    storage::migration::{get_storage_value, storage_key_iter, take_storage_value},
+    traits::Get,
     weights::Weight,
+    Blake2_128Concat, StorageHasher,
 };
 use frame_system::{self as system, ensure_signed};
+use sp_runtime::{traits::Bounded, RuntimeDebug};
+use sp_std::str;
 
 pub type Iri = Vec<u8>;
 
+/// Name of the slot an attestation is made in, allowing a DID to attest to unrelated claimgraphs
+/// independently.
+pub type Topic = [u8; 32];
+
+/// The topic used by `set_claim` and the one attestations made before topics existed were moved to.
+pub const DEFAULT_TOPIC: Topic = [0; 32];
+
+/// Maximum number of attestations moved to the `DEFAULT_TOPIC` in a block while migrating.
+const MIGRATION_BATCH_SIZE: usize = 1000;
+
 pub trait Trait: system::Config + did::Trait {
+    type Event: From<Event> + Into<<Self as system::Config>::Event>;
     /// The cost charged by the network to store a single byte in chain-state for the life of the
     /// chain.
     type StorageWeight: Get<Weight>;
+    /// Maximum number of past attestations kept per DID and topic. When exceeded, the oldest
+    /// attestation is dropped from the history.
+    type MaxAttestationHistory: Get<u32>;
+    /// Iris longer than this (in bytes) will not be accepted, `data:` Iris excepted.
+    type MaxIriSize: Get<u32>;
+    /// `data:` Iris longer than this (in bytes) will not be accepted.
+    type MaxDataIriSize: Get<u32>;
+    /// Schemes an Iri may use, compared case-insensitively. E.g. `b"ipfs"`, `b"https"`.
+    type AllowedIriSchemes: Get<&'static [&'static [u8]]>;
 }
 
 #[derive(Encode, Decode, Clone, PartialEq, Debug, Default)]
@@ -28,6 +64,31 @@ pub struct Attestation {
     iri: Option<Iri>,
 }
 
+/// An attestation accepted by the chain along with the block it was accepted in.
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttestationRecord<BlockNumber> {
+    #[codec(compact)]
+    pub priority: u64,
+    pub iri: Option<Iri>,
+    pub block: BlockNumber,
+}
+
+decl_event!(
+    pub enum Event {
+        /// An attestation was set for the DID on the topic with the given priority.
+        AttestationSet(Did, Topic, u64),
+    }
+);
+
+// This value is used by the `on_runtime_upgrade` logic to determine whether we run
+// storage migration logic.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+    /// Attestations are keyed by DID and topic.
+    V1,
+}
+
 decl_error! {
     /// Error for the attest module.
     pub enum Error for Module<T: Trait> {
@@ -40,7 +101,19 @@ decl_error! {
         /// error.
         PriorityTooLow,
         /// Signature verification failed while adding blob
-        InvalidSig
+        InvalidSig,
+        /// The Iri is not valid utf-8.
+        IriNotUtf8,
+        /// The Iri does not start with a scheme followed by a colon.
+        IriWithoutScheme,
+        /// The scheme of the Iri is not in `AllowedIriSchemes`.
+        IriSchemeNotAllowed,
+        /// The Iri is longer than `MaxIriSize`.
+        IriTooBig,
+        /// The `data:` Iri is longer than `MaxDataIriSize`.
+        DataIriTooBig,
+        /// The `data:` Iri does not separate the media type from the data with a comma.
+        MalformedDataIri,
     }
 }
 
@@ -65,16 +138,36 @@ decl_storage! {
         // An attestation on chain with iri set to None is sematically meaningless. Setting the
         // iri to None is equivalent to attesting to the empty claimgraph.
         //
-        // When Attestations::get(did).iri == Some(dat) and dat is a valid utf-8 Iri:
+        // When TopicAttestations::get(did, topic).iri == Some(dat) and dat is a valid utf-8 Iri:
         // `[did dock:attestsDocumentContents dat]`.
-        Attestations: map hasher(blake2_128_concat) Did => Attestation;
+        //
+        // Priorities of different topics are independent of each other.
+        TopicAttestations: double_map
+            hasher(blake2_128_concat) Did, hasher(blake2_128_concat) Topic
+            => Attestation;
+
+        // Past attestations of each DID and topic, oldest first, including the current one. Holds
+        // at most `MaxAttestationHistory` records.
+        TopicAttestationHistory get(fn attestation_history): double_map
+            hasher(blake2_128_concat) Did, hasher(blake2_128_concat) Topic
+            => Vec<AttestationRecord<T::BlockNumber>>;
+
+        // Storage version of the module. `None` means some attestations may still be stored in the
+        // map keyed by DID only ("Attestations"). They are moved to the `DEFAULT_TOPIC` a batch at
+        // a time at the start of each block and read from the old map until then.
+        //
+        // New networks start with last version.
+        StorageVersion build(|_| Some(Releases::V1)): Option<Releases>;
     }
 }
 
 decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        fn deposit_event() = default;
+
+        // The iri is stored as the current attestation and the whole history is rewritten.
         #[weight = {
-            T::DbWeight::get().reads_writes(2, 1)
+            T::DbWeight::get().reads_writes(5, 3)
                 + signature.weight()
                 + attests
                     .iri
@@ -82,6 +175,7 @@ decl_module! {
                     .map(|iri| iri.len())
                     .unwrap_or_default() as Weight
                     * T::StorageWeight::get()
+                + Module::<T>::max_history_weight()
         }]
         fn set_claim(
             origin,
@@ -89,32 +183,177 @@ decl_module! {
             attests: Attestation,
             signature: DidSignature,
         ) -> DispatchResult {
-            Module::<T>::set_claim_(origin, attester, attests, signature)
+            ensure_signed(origin)?;
+            let payload = StateChange::Attestation((attester, attests.clone())).encode();
+            Module::<T>::set_claim_(attester, DEFAULT_TOPIC, attests, &payload, signature)
+        }
+
+        /// Set the attestation of the DID on the given topic.
+        #[weight = {
+            T::DbWeight::get().reads_writes(5, 3)
+                + signature.weight()
+                + attests
+                    .iri
+                    .as_ref()
+                    .map(|iri| iri.len())
+                    .unwrap_or_default() as Weight
+                    * T::StorageWeight::get()
+                + Module::<T>::max_history_weight()
+        }]
+        fn set_topic_claim(
+            origin,
+            attester: Did,
+            topic: Topic,
+            attests: Attestation,
+            signature: DidSignature,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+            let payload =
+                StateChange::TopicAttestation((attester, topic, attests.clone())).encode();
+            Module::<T>::set_claim_(attester, topic, attests, &payload, signature)
+        }
+
+        fn on_initialize() -> Weight {
+            if StorageVersion::get().is_none() {
+                Self::migrate_to_topics(MIGRATION_BATCH_SIZE) + T::DbWeight::get().reads(1)
+            } else {
+                T::DbWeight::get().reads(1)
+            }
         }
     }
 }
 
 impl<T: Trait> Module<T> {
+    /// Set the attestation after checking the signature over `payload` and the priority.
     fn set_claim_(
-        origin: <T as system::Config>::Origin,
         attester: Did,
+        topic: Topic,
         attests: Attestation,
+        payload: &[u8],
         signature: DidSignature,
     ) -> DispatchResult {
-        ensure_signed(origin)?;
-
         // check
-        let payload = StateChange::Attestation((attester, attests.clone())).encode();
-        let valid = did::Module::<T>::verify_sig_from_did(&signature, &payload, &attester)?;
+        if let Some(iri) = &attests.iri {
+            Self::validate_iri(iri)?;
+        }
+        let valid = did::Module::<T>::verify_sig_from_did(&signature, payload, &attester)?;
         ensure!(valid, Error::<T>::InvalidSig);
-        let prev = Attestations::get(&attester);
+        let prev = Self::attestation(&attester, &topic);
         ensure!(prev.priority < attests.priority, Error::<T>::PriorityTooLow);
 
         // execute
-        Attestations::insert(&attester, &attests);
+        let record = AttestationRecord {
+            priority: attests.priority,
+            iri: attests.iri.clone(),
+            block: <system::Module<T>>::block_number(),
+        };
+        TopicAttestationHistory::<T>::mutate(&attester, &topic, |history| {
+            history.push(record);
+            let max = T::MaxAttestationHistory::get() as usize;
+            if history.len() > max {
+                history.drain(..history.len() - max);
+            }
+        });
+        if topic == DEFAULT_TOPIC && StorageVersion::get().is_none() {
+            // The new attestation supersedes any attestation not migrated yet.
+            take_storage_value::<Attestation>(b"Blob", b"Attestations", &Self::old_key(&attester));
+        }
+        TopicAttestations::insert(&attester, &topic, &attests);
+        Self::deposit_event(Event::AttestationSet(attester, topic, attests.priority));
 
         Ok(())
     }
+
+    /// Check that the Iri is utf-8, uses an allowed scheme and is within the size bound for its
+    /// scheme.
+    fn validate_iri(iri: &[u8]) -> DispatchResult {
+        let iri = str::from_utf8(iri).map_err(|_| Error::<T>::IriNotUtf8)?;
+        let colon = iri.find(':').ok_or(Error::<T>::IriWithoutScheme)?;
+        let scheme = &iri[..colon];
+        // scheme = ALPHA *( ALPHA / DIGIT / "+" / "-" / "." ) as per RFC 3986
+        let mut chars = scheme.chars();
+        ensure!(
+            chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.'),
+            Error::<T>::IriWithoutScheme
+        );
+        ensure!(
+            T::AllowedIriSchemes::get()
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(scheme.as_bytes())),
+            Error::<T>::IriSchemeNotAllowed
+        );
+
+        if scheme.eq_ignore_ascii_case("data") {
+            ensure!(
+                iri.len() <= T::MaxDataIriSize::get() as usize,
+                Error::<T>::DataIriTooBig
+            );
+            ensure!(iri[colon..].contains(','), Error::<T>::MalformedDataIri);
+        } else {
+            ensure!(
+                iri.len() <= T::MaxIriSize::get() as usize,
+                Error::<T>::IriTooBig
+            );
+        }
+        Ok(())
+    }
+
+    /// Weight of writing the largest possible history of a DID and topic, i.e.
+    /// `MaxAttestationHistory` records whose Iris are as long as allowed.
+    fn max_history_weight() -> Weight {
+        let max_iri = T::MaxIriSize::get().max(T::MaxDataIriSize::get()) as usize;
+        let max_record = AttestationRecord {
+            priority: u64::MAX,
+            iri: Some(Vec::new()),
+            block: T::BlockNumber::max_value(),
+        }
+        .encode()
+        .len()
+            + max_iri
+            + codec::Compact(max_iri as u32).encode().len();
+        let max_history = T::MaxAttestationHistory::get() as usize * max_record
+            + codec::Compact(T::MaxAttestationHistory::get())
+                .encode()
+                .len();
+        max_history as Weight * T::StorageWeight::get()
+    }
+
+    /// Return the current attestation of the DID on the topic.
+    pub fn attestation(did: &Did, topic: &Topic) -> Attestation {
+        if *topic == DEFAULT_TOPIC && StorageVersion::get().is_none() {
+            if let Some(att) =
+                get_storage_value::<Attestation>(b"Blob", b"Attestations", &Self::old_key(did))
+            {
+                return att;
+            }
+        }
+        TopicAttestations::get(did, topic)
+    }
+
+    /// Key of the DID in the map of attestations keyed by DID only.
+    fn old_key(did: &Did) -> Vec<u8> {
+        Blake2_128Concat::hash(did)
+    }
+
+    /// Move at most `limit` attestations from the map keyed by DID only to the `DEFAULT_TOPIC`.
+    /// Once the map is empty, the storage version is set.
+    fn migrate_to_topics(limit: usize) -> Weight {
+        let mut moved = 0;
+        for (did, att) in
+            storage_key_iter::<Did, Attestation, Blake2_128Concat>(b"Blob", b"Attestations")
+                .drain()
+                .take(limit)
+        {
+            TopicAttestations::insert(&did, &DEFAULT_TOPIC, att);
+            moved += 1;
+        }
+        if moved < limit {
+            StorageVersion::put(Releases::V1);
+        }
+        // Each moved entry is read, removed and written under the new key.
+        T::DbWeight::get().reads_writes(moved as u64 + 1, 2 * moved as u64 + 1)
+    }
 }
 
 #[cfg(test)]
@@ -242,7 +481,7 @@ mod test {
                 &did,
                 &Attestation {
                     priority: 2,
-                    iri: Some(vec![0]),
+                    iri: Some(b"ipfs://a".to_vec()),
                 },
                 &kp,
             )
@@ -252,7 +491,7 @@ mod test {
                     &did,
                     &Attestation {
                         priority: 2,
-                        iri: Some(vec![0, 2, 3]),
+                        iri: Some(b"ipfs://b".to_vec()),
                     },
                     &kp,
                 )
@@ -280,7 +519,7 @@ mod test {
                 );
             }
             assert_eq!(
-                Attestations::get(did).priority,
+                Mod::attestation(&did, &DEFAULT_TOPIC).priority,
                 prios.iter().max().unwrap().clone()
             );
         });
@@ -320,7 +559,7 @@ mod test {
         ext().execute_with(|| {
             let (did, kp) = newdid();
             assert_eq!(
-                Attestations::get(did),
+                Mod::attestation(&did, &DEFAULT_TOPIC),
                 Attestation {
                     priority: 0,
                     iri: None,
@@ -330,16 +569,16 @@ mod test {
                 &did,
                 &Attestation {
                     priority: 1,
-                    iri: Some(vec![0, 1, 2]),
+                    iri: Some(b"https://example.com/claims".to_vec()),
                 },
                 &kp,
             )
             .unwrap();
             assert_eq!(
-                Attestations::get(did),
+                Mod::attestation(&did, &DEFAULT_TOPIC),
                 Attestation {
                     priority: 1,
-                    iri: Some(vec![0, 1, 2]),
+                    iri: Some(b"https://example.com/claims".to_vec()),
                 }
             );
         });
@@ -364,6 +603,380 @@ mod test {
         });
     }
 
+    /// Every accepted attestation is recorded in the history with the block it was accepted in.
+    #[test]
+    fn history_records_accepted() {
+        ext().execute_with(|| {
+            let (did, kp) = newdid();
+            assert_eq!(Mod::attestation_history(did, DEFAULT_TOPIC), vec![]);
+            set_claim(
+                &did,
+                &Attestation {
+                    priority: 1,
+                    iri: Some(b"ipfs://1".to_vec()),
+                },
+                &kp,
+            )
+            .unwrap();
+            system::Module::<Test>::set_block_number(2);
+            // rejected attestations are not recorded
+            set_claim(
+                &did,
+                &Attestation {
+                    priority: 1,
+                    iri: Some(b"ipfs://2".to_vec()),
+                },
+                &kp,
+            )
+            .unwrap_err();
+            set_claim(
+                &did,
+                &Attestation {
+                    priority: 3,
+                    iri: None,
+                },
+                &kp,
+            )
+            .unwrap();
+            assert_eq!(
+                Mod::attestation_history(did, DEFAULT_TOPIC),
+                vec![
+                    AttestationRecord {
+                        priority: 1,
+                        iri: Some(b"ipfs://1".to_vec()),
+                        block: 1,
+                    },
+                    AttestationRecord {
+                        priority: 3,
+                        iri: None,
+                        block: 2,
+                    },
+                ]
+            );
+            assert_eq!(
+                attest_events(),
+                vec![
+                    Event::AttestationSet(did, DEFAULT_TOPIC, 1),
+                    Event::AttestationSet(did, DEFAULT_TOPIC, 3)
+                ]
+            );
+        });
+    }
+
+    /// The history is bounded, the oldest records are dropped first.
+    #[test]
+    fn history_bounded() {
+        ext().execute_with(|| {
+            let (did, kp) = newdid();
+            let max = <Test as Trait>::MaxAttestationHistory::get() as u64;
+            for priority in 1..=(max + 3) {
+                set_claim(
+                    &did,
+                    &Attestation {
+                        priority,
+                        iri: None,
+                    },
+                    &kp,
+                )
+                .unwrap();
+            }
+            let prios: Vec<u64> = Mod::attestation_history(did, DEFAULT_TOPIC)
+                .iter()
+                .map(|r| r.priority)
+                .collect();
+            assert_eq!(prios, (4..=(max + 3)).collect::<Vec<_>>());
+            assert_eq!(Mod::attestation(&did, &DEFAULT_TOPIC).priority, max + 3);
+        });
+    }
+
+    /// The weight of an attestation covers rewriting a full history of the largest Iris.
+    #[test]
+    fn weight_covers_history() {
+        use frame_support::weights::GetDispatchInfo;
+
+        ext().execute_with(|| {
+            let (did, kp) = newdid();
+            let max = <Test as Trait>::MaxAttestationHistory::get() as u64;
+            let mut iri = b"data:,".to_vec();
+            iri.resize(<Test as Trait>::MaxDataIriSize::get() as usize, b'a');
+            let att = Attestation {
+                priority: max,
+                iri: Some(iri),
+            };
+            for priority in 1..=max {
+                set_claim(
+                    &did,
+                    &Attestation {
+                        priority,
+                        ..att.clone()
+                    },
+                    &kp,
+                )
+                .unwrap();
+            }
+            let history_size =
+                TopicAttestationHistory::<Test>::get(&did, &DEFAULT_TOPIC).encoded_size();
+            assert!(
+                history_size as Weight * <Test as Trait>::StorageWeight::get()
+                    <= Mod::max_history_weight()
+            );
+            let sig = sign(&StateChange::Attestation((did, att.clone())), &kp);
+            let weight = Call::<Test>::set_claim(did, att, sig)
+                .get_dispatch_info()
+                .weight;
+            assert!(weight > Mod::max_history_weight());
+        });
+    }
+
+    /// Iris are checked for encoding, scheme and size before being accepted.
+    #[test]
+    fn iri_validation() {
+        ext().execute_with(|| {
+            let (did, kp) = newdid();
+            let max = <Test as Trait>::MaxIriSize::get() as usize;
+            let max_data = <Test as Trait>::MaxDataIriSize::get() as usize;
+            let long = |prefix: &str, len: usize| {
+                let mut iri = prefix.as_bytes().to_vec();
+                iri.resize(len, b'a');
+                iri
+            };
+            let mut priority = 0;
+            let mut check = |iri: Vec<u8>| {
+                priority += 1;
+                set_claim(
+                    &did,
+                    &Attestation {
+                        priority,
+                        iri: Some(iri),
+                    },
+                    &kp,
+                )
+            };
+
+            for iri in vec![
+                b"ipfs://QmQqzMTavQgT4f4T5v6PWBp7XNKtoPmC9jvn12WPT3gkSE".to_vec(),
+                b"https://example.com/claims.ttl".to_vec(),
+                b"did:dock:5CEdyZkZnALDdCAp7crTRiaCq6KViprTM6kHUQCD8X6VqGPW".to_vec(),
+                b"HTTPS://example.com".to_vec(),
+                b"data:,hello".to_vec(),
+                b"data:text/turtle;base64,PGE+IDxiPiA8Yz4gLg==".to_vec(),
+                long("https://", max),
+                long("data:,", max_data),
+            ] {
+                check(iri).unwrap();
+            }
+
+            for (iri, err) in vec![
+                (vec![0xff, 0xfe], Er::IriNotUtf8),
+                (b"no scheme".to_vec(), Er::IriWithoutScheme),
+                (b":empty".to_vec(), Er::IriWithoutScheme),
+                (b"1ipfs://a".to_vec(), Er::IriWithoutScheme),
+                (b"ftp://example.com".to_vec(), Er::IriSchemeNotAllowed),
+                (b"http://example.com".to_vec(), Er::IriSchemeNotAllowed),
+                (long("https://", max + 1), Er::IriTooBig),
+                (long("data:,", max_data + 1), Er::DataIriTooBig),
+                (b"data:text/plain".to_vec(), Er::MalformedDataIri),
+            ] {
+                assert_eq!(check(iri).unwrap_err(), err.into());
+            }
+
+            // the same checks apply to named topics
+            let err = set_topic_claim(
+                &did,
+                &[1; 32],
+                &Attestation {
+                    priority: 1,
+                    iri: Some(b"ftp://example.com".to_vec()),
+                },
+                &kp,
+            )
+            .unwrap_err();
+            assert_eq!(err, Er::IriSchemeNotAllowed.into());
+        });
+    }
+
+    /// Topics have independent priority sequences.
+    #[test]
+    fn topics_independent() {
+        ext().execute_with(|| {
+            let (did, kp) = newdid();
+            let topic: Topic = [1; 32];
+            set_claim(
+                &did,
+                &Attestation {
+                    priority: 5,
+                    iri: Some(b"ipfs://5".to_vec()),
+                },
+                &kp,
+            )
+            .unwrap();
+            // a lower priority is accepted on another topic
+            set_topic_claim(
+                &did,
+                &topic,
+                &Attestation {
+                    priority: 1,
+                    iri: Some(b"ipfs://1".to_vec()),
+                },
+                &kp,
+            )
+            .unwrap();
+            let err = set_topic_claim(
+                &did,
+                &topic,
+                &Attestation {
+                    priority: 1,
+                    iri: None,
+                },
+                &kp,
+            )
+            .unwrap_err();
+            assert_eq!(err, Er::PriorityTooLow.into());
+            assert_eq!(
+                Mod::attestation(&did, &DEFAULT_TOPIC),
+                Attestation {
+                    priority: 5,
+                    iri: Some(b"ipfs://5".to_vec()),
+                }
+            );
+            assert_eq!(
+                Mod::attestation(&did, &topic),
+                Attestation {
+                    priority: 1,
+                    iri: Some(b"ipfs://1".to_vec()),
+                }
+            );
+            assert_eq!(Mod::attestation_history(did, DEFAULT_TOPIC).len(), 1);
+            assert_eq!(Mod::attestation_history(did, topic).len(), 1);
+        });
+    }
+
+    /// A signature over an attestation on one topic is not valid for another topic.
+    #[test]
+    fn topic_sig_not_replayable() {
+        ext().execute_with(|| {
+            let (did, kp) = newdid();
+            let att = Attestation {
+                priority: 1,
+                iri: None,
+            };
+            let sig = sign(
+                &StateChange::TopicAttestation((did, [1; 32], att.clone())),
+                &kp,
+            );
+            let err = Mod::set_topic_claim(Origin::signed(0), did, [2; 32], att.clone(), sig)
+                .unwrap_err();
+            assert_eq!(err, Er::InvalidSig.into());
+
+            // a signature for the default topic made through `set_claim` is not accepted either
+            let sig = sign(&StateChange::Attestation((did, att.clone())), &kp);
+            let err =
+                Mod::set_topic_claim(Origin::signed(0), did, DEFAULT_TOPIC, att, sig).unwrap_err();
+            assert_eq!(err, Er::InvalidSig.into());
+        });
+    }
+
+    /// Attestations keyed by DID only are moved to the default topic a batch at a time and read
+    /// from the old map until then.
+    #[test]
+    fn migrate_to_topics() {
+        use frame_support::{storage::migration::put_storage_value, traits::OnInitialize};
+        use frame_support::{StorageHasher, Twox128};
+
+        ext().execute_with(|| {
+            StorageVersion::kill();
+            let (dida, kpa) = newdid();
+            let (didb, didc) = (rand::random::<Did>(), rand::random::<Did>());
+            let atta = Attestation {
+                priority: 2,
+                iri: Some(vec![1, 2]),
+            };
+            let attb = Attestation {
+                priority: 7,
+                iri: None,
+            };
+            for (did, att) in &[(dida, &atta), (didb, &attb), (didc, &attb)] {
+                put_storage_value(
+                    b"Blob",
+                    b"Attestations",
+                    &Blake2_128Concat::hash(did),
+                    (*att).clone(),
+                );
+            }
+
+            // attestations not migrated yet are still in effect
+            assert_eq!(Mod::attestation(&dida, &DEFAULT_TOPIC), atta);
+            let err = set_claim(
+                &dida,
+                &Attestation {
+                    priority: 2,
+                    iri: None,
+                },
+                &kpa,
+            )
+            .unwrap_err();
+            assert_eq!(err, Er::PriorityTooLow.into());
+            let newa = Attestation {
+                priority: 3,
+                iri: None,
+            };
+            set_claim(&dida, &newa, &kpa).unwrap();
+
+            Mod::migrate_to_topics(1);
+            assert_eq!(StorageVersion::get(), None);
+            Mod::on_initialize(2);
+            assert_eq!(StorageVersion::get(), Some(Releases::V1));
+
+            assert_eq!(Mod::attestation(&dida, &DEFAULT_TOPIC), newa);
+            assert_eq!(Mod::attestation(&didb, &DEFAULT_TOPIC), attb);
+            assert_eq!(Mod::attestation(&didc, &DEFAULT_TOPIC), attb);
+            assert_eq!(Mod::attestation_history(didb, DEFAULT_TOPIC), vec![]);
+            // old entries are gone
+            let mut old_prefix = Twox128::hash(b"Blob").to_vec();
+            old_prefix.extend_from_slice(&Twox128::hash(b"Attestations"));
+            assert_eq!(
+                sp_io::storage::next_key(&old_prefix).filter(|k| k.starts_with(&old_prefix)),
+                None
+            );
+
+            // nothing is left to migrate
+            assert_eq!(
+                Mod::on_initialize(3),
+                <Test as system::Config>::DbWeight::get().reads(1)
+            );
+            assert_eq!(Mod::attestation(&dida, &DEFAULT_TOPIC), newa);
+        });
+    }
+
+    fn attest_events() -> Vec<Event> {
+        system::Module::<Test>::events()
+            .iter()
+            .filter_map(|event_record| match &event_record.event {
+                TestEvent::Attest(e) => Some(e.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// helper
+    fn set_topic_claim(
+        claimer: &did::Did,
+        topic: &Topic,
+        att: &Attestation,
+        kp: &sr25519::Pair,
+    ) -> DispatchResult {
+        Mod::set_topic_claim(
+            Origin::signed(0),
+            *claimer,
+            *topic,
+            att.clone(),
+            sign(
+                &StateChange::TopicAttestation((*claimer, *topic, att.clone())),
+                kp,
+            ),
+        )
+    }
+
     /// helper
     fn set_claim(claimer: &did::Did, att: &Attestation, kp: &sr25519::Pair) -> DispatchResult {
         Mod::set_claim(
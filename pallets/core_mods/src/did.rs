@@ -220,6 +220,16 @@ impl KeyDetail {
             public_key,
         }
     }
+
+    /// The controller of the DID
+    pub fn controller(&self) -> &Did {
+        &self.controller
+    }
+
+    /// The public key of the DID
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
 }
 
 /// This struct is passed as an argument while updating the key for a DID.
@@ -18,6 +18,11 @@ pub enum StateChange {
     Blob(blob::Blob),
     MasterVote(master::Payload),
     Attestation((did::Did, attest::Attestation)),
+    DidAnchor(anchor::DidAnchor),
+    TopicAttestation((did::Did, attest::Topic, attest::Attestation)),
+    MasterCancel(master::CancelPayload),
+    MasterVeto(master::Payload),
+    MasterHandover(master::Payload),
 }
 
 // This should be same as the type defined in runtime/src/lib.rs. Less than ideal shortcut as this module shouldn't
@@ -32,6 +37,7 @@ pub mod blob;
 pub mod did;
 pub mod master;
 pub mod revoke;
+pub mod runtime_api;
 
 #[cfg(test)]
 mod test_common;
@@ -52,15 +52,34 @@
 //! are submitted in a single transaction. If enough valid votes endorse the proposal, the proposal
 //! is run as root. If the running the proposal as root succeeds, a new round of voting is started.
 //!
-//! Each member of Master is idenitified by their dock DID.
+//! Each member of Master is idenitified by their dock DID. Members can have different voting
+//! weights and a proposal needs votes whose weights add up to the vote requirement. A member can
+//! also be restricted to voting on certain Calls only, see `MemberDetail`.
 //!
 //! This module implement partial replay protection to prevent unauthorized resubmission of votes
 //! from previous rounds.
+//!
+//! Votes can also be collected on-chain. A member `propose`s a `runtime::Call` for the current
+//! round, other members `approve` it over time and the proposal is run as root as soon as it has
+//! enough votes. The votes are the same signatures that would be submitted to `execute`. A pending
+//! proposal expires after `ProposalLifetime` blocks or when the round ends, whichever comes first,
+//! and can be cancelled by its proposer.
+//!
+//! Sensitive calls can be timelocked with `set_timelock`. Instead of running right away, an
+//! authorized proposal matching a timelocked call prefix is scheduled through the Scheduler and
+//! runs once the delay has passed. Until then, members can veto it with enough votes over
+//! `StateChange::MasterVeto`.
+//!
+//! Membership can be changed incrementally with `add_member`, `remove_member` and `set_threshold`,
+//! which keep the vote requirement satisfiable and only accept DIDs that exist. A whole new
+//! membership is better installed with a handover: `begin_handover` stores the new membership and
+//! it only takes effect once enough of the new members confirm it with `confirm_handover`, so a
+//! membership that can't vote is never installed.
 
 use crate::{
     did::{Did, DidSignature},
     revoke::get_weight_for_pauth,
-    StateChange,
+    BlockNumber, StateChange,
 };
 use alloc::{
     boxed::Box,
@@ -76,11 +95,19 @@ use frame_support::{
         DispatchError, DispatchErrorWithPostInfo, DispatchResult, DispatchResultWithPostInfo,
     },
     ensure,
-    traits::{Get, UnfilteredDispatchable},
+    storage::{with_transaction, TransactionOutcome},
+    traits::{
+        schedule::{DispatchTime, Named as ScheduleNamed, Priority},
+        Get, IsSubType, UnfilteredDispatchable,
+    },
     weights::{GetDispatchInfo, Pays, RuntimeDbWeight, Weight},
     Parameter,
 };
 use frame_system::{self as system, ensure_root, ensure_signed};
+use sp_runtime::{
+    traits::{Hash, One, SaturatedConversion, Zero},
+    RuntimeDebug,
+};
 
 #[derive(Encode, Decode, Clone, PartialEq, Debug)]
 pub struct Payload {
@@ -90,25 +117,162 @@ pub struct Payload {
     round_no: u64,
 }
 
+/// Signed by the proposer of a pending proposal to cancel it.
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct CancelPayload {
+    /// The serialized Call of the pending proposal.
+    proposal: Vec<u8>,
+    /// The round the proposal is for.
+    round_no: u64,
+    /// The block the proposal was made in. Tells apart proposals of the same Call made in the
+    /// same round so that a cancellation can't be replayed against a later one.
+    proposed_at: BlockNumber,
+}
+
 /// Proof of authorization by Master.
 pub type PMAuth = BTreeMap<Did, DidSignature>;
 
+/// A proposal whose votes are being collected on-chain.
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct PendingProposal<Call, BlockNumber> {
+    /// The Call to be run as root.
+    pub proposal: Call,
+    /// The round the votes are for. The proposal can't be executed once the round ends.
+    pub round_no: u64,
+    /// Member who made the proposal, only they can cancel it.
+    pub proposer: Did,
+    /// Votes collected so far, including the proposer's.
+    pub approvals: PMAuth,
+    /// The block the proposal was made in.
+    pub proposed_at: BlockNumber,
+    /// The proposal can't be approved anymore from this block onwards.
+    pub expires_at: BlockNumber,
+}
+
+/// A proposal that has been authorized and is waiting for its timelock to pass.
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct ScheduledProposal<Call, BlockNumber> {
+    /// The Call to be run as root.
+    pub proposal: Call,
+    /// The round the proposal was authorized in. Vetoes are signed for this round.
+    pub round_no: u64,
+    /// Members whose votes authorized the proposal.
+    pub authors: Vec<Did>,
+    /// Block the proposal is scheduled to run in.
+    pub enact_at: BlockNumber,
+    /// Weight of the proposal when it was scheduled, charged when it runs.
+    pub weight: Weight,
+}
+
+/// Outcome of running a proposal as root without keeping its changes. See `Module::dry_run`.
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+pub struct DryRun<Event> {
+    /// Result of the dispatch.
+    pub result: DispatchResult,
+    /// Weight consumed by the dispatch. The declared weight is used when the dispatch does not
+    /// report the actual one.
+    pub weight: Weight,
+    /// Events emitted by the dispatch.
+    pub events: Vec<Event>,
+}
+
+/// Prefix of a SCALE encoded Call. The first byte of an encoded `runtime::Call` is the index of the
+/// module in the runtime and the second byte is the index of the call in that module. So a
+/// single byte prefix matches all calls of a module and a two byte prefix matches a single call.
+pub type CallPrefix = Vec<u8>;
+
+#[derive(Encode, Decode, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemberDetail {
+    /// Weight of the member's vote.
+    pub weight: u64,
+    /// When set, the member can only vote on Calls whose encoding starts with one of these
+    /// prefixes. When not set, the member can vote on any Call.
+    pub allowed_calls: Option<BTreeSet<CallPrefix>>,
+}
+
+impl Default for MemberDetail {
+    fn default() -> Self {
+        MemberDetail {
+            weight: 1,
+            allowed_calls: None,
+        }
+    }
+}
+
+impl MemberDetail {
+    /// Whether the member can vote on the given encoded Call.
+    pub fn can_vote_on(&self, encoded_call: &[u8]) -> bool {
+        self.allowed_calls.as_ref().map_or(true, |allowed| {
+            allowed
+                .iter()
+                .any(|prefix| encoded_call.starts_with(prefix))
+        })
+    }
+}
+
 #[derive(Encode, Decode, Clone, PartialEq, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Membership {
-    pub members: BTreeSet<Did>,
+    #[cfg_attr(feature = "serde", serde(with = "members_as_pairs"))]
+    pub members: BTreeMap<Did, MemberDetail>,
+    /// Sum of the weights of votes needed to execute a proposal.
     pub vote_requirement: u64,
 }
 
 impl Default for Membership {
     fn default() -> Self {
         Membership {
-            members: BTreeSet::new(),
+            members: BTreeMap::new(),
             vote_requirement: 1,
         }
     }
 }
 
+impl Membership {
+    /// Sum of the weights of all members.
+    pub fn total_weight(&self) -> u64 {
+        self.members
+            .values()
+            .fold(0, |total, member| total.saturating_add(member.weight))
+    }
+}
+
+/// Members are serialized as a list of pairs as JSON only allows strings as map keys.
+#[cfg(feature = "serde")]
+mod members_as_pairs {
+    use super::{BTreeMap, Did, MemberDetail, Vec};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        members: &BTreeMap<Did, MemberDetail>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(members.iter())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<Did, MemberDetail>, D::Error> {
+        Ok(Vec::<(Did, MemberDetail)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+// This value is used by the `on_runtime_upgrade` logic to determine whether we run
+// storage migration logic.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Releases {
+    /// Members have voting weights and optional call restrictions.
+    V1,
+}
+
+/// Identifier of Master's named schedules.
+const MASTER_ID: [u8; 8] = *b"dockmstr";
+/// Priority of Master's named schedules.
+const SCHEDULE_PRIORITY: Priority = 63;
+
 // Minimum weight of Master's extrinsics. This is not based on any computation but only there to account for
 // some in-memory operations
 const MIN_WEIGHT: Weight = 10_000;
@@ -118,6 +282,25 @@ fn get_min_weight_for_execute(auth: &PMAuth, db_weights: RuntimeDbWeight) -> Wei
     MIN_WEIGHT + get_weight_for_pauth(&auth, db_weights) + db_weights.reads_writes(1, 1)
 }
 
+/// Weight for voting on a pending proposal, not counting the execution of the proposal. Considers
+/// cost of signature verification and update to the pending proposal and round no
+fn get_weight_for_vote(signature: &DidSignature, db_weights: RuntimeDbWeight) -> Weight {
+    MIN_WEIGHT + signature.weight() + db_weights.reads_writes(4, 2)
+}
+
+/// Lists the calls wrapped by a call, like the calls of a utility batch, so that timelocks apply
+/// to the wrapped calls as well.
+pub trait WrappedCalls<Call> {
+    /// The calls directly wrapped by `call`, empty if it does not wrap any.
+    fn wrapped_calls(call: &Call) -> Vec<&Call>;
+}
+
+impl<Call> WrappedCalls<Call> for () {
+    fn wrapped_calls(_call: &Call) -> Vec<&Call> {
+        Vec::new()
+    }
+}
+
 pub trait Trait: system::Config + crate::did::Trait
 where
     <Self as system::Config>::AccountId: Ord,
@@ -127,19 +310,55 @@ where
     /// The dispatchable that master may call as Root. It is possible to use another type here, but
     /// it's expected that your runtime::Call will be used.
     /// Master's call should bypass any filter.
-    type Call: Parameter + UnfilteredDispatchable<Origin = Self::Origin> + GetDispatchInfo;
+    /// Master's own calls must be convertible to it so that timelocked proposals can be scheduled.
+    type Call: Parameter
+        + UnfilteredDispatchable<Origin = Self::Origin>
+        + GetDispatchInfo
+        + From<Call<Self>>
+        + IsSubType<Call<Self>>;
+
+    /// Number of blocks a proposal stays open for votes after being proposed.
+    type ProposalLifetime: Get<Self::BlockNumber>;
+
+    /// The Scheduler used to delay timelocked proposals.
+    type Scheduler: ScheduleNamed<Self::BlockNumber, <Self as Trait>::Call, Self::PalletsOrigin>;
+
+    /// Overarching type of all pallets origins.
+    type PalletsOrigin: From<system::RawOrigin<Self::AccountId>>;
+
+    /// Finds the calls wrapped by a proposal so that a timelocked call can't skip its delay by
+    /// being wrapped.
+    type WrappedCalls: WrappedCalls<<Self as Trait>::Call>;
 }
 
 decl_storage! {
     trait Store for Module<T: Trait> as Master {
         pub Members: Membership;
         pub Round: u64;
+        /// Proposals collecting votes on-chain, keyed by the hash of the proposed Call.
+        pub Proposals get(fn proposals): map hasher(identity) T::Hash =>
+            Option<PendingProposal<<T as Trait>::Call, T::BlockNumber>>;
+        /// Storage version of the module. `None` means `Members` is still a set of DIDs with equal
+        /// votes, which is migrated on runtime upgrade.
+        ///
+        /// New networks start with last version.
+        StorageVersion build(|_| Some(Releases::V1)): Option<Releases>;
+        /// Delay in blocks before an authorized proposal runs, by call prefix. When several
+        /// prefixes match a proposal, the longest delay applies.
+        pub Timelocks get(fn timelocks): BTreeMap<CallPrefix, T::BlockNumber>;
+        /// Authorized proposals waiting for their timelock to pass, keyed by the hash of the
+        /// proposed Call and the round it was authorized in.
+        pub Scheduled get(fn scheduled): map hasher(identity) T::Hash =>
+            Option<ScheduledProposal<<T as Trait>::Call, T::BlockNumber>>;
+        /// Membership waiting to be confirmed by its members before replacing the current one.
+        pub PendingHandover get(fn pending_handover): Option<Membership>;
     }
     add_extra_genesis {
         config(members): Membership;
         build(|slef: &Self| {
             debug_assert!(slef.members.vote_requirement != 0);
-            debug_assert!(slef.members.vote_requirement <= slef.members.members.len() as u64);
+            debug_assert!(slef.members.vote_requirement <= slef.members.total_weight());
+            debug_assert!(slef.members.members.values().all(|m| m.weight != 0));
             Members::set(slef.members.clone());
         })
     }
@@ -158,13 +377,45 @@ decl_error! {
         ZeroVoteRequirement,
         /// There aren't enough members to satisfy that vote requirement.
         VoteRequirementTooHigh,
+        /// The same Call is already pending.
+        ProposalAlreadyExists,
+        /// There is no pending proposal for the given Call.
+        NoSuchProposal,
+        /// The proposal has expired or its round has ended.
+        ProposalExpired,
+        /// The proposal is still open for votes.
+        ProposalNotExpired,
+        /// The member has already approved this proposal.
+        AlreadyApproved,
+        /// Only the proposer can cancel a proposal.
+        NotProposer,
+        /// The member is not allowed to vote on this Call.
+        CallNotAllowed,
+        /// A member with a voting weight of 0 can't contribute to any vote.
+        ZeroMemberWeight,
+        /// The Scheduler did not accept the timelocked proposal.
+        SchedulingFailed,
+        /// There is no scheduled proposal with the given hash.
+        NoSuchScheduledProposal,
+        /// The DID is already a member.
+        AlreadyMember,
+        /// Members must be existing DIDs.
+        MemberDidNotFound,
+        /// There is no membership handover in progress.
+        NoPendingHandover,
+        /// The timelock of the scheduled proposal has not passed yet.
+        TimelockNotPassed,
+        /// The bytes given are not exactly the encoding of a Call.
+        UndecodableProposal,
     }
 }
 
 decl_event! {
     pub enum Event<T>
     where
-        <T as Trait>::Call
+        <T as Trait>::Call,
+        Hash = <T as system::Config>::Hash,
+        BlockNumber = <T as system::Config>::BlockNumber,
     {
         /// A proposal succeeded and was executed. The dids listed are the members whose votes were
         /// used as proof of authorization. The executed call is provided.
@@ -173,6 +424,33 @@ decl_event! {
         UnderNewOwnership,
         /// A proposal failed to execute
         ExecutionFailed(Vec<Did>, Box<Call>, DispatchError),
+        /// A member proposed a Call for on-chain voting. The hash of the Call identifies the
+        /// proposal.
+        Proposed(Hash, Did),
+        /// A member approved a pending proposal.
+        Approved(Hash, Did),
+        /// A pending proposal was cancelled by its proposer.
+        Cancelled(Hash),
+        /// An expired proposal was removed.
+        ProposalCleared(Hash),
+        /// An authorized proposal was timelocked and will run in the given block. The dids listed
+        /// are the members whose votes authorized it. `Executed` or `ExecutionFailed` follows when
+        /// it runs.
+        Scheduled(Hash, Vec<Did>, BlockNumber),
+        /// A scheduled proposal was vetoed by the listed members and won't run.
+        Vetoed(Hash, Vec<Did>),
+        /// The timelock for the call prefix was set. A delay of 0 removes the timelock.
+        TimelockSet(CallPrefix, BlockNumber),
+        /// A member was added to Master.
+        MemberAdded(Did),
+        /// A member was removed from Master.
+        MemberRemoved(Did),
+        /// The vote requirement of Master was changed.
+        ThresholdSet(u64),
+        /// A new membership was proposed and waits for confirmation by its members.
+        HandoverStarted,
+        /// The pending membership handover was cancelled.
+        HandoverCancelled,
     }
 }
 
@@ -230,8 +508,11 @@ decl_module! {
         ///
         /// A vote requirement of zero is not allowed and will result in an error.
         /// A vote requirement larger than the size of the member list is not allowed and will
-        /// result in an error.
-        #[weight = MIN_WEIGHT + T::DbWeight::get().reads_writes(1, 2)]
+        /// result in an error. All members must be existing DIDs.
+        ///
+        /// The new members don't have to agree to the change, prefer `begin_handover` to replace
+        /// the whole membership.
+        #[weight = MIN_WEIGHT + T::DbWeight::get().reads_writes(1 + membership.members.len() as u64, 3)]
         pub fn set_members(
             origin,
             membership: Membership,
@@ -239,6 +520,199 @@ decl_module! {
             Module::<T>::set_members_(origin, membership)?;
             Ok(Pays::No.into())
         }
+
+        /// Root-only. Add a member to master. The DID must exist. Increases the round number.
+        #[weight = MIN_WEIGHT + T::DbWeight::get().reads_writes(2, 3)]
+        pub fn add_member(
+            origin,
+            did: Did,
+            detail: MemberDetail,
+        ) -> DispatchResultWithPostInfo {
+            Module::<T>::add_member_(origin, did, detail)?;
+            Ok(Pays::No.into())
+        }
+
+        /// Root-only. Remove a member from master. Fails if the remaining members can't meet the
+        /// vote requirement. Increases the round number.
+        #[weight = MIN_WEIGHT + T::DbWeight::get().reads_writes(1, 3)]
+        pub fn remove_member(
+            origin,
+            did: Did,
+        ) -> DispatchResultWithPostInfo {
+            Module::<T>::remove_member_(origin, did)?;
+            Ok(Pays::No.into())
+        }
+
+        /// Root-only. Change the vote requirement of master. The requirement must be non-zero and
+        /// at most the total weight of the members. Increases the round number.
+        #[weight = MIN_WEIGHT + T::DbWeight::get().reads_writes(1, 3)]
+        pub fn set_threshold(
+            origin,
+            vote_requirement: u64,
+        ) -> DispatchResultWithPostInfo {
+            Module::<T>::set_threshold_(origin, vote_requirement)?;
+            Ok(Pays::No.into())
+        }
+
+        /// Root-only. Start handing master over to a new membership, replacing any handover in
+        /// progress. The new membership takes effect once confirmed with `confirm_handover`.
+        #[weight = MIN_WEIGHT + T::DbWeight::get().reads_writes(membership.members.len() as u64, 1)]
+        pub fn begin_handover(
+            origin,
+            membership: Membership,
+        ) -> DispatchResultWithPostInfo {
+            Module::<T>::begin_handover_(origin, membership)?;
+            Ok(Pays::No.into())
+        }
+
+        /// Root-only. Cancel the membership handover in progress.
+        #[weight = MIN_WEIGHT + T::DbWeight::get().reads_writes(1, 1)]
+        pub fn cancel_handover(origin) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            PendingHandover::take().ok_or(MasterError::<T>::NoPendingHandover)?;
+            Self::deposit_event(RawEvent::HandoverCancelled);
+            Ok(Pays::No.into())
+        }
+
+        /// Complete the membership handover in progress. `auth` holds signatures of members of
+        /// the new membership over `StateChange::MasterHandover` for the new membership and the
+        /// current round, whose weights add up to the new vote requirement. The new membership
+        /// replaces the current one and the round number is increased. The members must still be
+        /// existing DIDs.
+        #[weight = MIN_WEIGHT + get_weight_for_pauth(&auth, T::DbWeight::get())
+            + T::DbWeight::get().reads_writes(3 + Module::<T>::pending_handover_size(), 3)]
+        pub fn confirm_handover(
+            origin,
+            auth: PMAuth,
+        ) -> DispatchResult {
+            Module::<T>::confirm_handover_(origin, auth)
+        }
+
+        /// Propose a Call for on-chain voting in the current round. The proposer must be a member
+        /// and `signature` is their vote, i.e. the signature that would be passed to `execute`.
+        /// If the proposer's vote is enough, the proposal is executed right away.
+        #[
+            weight = (
+             get_weight_for_vote(&signature, T::DbWeight::get()) + proposal.get_dispatch_info().weight,
+             proposal.get_dispatch_info().class,
+             proposal.get_dispatch_info().pays_fee,
+            )
+        ]
+        pub fn propose(
+            origin,
+            proposal: Box<<T as Trait>::Call>,
+            proposer: Did,
+            signature: DidSignature,
+        ) -> DispatchResultWithPostInfo {
+            Module::<T>::propose_(origin, proposal, proposer, signature)
+        }
+
+        /// Vote for a pending proposal. The proposal is executed as soon as it has enough votes.
+        /// The Call is passed in full rather than its hash so that the weight of this extrinsic
+        /// accounts for the execution.
+        #[
+            weight = (
+             get_weight_for_vote(&signature, T::DbWeight::get()) + proposal.get_dispatch_info().weight,
+             proposal.get_dispatch_info().class,
+             proposal.get_dispatch_info().pays_fee,
+            )
+        ]
+        pub fn approve(
+            origin,
+            proposal: Box<<T as Trait>::Call>,
+            voter: Did,
+            signature: DidSignature,
+        ) -> DispatchResultWithPostInfo {
+            Module::<T>::approve_(origin, proposal, voter, signature)
+        }
+
+        /// Withdraw a pending proposal. Only the proposer can do this, `signature` is their
+        /// signature over `StateChange::MasterCancel` for the proposal, its round and the block it
+        /// was made in.
+        #[weight = MIN_WEIGHT + signature.weight() + T::DbWeight::get().reads_writes(2, 1)]
+        pub fn cancel(
+            origin,
+            proposal_hash: T::Hash,
+            canceller: Did,
+            signature: DidSignature,
+        ) -> DispatchResult {
+            Module::<T>::cancel_(origin, proposal_hash, canceller, signature)
+        }
+
+        /// Remove a proposal that has expired or whose round has ended. Can be called by anyone.
+        #[weight = MIN_WEIGHT + T::DbWeight::get().reads_writes(2, 1)]
+        pub fn clear_expired(origin, proposal_hash: T::Hash) -> DispatchResult {
+            Module::<T>::clear_expired_(origin, proposal_hash)
+        }
+
+        /// Root-only. Delay proposals whose encoding starts with `prefix` by `delay` blocks. A delay
+        /// of 0 removes the timelock. Proposals scheduled already are not affected.
+        ///
+        /// When proposed through Master, this call is always delayed by the longest timelock so
+        /// that timelocks can't be lifted faster than they apply.
+        #[weight = MIN_WEIGHT + T::DbWeight::get().reads_writes(1, 1)]
+        pub fn set_timelock(
+            origin,
+            prefix: CallPrefix,
+            delay: T::BlockNumber,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            Timelocks::<T>::mutate(|timelocks| {
+                if delay.is_zero() {
+                    timelocks.remove(&prefix);
+                } else {
+                    timelocks.insert(prefix.clone(), delay);
+                }
+            });
+            Self::deposit_event(RawEvent::TimelockSet(prefix, delay));
+            Ok(Pays::No.into())
+        }
+
+        /// Stop a scheduled proposal from running. Needs votes over `StateChange::MasterVeto` for
+        /// the proposal and the round it was authorized in, adding up to the vote requirement.
+        #[weight = MIN_WEIGHT + get_weight_for_pauth(&auth, T::DbWeight::get())
+            + T::DbWeight::get().reads_writes(3, 3)]
+        pub fn veto(
+            origin,
+            proposal_hash: T::Hash,
+            auth: PMAuth,
+        ) -> DispatchResult {
+            Module::<T>::veto_(origin, proposal_hash, auth)
+        }
+
+        /// Root-only. Run a scheduled proposal, called by the Scheduler once the timelock has
+        /// passed. The weight of the proposal recorded when it was scheduled is charged.
+        #[weight = MIN_WEIGHT + T::DbWeight::get().reads_writes(2, 1)
+            + Module::<T>::scheduled_weight(proposal_hash)]
+        pub fn enact_scheduled(
+            origin,
+            proposal_hash: T::Hash,
+        ) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            let scheduled = Scheduled::<T>::get(&proposal_hash)
+                .ok_or(MasterError::<T>::NoSuchScheduledProposal)?;
+            ensure!(
+                <system::Module<T>>::block_number() >= scheduled.enact_at,
+                MasterError::<T>::TimelockNotPassed
+            );
+            Scheduled::<T>::remove(&proposal_hash);
+            Self::dispatch_(
+                Box::new(scheduled.proposal),
+                scheduled.authors,
+                MIN_WEIGHT + T::DbWeight::get().reads_writes(2, 1),
+                None,
+            )
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            if StorageVersion::get().is_none() {
+                let weight = Self::migrate_to_weighted_membership();
+                StorageVersion::put(Releases::V1);
+                weight + T::DbWeight::get().reads_writes(1, 1)
+            } else {
+                T::DbWeight::get().reads(1)
+            }
+        }
     }
 }
 
@@ -261,41 +735,79 @@ impl<T: Trait> Module<T> {
         ensure_signed(origin)?;
 
         // check
-        let membership = Members::get();
-        ensure!(
-            auth.len() as u64 >= membership.vote_requirement,
-            MasterError::<T>::InsufficientVotes,
-        );
-        ensure!(
-            auth.keys().all(|k| membership.members.contains(k)),
-            MasterError::<T>::NotMember,
-        );
-        let payload = StateChange::MasterVote(Payload {
-            proposal: proposal.encode(),
-            round_no: Round::get(),
-        })
-        .encode();
-        for (did, sig) in auth.iter() {
-            let valid = crate::did::Module::<T>::verify_sig_from_did(sig, &payload, did)?;
-            ensure!(valid, MasterError::<T>::BadSig);
+        Self::verify_auth(proposal.encode(), &auth)?;
+
+        let authors = auth.keys().cloned().collect();
+        let min_weight = get_min_weight_for_execute(&auth, T::DbWeight::get());
+        Self::enact_or_schedule(proposal, authors, min_weight, given_weight)
+    }
+
+    /// Run an authorized proposal now or schedule it if a timelock applies to it, then start a new
+    /// round. If `given_weight` is None, the weight used plus `min_weight` is returned, otherwise
+    /// `given_weight` is returned.
+    fn enact_or_schedule(
+        proposal: Box<<T as Trait>::Call>,
+        authors: Vec<Did>,
+        min_weight: Weight,
+        given_weight: Option<Weight>,
+    ) -> DispatchResultWithPostInfo {
+        let delay = Self::delay_for(&proposal);
+        if delay.is_zero() {
+            let result = Self::dispatch_(proposal, authors, min_weight, given_weight);
+            Self::new_round();
+            return result;
         }
 
+        let round_no = Round::get();
+        let hash = T::Hashing::hash_of(&(&proposal, round_no));
+        let enact_at = <system::Module<T>>::block_number() + delay;
+        let call = Call::<T>::enact_scheduled(hash);
+        T::Scheduler::schedule_named(
+            (MASTER_ID, hash).encode(),
+            DispatchTime::At(enact_at),
+            None,
+            SCHEDULE_PRIORITY,
+            system::RawOrigin::Root.into(),
+            call.into(),
+        )
+        .map_err(|_| MasterError::<T>::SchedulingFailed)?;
+        let proposal_weight = proposal.get_dispatch_info().weight;
+        Scheduled::<T>::insert(
+            &hash,
+            ScheduledProposal {
+                proposal: *proposal,
+                round_no,
+                authors: authors.clone(),
+                enact_at,
+                weight: proposal_weight,
+            },
+        );
+        Self::new_round();
+        Self::deposit_event(RawEvent::Scheduled(hash, authors, enact_at));
+
+        // The proposal did not run so its weight is not charged here.
+        let weight = min_weight + T::DbWeight::get().reads_writes(3, 3);
+        Ok(Some(given_weight.unwrap_or(weight)).into())
+    }
+
+    /// Dispatch an authorized proposal as Root. The outcome of the dispatch is logged as an event.
+    /// If `given_weight` is None, the weight of the dispatch plus `min_weight` is returned,
+    /// otherwise `given_weight` is returned.
+    fn dispatch_(
+        proposal: Box<<T as Trait>::Call>,
+        authors: Vec<Did>,
+        min_weight: Weight,
+        given_weight: Option<Weight>,
+    ) -> DispatchResultWithPostInfo {
         // execute call and collect dispatch info to return
         let dispatch_result = proposal
             .clone()
             .dispatch_bypass_filter(system::RawOrigin::Root.into());
 
-        // Update round (nonce)
-        Round::mutate(|round| {
-            *round += 1;
-        });
-
         // Weight from dispatch's declaration. If dispatch does not return a weight in `PostDispatchInfo`,
         // then this weight is used.
         let dispatch_decl_weight = proposal.get_dispatch_info().weight;
 
-        let authors = auth.keys().cloned().collect();
-
         // If weight was not given in `given_weight`, look for weight of dispatch in `post_info`. If
         // `post_info` does not have weight, use weight from declaration. Also add minimum weight for execution
         let actual_weight = move |post_info: PostDispatchInfo| {
@@ -304,7 +816,7 @@ impl<T: Trait> Module<T> {
                     post_info
                         .actual_weight
                         .unwrap_or_else(|| dispatch_decl_weight)
-                        + get_min_weight_for_execute(&auth, T::DbWeight::get()),
+                        + min_weight,
                 )
             })
         };
@@ -335,50 +847,521 @@ impl<T: Trait> Module<T> {
         ensure_root(origin)?;
 
         // check
+        Self::ensure_dids_exist(membership.members.keys())?;
+
+        Self::update_membership(membership, RawEvent::UnderNewOwnership)
+    }
+
+    fn add_member_(origin: T::Origin, did: Did, detail: MemberDetail) -> DispatchResult {
+        ensure_root(origin)?;
+
+        // check
+        let mut membership = Members::get();
+        ensure!(
+            !membership.members.contains_key(&did),
+            MasterError::<T>::AlreadyMember
+        );
+        Self::ensure_dids_exist(core::iter::once(&did))?;
+
+        membership.members.insert(did, detail);
+        Self::update_membership(membership, RawEvent::MemberAdded(did))
+    }
+
+    fn remove_member_(origin: T::Origin, did: Did) -> DispatchResult {
+        ensure_root(origin)?;
+
+        // check
+        let mut membership = Members::get();
+        ensure!(
+            membership.members.remove(&did).is_some(),
+            MasterError::<T>::NotMember
+        );
+
+        Self::update_membership(membership, RawEvent::MemberRemoved(did))
+    }
+
+    fn set_threshold_(origin: T::Origin, vote_requirement: u64) -> DispatchResult {
+        ensure_root(origin)?;
+
+        let mut membership = Members::get();
+        membership.vote_requirement = vote_requirement;
+        Self::update_membership(membership, RawEvent::ThresholdSet(vote_requirement))
+    }
+
+    fn begin_handover_(origin: T::Origin, membership: Membership) -> DispatchResult {
+        ensure_root(origin)?;
+
+        // check
+        Self::validate_membership(&membership)?;
+        Self::ensure_dids_exist(membership.members.keys())?;
+
+        // execute
+        PendingHandover::put(membership);
+
+        // events
+        Self::deposit_event(RawEvent::HandoverStarted);
+
+        Ok(())
+    }
+
+    fn confirm_handover_(origin: T::Origin, auth: PMAuth) -> DispatchResult {
+        ensure_signed(origin)?;
+
+        // check
+        let membership = PendingHandover::get().ok_or(MasterError::<T>::NoPendingHandover)?;
+        // DIDs may have been removed since the handover began
+        Self::ensure_dids_exist(membership.members.keys())?;
+        let mut votes: u64 = 0;
+        for did in auth.keys() {
+            let member = membership
+                .members
+                .get(did)
+                .ok_or(MasterError::<T>::NotMember)?;
+            votes = votes.saturating_add(member.weight);
+        }
+        ensure!(
+            votes >= membership.vote_requirement,
+            MasterError::<T>::InsufficientVotes
+        );
+        let payload = StateChange::MasterHandover(Payload {
+            proposal: membership.encode(),
+            round_no: Round::get(),
+        })
+        .encode();
+        for (did, sig) in auth.iter() {
+            let valid = crate::did::Module::<T>::verify_sig_from_did(sig, &payload, did)?;
+            ensure!(valid, MasterError::<T>::BadSig);
+        }
+
+        // execute
+        Self::update_membership(membership, RawEvent::UnderNewOwnership)
+    }
+
+    /// Check that the vote requirement of the membership is non-zero and can be met by its
+    /// members, and that every member has a non-zero weight.
+    fn validate_membership(membership: &Membership) -> DispatchResult {
         ensure!(
             membership.vote_requirement != 0,
             MasterError::<T>::ZeroVoteRequirement
         );
         ensure!(
-            membership.vote_requirement <= membership.members.len() as u64,
+            membership.members.values().all(|member| member.weight != 0),
+            MasterError::<T>::ZeroMemberWeight
+        );
+        ensure!(
+            membership.vote_requirement <= membership.total_weight(),
             MasterError::<T>::VoteRequirementTooHigh
         );
+        Ok(())
+    }
+
+    fn ensure_dids_exist<'a>(dids: impl Iterator<Item = &'a Did>) -> DispatchResult {
+        for did in dids {
+            ensure!(
+                crate::did::Dids::<T>::contains_key(did),
+                MasterError::<T>::MemberDidNotFound
+            );
+        }
+        Ok(())
+    }
+
+    /// Validate and install `membership`, then start a new round since votes were cast for the
+    /// previous membership. Any handover in progress is dropped as it was begun for the previous
+    /// membership.
+    fn update_membership(membership: Membership, event: Event<T>) -> DispatchResult {
+        Self::validate_membership(&membership)?;
 
         // execute
         Members::set(membership);
-        Round::mutate(|round| {
-            *round += 1;
-        });
+        PendingHandover::kill();
+        Self::new_round();
 
         // events
-        Self::deposit_event(RawEvent::UnderNewOwnership);
+        Self::deposit_event(event);
 
         Ok(())
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::test_common::*;
-    type MasterMod = crate::master::Module<Test>;
-    use alloc::collections::BTreeMap;
-    use sp_core::H256;
+    fn propose_(
+        origin: T::Origin,
+        proposal: Box<<T as Trait>::Call>,
+        proposer: Did,
+        signature: DidSignature,
+    ) -> DispatchResultWithPostInfo {
+        ensure_signed(origin)?;
 
-    // XXX: To check both `execute` and `execute_unchecked_weight`, we can simply test `execute_` but
-    // thats less future proof in theory
+        // check
+        let hash = T::Hashing::hash_of(&proposal);
+        if let Some(pending) = Proposals::<T>::get(&hash) {
+            ensure!(
+                !Self::is_open(&pending),
+                MasterError::<T>::ProposalAlreadyExists
+            );
+        }
+        let round_no = Round::get();
+        Self::verify_vote(&proposal, round_no, &proposer, &signature)?;
 
-    /// set_members() may be called from within execute()
-    /// that should cause round number to be incremented twice
-    #[test]
-    fn execute_set_members() {
-        ext().execute_with(|| {
-            Members::set(Membership {
-                members: set(&[]),
-                vote_requirement: 0,
-            });
+        // execute
+        let mut approvals = PMAuth::new();
+        approvals.insert(proposer, signature.clone());
+        let now = <system::Module<T>>::block_number();
+        let pending = PendingProposal {
+            proposal: *proposal,
+            round_no,
+            proposer,
+            approvals,
+            proposed_at: now,
+            expires_at: now + T::ProposalLifetime::get(),
+        };
+        Self::deposit_event(RawEvent::Proposed(hash, proposer));
+        Self::record_vote(hash, pending, &signature)
+    }
+
+    fn approve_(
+        origin: T::Origin,
+        proposal: Box<<T as Trait>::Call>,
+        voter: Did,
+        signature: DidSignature,
+    ) -> DispatchResultWithPostInfo {
+        ensure_signed(origin)?;
+
+        // check
+        let hash = T::Hashing::hash_of(&proposal);
+        let mut pending = Proposals::<T>::get(&hash).ok_or(MasterError::<T>::NoSuchProposal)?;
+        ensure!(Self::is_open(&pending), MasterError::<T>::ProposalExpired);
+        ensure!(
+            !pending.approvals.contains_key(&voter),
+            MasterError::<T>::AlreadyApproved
+        );
+        Self::verify_vote(&pending.proposal, pending.round_no, &voter, &signature)?;
+
+        // execute
+        pending.approvals.insert(voter, signature.clone());
+        Self::deposit_event(RawEvent::Approved(hash, voter));
+        Self::record_vote(hash, pending, &signature)
+    }
+
+    fn cancel_(
+        origin: T::Origin,
+        proposal_hash: T::Hash,
+        canceller: Did,
+        signature: DidSignature,
+    ) -> DispatchResult {
+        ensure_signed(origin)?;
+
+        // check
+        let pending =
+            Proposals::<T>::get(&proposal_hash).ok_or(MasterError::<T>::NoSuchProposal)?;
+        ensure!(pending.proposer == canceller, MasterError::<T>::NotProposer);
+        let payload = StateChange::MasterCancel(CancelPayload {
+            proposal: pending.proposal.encode(),
+            round_no: pending.round_no,
+            proposed_at: pending.proposed_at.saturated_into(),
+        })
+        .encode();
+        let valid = crate::did::Module::<T>::verify_sig_from_did(&signature, &payload, &canceller)?;
+        ensure!(valid, MasterError::<T>::BadSig);
+
+        // execute
+        Proposals::<T>::remove(&proposal_hash);
+        Self::deposit_event(RawEvent::Cancelled(proposal_hash));
+
+        Ok(())
+    }
+
+    fn clear_expired_(origin: T::Origin, proposal_hash: T::Hash) -> DispatchResult {
+        ensure_signed(origin)?;
+
+        // check
+        let pending =
+            Proposals::<T>::get(&proposal_hash).ok_or(MasterError::<T>::NoSuchProposal)?;
+        ensure!(
+            !Self::is_open(&pending),
+            MasterError::<T>::ProposalNotExpired
+        );
+
+        // execute
+        Proposals::<T>::remove(&proposal_hash);
+        Self::deposit_event(RawEvent::ProposalCleared(proposal_hash));
+
+        Ok(())
+    }
+
+    /// Whether a pending proposal can still collect votes.
+    fn is_open(pending: &PendingProposal<<T as Trait>::Call, T::BlockNumber>) -> bool {
+        pending.round_no == Round::get() && <system::Module<T>>::block_number() < pending.expires_at
+    }
+
+    /// Check that `voter` is a member and `signature` is their vote for `proposal` in round
+    /// `round_no`.
+    fn verify_vote(
+        proposal: &<T as Trait>::Call,
+        round_no: u64,
+        voter: &Did,
+        signature: &DidSignature,
+    ) -> DispatchResult {
+        let encoded_call = proposal.encode();
+        Self::weight_of_votes(&Members::get(), &encoded_call, core::iter::once(voter))?;
+        let payload = StateChange::MasterVote(Payload {
+            proposal: encoded_call,
+            round_no,
+        })
+        .encode();
+        let valid = crate::did::Module::<T>::verify_sig_from_did(signature, &payload, voter)?;
+        ensure!(valid, MasterError::<T>::BadSig);
+        Ok(())
+    }
+
+    /// Execute the proposal if it has enough votes, store it as pending otherwise. `signature` is
+    /// the vote submitted in this extrinsic, used for weight computation.
+    fn record_vote(
+        hash: T::Hash,
+        pending: PendingProposal<<T as Trait>::Call, T::BlockNumber>,
+        signature: &DidSignature,
+    ) -> DispatchResultWithPostInfo {
+        let vote_weight = get_weight_for_vote(signature, T::DbWeight::get());
+        let membership = Members::get();
+        let votes = Self::weight_of_votes(
+            &membership,
+            &pending.proposal.encode(),
+            pending.approvals.keys(),
+        )?;
+        if votes < membership.vote_requirement {
+            Proposals::<T>::insert(&hash, pending);
+            return Ok(Some(vote_weight).into());
+        }
+
+        // Storage is not rolled back on error so the proposal is removed only once it has run or
+        // has been scheduled. It stays pending if scheduling fails.
+        let scheduling = !Self::delay_for(&pending.proposal).is_zero();
+        let authors = pending.approvals.keys().cloned().collect();
+        let result =
+            Self::enact_or_schedule(Box::new(pending.proposal), authors, vote_weight, None);
+        if !scheduling || result.is_ok() {
+            Proposals::<T>::remove(&hash);
+        }
+        result
+    }
+
+    fn veto_(origin: T::Origin, proposal_hash: T::Hash, auth: PMAuth) -> DispatchResult {
+        ensure_signed(origin)?;
+
+        // check
+        let scheduled =
+            Scheduled::<T>::get(&proposal_hash).ok_or(MasterError::<T>::NoSuchScheduledProposal)?;
+        let membership = Members::get();
+        let encoded_call = scheduled.proposal.encode();
+        let votes = Self::weight_of_votes(&membership, &encoded_call, auth.keys())?;
+        ensure!(
+            votes >= membership.vote_requirement,
+            MasterError::<T>::InsufficientVotes
+        );
+        let payload = StateChange::MasterVeto(Payload {
+            proposal: encoded_call,
+            round_no: scheduled.round_no,
+        })
+        .encode();
+        for (did, sig) in auth.iter() {
+            let valid = crate::did::Module::<T>::verify_sig_from_did(sig, &payload, did)?;
+            ensure!(valid, MasterError::<T>::BadSig);
+        }
+
+        // execute
+        T::Scheduler::cancel_named((MASTER_ID, proposal_hash).encode())
+            .map_err(|_| MasterError::<T>::NoSuchScheduledProposal)?;
+        Scheduled::<T>::remove(&proposal_hash);
+        Self::deposit_event(RawEvent::Vetoed(
+            proposal_hash,
+            auth.keys().cloned().collect(),
+        ));
+
+        Ok(())
+    }
+
+    /// The bytes a member signs to vote for the SCALE encoded `proposal` in the current round, i.e.
+    /// the encoding of `StateChange::MasterVote`. Fails if `proposal` is not exactly the encoding of
+    /// a Call.
+    pub fn vote_payload(proposal: Vec<u8>) -> Result<Vec<u8>, DispatchError> {
+        Self::decode_proposal(&proposal)?;
+        Ok(Self::vote_payload_(proposal))
+    }
+
+    fn vote_payload_(proposal: Vec<u8>) -> Vec<u8> {
+        StateChange::MasterVote(Payload {
+            proposal,
+            round_no: Round::get(),
+        })
+        .encode()
+    }
+
+    /// Check that `auth` holds valid votes of current members for the SCALE encoded `proposal` in
+    /// the current round and that their weights add up to the vote requirement. Returns the total
+    /// weight of the votes.
+    pub fn verify_auth(proposal: Vec<u8>, auth: &PMAuth) -> Result<u64, DispatchError> {
+        let membership = Members::get();
+        let votes = Self::weight_of_votes(&membership, &proposal, auth.keys())?;
+        ensure!(
+            votes >= membership.vote_requirement,
+            MasterError::<T>::InsufficientVotes,
+        );
+        let payload = Self::vote_payload_(proposal);
+        for (did, sig) in auth.iter() {
+            let valid = crate::did::Module::<T>::verify_sig_from_did(sig, &payload, did)?;
+            ensure!(valid, MasterError::<T>::BadSig);
+        }
+        Ok(votes)
+    }
+
+    /// Run the SCALE encoded `proposal` as root against the current state and discard all its
+    /// changes. Lets members check a proposal before voting on it. Returns None if `proposal` is
+    /// not exactly the encoding of a Call.
+    pub fn dry_run(proposal: Vec<u8>) -> Option<DryRun<<T as system::Config>::Event>> {
+        let proposal = Self::decode_proposal(&proposal).ok()?;
+        let decl_weight = proposal.get_dispatch_info().weight;
+        let dry_run = with_transaction(|| {
+            let events_before = <system::Module<T>>::events().len();
+            let (result, post_info) =
+                match proposal.dispatch_bypass_filter(system::RawOrigin::Root.into()) {
+                    Ok(post_info) => (Ok(()), post_info),
+                    Err(e) => (Err(e.error), e.post_info),
+                };
+            let events = <system::Module<T>>::events()
+                .into_iter()
+                .skip(events_before)
+                .map(|record| record.event)
+                .collect();
+            TransactionOutcome::Rollback(DryRun {
+                result,
+                weight: post_info.actual_weight.unwrap_or(decl_weight),
+                events,
+            })
+        });
+        Some(dry_run)
+    }
+
+    /// Decode a SCALE encoded Call, rejecting trailing bytes so that a Call has a single encoding.
+    fn decode_proposal(proposal: &[u8]) -> Result<<T as Trait>::Call, DispatchError> {
+        let mut input = proposal;
+        let call = <T as Trait>::Call::decode(&mut input)
+            .map_err(|_| MasterError::<T>::UndecodableProposal)?;
+        ensure!(input.is_empty(), MasterError::<T>::UndecodableProposal);
+        Ok(call)
+    }
+
+    /// Longest delay among the timelocks whose prefix matches the encoded Call, 0 if none does.
+    fn timelock_for(encoded_call: &[u8]) -> T::BlockNumber {
+        Timelocks::<T>::get()
+            .into_iter()
+            .filter(|(prefix, _)| encoded_call.starts_with(prefix))
+            .map(|(_, delay)| delay)
+            .max()
+            .unwrap_or_else(Zero::zero)
+    }
+
+    /// Delay in blocks before an authorized proposal runs. Master's own `set_timelock` and
+    /// `enact_scheduled` are always delayed, by the longest timelock and by at least one block, so
+    /// that Master can't use them to skip a timelock. A proposal wrapping other calls is delayed by
+    /// the longest delay of any of them.
+    fn delay_for(proposal: &<T as Trait>::Call) -> T::BlockNumber {
+        let delay = match proposal.is_sub_type() {
+            Some(Call::set_timelock(..)) | Some(Call::enact_scheduled(..)) => Timelocks::<T>::get()
+                .into_iter()
+                .map(|(_, delay)| delay)
+                .max()
+                .unwrap_or_else(Zero::zero)
+                .max(One::one()),
+            _ => Self::timelock_for(&proposal.encode()),
+        };
+        T::WrappedCalls::wrapped_calls(proposal)
+            .into_iter()
+            .map(Self::delay_for)
+            .fold(delay, |longest, delay| longest.max(delay))
+    }
+
+    /// Number of members of the membership handover in progress, 0 if there is none.
+    fn pending_handover_size() -> u64 {
+        PendingHandover::get().map_or(0, |membership| membership.members.len() as u64)
+    }
+
+    /// Weight of the scheduled proposal recorded when it was scheduled, 0 if there is none.
+    fn scheduled_weight(proposal_hash: &T::Hash) -> Weight {
+        Scheduled::<T>::get(proposal_hash).map_or(0, |scheduled| scheduled.weight)
+    }
+
+    /// Start a new round, invalidating all votes for the current one.
+    fn new_round() {
+        Round::mutate(|round| {
+            *round += 1;
+        });
+    }
+
+    /// Sum of the weights of the votes of `voters` on the encoded Call. Fails if any voter is not a
+    /// member or is not allowed to vote on the Call.
+    fn weight_of_votes<'a>(
+        membership: &Membership,
+        encoded_call: &[u8],
+        voters: impl Iterator<Item = &'a Did>,
+    ) -> Result<u64, DispatchError> {
+        let mut votes: u64 = 0;
+        for voter in voters {
+            let member = membership
+                .members
+                .get(voter)
+                .ok_or(MasterError::<T>::NotMember)?;
+            ensure!(
+                member.can_vote_on(encoded_call),
+                MasterError::<T>::CallNotAllowed
+            );
+            votes = votes.saturating_add(member.weight);
+        }
+        Ok(votes)
+    }
+
+    /// Give every member of the set of DIDs stored before weights existed a weight of 1 and no
+    /// call restrictions. The vote requirement is unchanged.
+    fn migrate_to_weighted_membership() -> Weight {
+        #[derive(Decode)]
+        struct OldMembership {
+            members: BTreeSet<Did>,
+            vote_requirement: u64,
+        }
+
+        let _ = Members::translate(|old: Option<OldMembership>| {
+            old.map(|old| Membership {
+                members: old
+                    .members
+                    .into_iter()
+                    .map(|did| (did, MemberDetail::default()))
+                    .collect(),
+                vote_requirement: old.vote_requirement,
+            })
+        });
+        T::DbWeight::get().reads_writes(1, 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_common::*;
+    type MasterMod = crate::master::Module<Test>;
+    use alloc::collections::BTreeMap;
+    use sp_core::H256;
+
+    // XXX: To check both `execute` and `execute_unchecked_weight`, we can simply test `execute_` but
+    // thats less future proof in theory
+
+    /// set_members() may be called from within execute()
+    /// that should cause round number to be incremented twice
+    #[test]
+    fn execute_set_members() {
+        ext().execute_with(|| {
+            Members::set(Membership {
+                members: members(&[]),
+                vote_requirement: 0,
+            });
             let new_members = Membership {
-                members: set(&[newdid().0]),
+                members: members(&[newdid().0]),
                 vote_requirement: 1,
             };
             let call = TestCall::Master(Call::set_members(new_members.clone()));
@@ -394,7 +1377,7 @@ mod test {
     fn round_inc() {
         ext().execute_with(|| {
             Members::set(Membership {
-                members: set(&[]),
+                members: members(&[]),
                 vote_requirement: 0,
             });
             let call = TestCall::System(system::Call::<Test>::set_storage(vec![]));
@@ -412,7 +1395,7 @@ mod test {
     fn non_root_impossible() {
         ext().execute_with(|| {
             Members::set(Membership {
-                members: set(&[]),
+                members: members(&[]),
                 vote_requirement: 0,
             });
             let call = TestCall::System(system::Call::<Test>::remark(vec![]));
@@ -427,7 +1410,7 @@ mod test {
             MasterMod::set_members(
                 system::RawOrigin::Root.into(),
                 Membership {
-                    members: set(&[newdid().0]),
+                    members: members(&[newdid().0]),
                     vote_requirement: 1,
                 },
             )
@@ -438,7 +1421,7 @@ mod test {
         ext().execute_with(|| {
             let call = TestCall::System(system::Call::<Test>::set_storage(vec![]));
             Members::set(Membership {
-                members: set(&[]),
+                members: members(&[]),
                 vote_requirement: 0,
             });
             MasterMod::execute(Origin::signed(0), Box::new(call.clone()), map(&[])).unwrap();
@@ -458,7 +1441,7 @@ mod test {
                 round_no: Round::get(),
             });
             Members::set(Membership {
-                members: set(&[dida, didb, didc]),
+                members: members(&[dida, didb, didc]),
                 vote_requirement: 2,
             });
             MasterMod::execute(
@@ -478,11 +1461,11 @@ mod test {
 
         ext().execute_with(|| {
             Members::set(Membership {
-                members: set(&[]),
+                members: members(&[]),
                 vote_requirement: 0,
             });
             let call = TestCall::Master(Call::<Test>::set_members(Membership {
-                members: set(&[newdid().0]),
+                members: members(&[newdid().0]),
                 vote_requirement: 1,
             }));
             MasterMod::execute(Origin::signed(0), Box::new(call.clone()), map(&[])).unwrap();
@@ -498,7 +1481,7 @@ mod test {
         ext().execute_with(|| {
             let call = TestCall::System(system::Call::<Test>::remark(vec![]));
             Members::set(Membership {
-                members: set(&[]),
+                members: members(&[]),
                 vote_requirement: 0,
             });
             let res = MasterMod::execute(Origin::signed(0), Box::new(call.clone()), map(&[]));
@@ -524,7 +1507,7 @@ mod test {
                 round_no: Round::get(),
             });
             Members::set(Membership {
-                members: set(&[]),
+                members: members(&[]),
                 vote_requirement: 1,
             });
             let err = MasterMod::execute(
@@ -550,7 +1533,7 @@ mod test {
                 round_no: Round::get(),
             });
             Members::set(Membership {
-                members: set(&[dida, didb, didc]),
+                members: members(&[dida, didb, didc]),
                 vote_requirement: 2,
             });
 
@@ -577,7 +1560,7 @@ mod test {
                 round_no: Round::get(),
             });
             Members::set(Membership {
-                members: set(&[dida, didb, didc]),
+                members: members(&[dida, didb, didc]),
                 vote_requirement: 3,
             });
             MasterMod::execute(
@@ -600,7 +1583,7 @@ mod test {
             let (didb, didbk) = newdid();
             let (didc, didck) = newdid();
             Members::set(Membership {
-                members: set(&[dida, didb, didc]),
+                members: members(&[dida, didb, didc]),
                 vote_requirement: 2,
             });
             let call = TestCall::System(system::Call::<Test>::set_storage(vec![]));
@@ -640,7 +1623,7 @@ mod test {
             let (didb, didbk) = newdid();
             let (_didc, didck) = newdid();
             Members::set(Membership {
-                members: set(&[dida, didb]),
+                members: members(&[dida, didb]),
                 vote_requirement: 1,
             });
             let call = Box::new(TestCall::System(system::Call::<Test>::set_storage(vec![])));
@@ -686,7 +1669,7 @@ mod test {
             let (dida, _didak) = newdid();
             let (didc, didck) = newdid();
             Members::set(Membership {
-                members: set(&[dida]),
+                members: members(&[dida]),
                 vote_requirement: 1,
             });
             let call = Box::new(TestCall::System(system::Call::<Test>::set_storage(vec![])));
@@ -709,7 +1692,7 @@ mod test {
         ext().execute_with(|| {
             let (dida, didak) = newdid();
             Members::set(Membership {
-                members: set(&[dida]),
+                members: members(&[dida]),
                 vote_requirement: 1,
             });
             let call = TestCall::System(system::Call::<Test>::set_storage(vec![]));
@@ -746,7 +1729,7 @@ mod test {
                 round_no: Round::get(),
             });
             Members::set(Membership {
-                members: set(&[dida, didb]),
+                members: members(&[dida, didb]),
                 vote_requirement: 2,
             });
 
@@ -765,11 +1748,11 @@ mod test {
         ext().execute_with(|| {
             for m in [
                 Membership {
-                    members: set(&[]),
+                    members: members(&[]),
                     vote_requirement: 0,
                 },
                 Membership {
-                    members: set(&[newdid().0]),
+                    members: members(&[newdid().0]),
                     vote_requirement: 0,
                 },
             ]
@@ -787,19 +1770,19 @@ mod test {
         ext().execute_with(|| {
             for m in [
                 Membership {
-                    members: set(&[]),
+                    members: members(&[]),
                     vote_requirement: 1,
                 },
                 Membership {
-                    members: set(&[newdid().0]),
+                    members: members(&[newdid().0]),
                     vote_requirement: 2,
                 },
                 Membership {
-                    members: set(&[newdid().0]),
+                    members: members(&[newdid().0]),
                     vote_requirement: 3,
                 },
                 Membership {
-                    members: set(&[newdid().0]),
+                    members: members(&[newdid().0]),
                     vote_requirement: u64::MAX,
                 },
             ]
@@ -812,30 +1795,1118 @@ mod test {
         });
     }
 
-    fn master_events() -> Vec<Event<Test>> {
-        system::Module::<Test>::events()
-            .iter()
-            .filter_map(|event_record| {
-                let system::EventRecord::<TestEvent, H256> {
-                    phase,
-                    event,
-                    topics,
-                } = event_record;
-                assert_eq!(phase, &system::Phase::Initialization);
-                assert_eq!(topics, &vec![]);
-                match event {
-                    TestEvent::Master(e) => Some(e.clone()),
-                    _ => None,
-                }
-            })
-            .collect()
+    /// A proposal collects votes on-chain and is executed once it has enough of them.
+    #[test]
+    fn propose_approve_execute() {
+        ext().execute_with(|| {
+            let (dida, didak) = newdid();
+            let (didb, didbk) = newdid();
+            let (didc, didck) = newdid();
+            Members::set(Membership {
+                members: members(&[dida, didb, didc]),
+                vote_requirement: 3,
+            });
+            let kv = (vec![4; 20], vec![5; 20]);
+            let call = TestCall::System(system::Call::<Test>::set_storage(vec![kv.clone()]));
+            let hash = <Test as system::Config>::Hashing::hash_of(&call);
+            let sc = vote(&call);
+
+            MasterMod::propose(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                dida,
+                sign(&sc, &didak),
+            )
+            .unwrap();
+            let pending = MasterMod::proposals(hash).unwrap();
+            assert_eq!(pending.proposer, dida);
+            assert_eq!(pending.round_no, 0);
+            assert_eq!(pending.expires_at, 1 + ProposalLifetime::get());
+
+            // votes from non members, with bad signatures or repeated votes are rejected
+            let (didd, diddk) = newdid();
+            let err = MasterMod::approve(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                didd,
+                sign(&sc, &diddk),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::NotMember.into());
+            let err = MasterMod::approve(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                didb,
+                sign(&sc, &didak),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::BadSig.into());
+            let err = MasterMod::approve(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                dida,
+                sign(&sc, &didak),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::AlreadyApproved.into());
+
+            system::Module::<Test>::set_block_number(5);
+            MasterMod::approve(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                didb,
+                sign(&sc, &didbk),
+            )
+            .unwrap();
+            assert_eq!(sp_io::storage::get(&kv.0), None);
+            assert_eq!(Round::get(), 0);
+
+            MasterMod::approve(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                didc,
+                sign(&sc, &didck),
+            )
+            .unwrap();
+            assert_eq!(sp_io::storage::get(&kv.0), Some(kv.1.to_vec()));
+            assert_eq!(Round::get(), 1);
+            assert!(MasterMod::proposals(hash).is_none());
+            assert_eq!(
+                master_events(),
+                vec![
+                    Event::<Test>::Proposed(hash, dida),
+                    Event::<Test>::Approved(hash, didb),
+                    Event::<Test>::Approved(hash, didc),
+                    Event::<Test>::Executed(sorted(vec![dida, didb, didc]), Box::new(call.clone())),
+                ]
+            );
+
+            // can't approve an executed proposal
+            let err =
+                MasterMod::approve(Origin::signed(0), Box::new(call), dida, sign(&sc, &didak))
+                    .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::NoSuchProposal.into());
+        });
     }
 
-    fn map<K: Ord, V>(slice: &[(K, V)]) -> BTreeMap<K, V>
-    where
-        (K, V): Clone,
-    {
-        slice.iter().cloned().collect()
+    /// A proposal is executed right away when the proposer's vote is enough.
+    #[test]
+    fn propose_single_vote() {
+        ext().execute_with(|| {
+            let (dida, didak) = newdid();
+            let (didb, _) = newdid();
+            Members::set(Membership {
+                members: members(&[dida, didb]),
+                vote_requirement: 1,
+            });
+            let call = TestCall::System(system::Call::<Test>::set_storage(vec![]));
+            let hash = <Test as system::Config>::Hashing::hash_of(&call);
+            MasterMod::propose(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                dida,
+                sign(&vote(&call), &didak),
+            )
+            .unwrap();
+            assert!(MasterMod::proposals(hash).is_none());
+            assert_eq!(Round::get(), 1);
+            assert_eq!(
+                master_events(),
+                vec![
+                    Event::<Test>::Proposed(hash, dida),
+                    Event::<Test>::Executed(vec![dida], Box::new(call)),
+                ]
+            );
+        });
+    }
+
+    /// Proposals can't be approved after they expire or their round ends and can then be cleared.
+    #[test]
+    fn proposal_expiry() {
+        ext().execute_with(|| {
+            let (dida, didak) = newdid();
+            let (didb, didbk) = newdid();
+            Members::set(Membership {
+                members: members(&[dida, didb]),
+                vote_requirement: 2,
+            });
+            let call = TestCall::System(system::Call::<Test>::set_storage(vec![]));
+            let hash = <Test as system::Config>::Hashing::hash_of(&call);
+            let sc = vote(&call);
+            MasterMod::propose(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                dida,
+                sign(&sc, &didak),
+            )
+            .unwrap();
+
+            // same call can't be proposed twice
+            let err = MasterMod::propose(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                didb,
+                sign(&sc, &didbk),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::ProposalAlreadyExists.into());
+            let err = MasterMod::clear_expired(Origin::signed(0), hash).unwrap_err();
+            assert_eq!(err, MasterError::<Test>::ProposalNotExpired.into());
+
+            system::Module::<Test>::set_block_number(1 + ProposalLifetime::get());
+            let err = MasterMod::approve(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                didb,
+                sign(&sc, &didbk),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::ProposalExpired.into());
+            MasterMod::clear_expired(Origin::signed(0), hash).unwrap();
+            assert!(MasterMod::proposals(hash).is_none());
+            assert_eq!(
+                master_events(),
+                vec![
+                    Event::<Test>::Proposed(hash, dida),
+                    Event::<Test>::ProposalCleared(hash),
+                ]
+            );
+        });
+
+        ext().execute_with(|| {
+            let (dida, didak) = newdid();
+            let (didb, didbk) = newdid();
+            Members::set(Membership {
+                members: members(&[dida, didb]),
+                vote_requirement: 2,
+            });
+            let call = TestCall::System(system::Call::<Test>::set_storage(vec![]));
+            let hash = <Test as system::Config>::Hashing::hash_of(&call);
+            let sc = vote(&call);
+            MasterMod::propose(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                dida,
+                sign(&sc, &didak),
+            )
+            .unwrap();
+
+            // the round ends with another execution
+            Round::mutate(|round| *round += 1);
+            let err = MasterMod::approve(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                didb,
+                sign(&sc, &didbk),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::ProposalExpired.into());
+
+            // a stale proposal is replaced when proposed again
+            let sc = vote(&call);
+            MasterMod::propose(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                didb,
+                sign(&sc, &didbk),
+            )
+            .unwrap();
+            let pending = MasterMod::proposals(hash).unwrap();
+            assert_eq!(pending.proposer, didb);
+            assert_eq!(pending.round_no, 1);
+        });
+    }
+
+    /// Only the proposer can cancel a proposal.
+    #[test]
+    fn cancel_proposal() {
+        ext().execute_with(|| {
+            let (dida, didak) = newdid();
+            let (didb, didbk) = newdid();
+            Members::set(Membership {
+                members: members(&[dida, didb]),
+                vote_requirement: 2,
+            });
+            let call = TestCall::System(system::Call::<Test>::set_storage(vec![]));
+            let hash = <Test as system::Config>::Hashing::hash_of(&call);
+            MasterMod::propose(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                dida,
+                sign(&vote(&call), &didak),
+            )
+            .unwrap();
+
+            let cancel = StateChange::MasterCancel(CancelPayload {
+                proposal: call.encode(),
+                round_no: Round::get(),
+                proposed_at: 1,
+            });
+            let err = MasterMod::cancel(Origin::signed(0), hash, didb, sign(&cancel, &didbk))
+                .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::NotProposer.into());
+            // a vote is not a cancellation
+            let err = MasterMod::cancel(Origin::signed(0), hash, dida, sign(&vote(&call), &didak))
+                .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::BadSig.into());
+
+            MasterMod::cancel(Origin::signed(0), hash, dida, sign(&cancel, &didak)).unwrap();
+            assert!(MasterMod::proposals(hash).is_none());
+
+            // the cancellation doesn't apply to the same Call proposed again in the same round
+            system::Module::<Test>::set_block_number(2);
+            MasterMod::propose(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                dida,
+                sign(&vote(&call), &didak),
+            )
+            .unwrap();
+            let err = MasterMod::cancel(Origin::signed(0), hash, dida, sign(&cancel, &didak))
+                .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::BadSig.into());
+            assert_eq!(MasterMod::proposals(hash).unwrap().proposed_at, 2);
+            assert_eq!(
+                master_events(),
+                vec![
+                    Event::<Test>::Proposed(hash, dida),
+                    Event::<Test>::Cancelled(hash),
+                    Event::<Test>::Proposed(hash, dida),
+                ]
+            );
+        });
+    }
+
+    /// Votes count with the weight of the voting member.
+    #[test]
+    fn weighted_votes() {
+        ext().execute_with(|| {
+            let (dida, didak) = newdid();
+            let (didb, didbk) = newdid();
+            let (didc, didck) = newdid();
+            let mut membership = Membership {
+                members: members(&[dida, didb, didc]),
+                vote_requirement: 3,
+            };
+            membership.members.get_mut(&dida).unwrap().weight = 3;
+            MasterMod::set_members(Origin::root(), membership).unwrap();
+            let call = TestCall::System(system::Call::<Test>::set_storage(vec![]));
+
+            let sc = vote(&call);
+            let err = MasterMod::execute(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                map(&[(didb, sign(&sc, &didbk)), (didc, sign(&sc, &didck))]),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::InsufficientVotes.into());
+            MasterMod::execute(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                map(&[(dida, sign(&sc, &didak))]),
+            )
+            .unwrap();
+
+            // on-chain votes are weighted too
+            let sc = vote(&call);
+            MasterMod::propose(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                didb,
+                sign(&sc, &didbk),
+            )
+            .unwrap();
+            MasterMod::approve(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                didc,
+                sign(&sc, &didck),
+            )
+            .unwrap();
+            assert_eq!(Round::get(), 2);
+            MasterMod::approve(Origin::signed(0), Box::new(call), dida, sign(&sc, &didak)).unwrap();
+            assert_eq!(Round::get(), 3);
+        });
+    }
+
+    /// Members with call restrictions can only vote on the allowed calls.
+    #[test]
+    fn call_filter() {
+        ext().execute_with(|| {
+            let (dida, didak) = newdid();
+            let (didb, didbk) = newdid();
+            let set_storage = TestCall::System(system::Call::<Test>::set_storage(vec![]));
+            let remark = TestCall::System(system::Call::<Test>::remark(vec![]));
+            let set_members = TestCall::Master(Call::<Test>::set_members(Membership {
+                members: members(&[dida]),
+                vote_requirement: 1,
+            }));
+            let mut membership = Membership {
+                members: members(&[dida, didb]),
+                vote_requirement: 1,
+            };
+            // didb can only vote on `system::set_storage`
+            membership.members.get_mut(&didb).unwrap().allowed_calls =
+                Some(set(&[set_storage.encode()[..2].to_vec()]));
+            MasterMod::set_members(Origin::root(), membership).unwrap();
+
+            for call in &[remark.clone(), set_members.clone()] {
+                let err = MasterMod::execute(
+                    Origin::signed(0),
+                    Box::new(call.clone()),
+                    map(&[(didb, sign(&vote(call), &didbk))]),
+                )
+                .unwrap_err();
+                assert_eq!(err, MasterError::<Test>::CallNotAllowed.into());
+                let err = MasterMod::propose(
+                    Origin::signed(0),
+                    Box::new(call.clone()),
+                    didb,
+                    sign(&vote(call), &didbk),
+                )
+                .unwrap_err();
+                assert_eq!(err, MasterError::<Test>::CallNotAllowed.into());
+            }
+
+            // a restricted vote can't be combined with other votes to pass a disallowed call
+            let err = MasterMod::execute(
+                Origin::signed(0),
+                Box::new(set_members.clone()),
+                map(&[
+                    (dida, sign(&vote(&set_members), &didak)),
+                    (didb, sign(&vote(&set_members), &didbk)),
+                ]),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::CallNotAllowed.into());
+
+            MasterMod::execute(
+                Origin::signed(0),
+                Box::new(set_storage.clone()),
+                map(&[(didb, sign(&vote(&set_storage), &didbk))]),
+            )
+            .unwrap();
+            MasterMod::execute(
+                Origin::signed(0),
+                Box::new(set_members.clone()),
+                map(&[(dida, sign(&vote(&set_members), &didak))]),
+            )
+            .unwrap();
+        });
+    }
+
+    #[test]
+    fn err_zero_member_weight() {
+        ext().execute_with(|| {
+            let (dida, _) = newdid();
+            let (didb, _) = newdid();
+            let mut membership = Membership {
+                members: members(&[dida, didb]),
+                vote_requirement: 1,
+            };
+            membership.members.get_mut(&didb).unwrap().weight = 0;
+            let err = MasterMod::set_members(Origin::root(), membership.clone()).unwrap_err();
+            assert_eq!(err, MasterError::<Test>::ZeroMemberWeight.into());
+
+            // vote requirement is checked against the total weight
+            membership.members.get_mut(&didb).unwrap().weight = 4;
+            membership.vote_requirement = 6;
+            let err = MasterMod::set_members(Origin::root(), membership.clone()).unwrap_err();
+            assert_eq!(err, MasterError::<Test>::VoteRequirementTooHigh.into());
+            membership.vote_requirement = 5;
+            MasterMod::set_members(Origin::root(), membership).unwrap();
+        });
+    }
+
+    /// A set of DIDs is migrated to members of weight 1 without call restrictions.
+    #[test]
+    fn migrate_to_weighted_membership() {
+        use frame_support::{storage::unhashed, traits::OnRuntimeUpgrade};
+
+        ext().execute_with(|| {
+            let (dida, _) = newdid();
+            let (didb, _) = newdid();
+            StorageVersion::kill();
+            unhashed::put(&Members::hashed_key(), &(set(&[dida, didb]), 2u64));
+
+            MasterMod::on_runtime_upgrade();
+
+            assert_eq!(StorageVersion::get(), Some(Releases::V1));
+            assert_eq!(
+                Members::get(),
+                Membership {
+                    members: members(&[dida, didb]),
+                    vote_requirement: 2,
+                }
+            );
+
+            // running again is a no-op
+            MasterMod::on_runtime_upgrade();
+            assert_eq!(Members::get().members.len(), 2);
+        });
+    }
+
+    /// Authorized proposals matching a timelock run once the delay has passed.
+    #[test]
+    fn timelocked_execution() {
+        ext().execute_with(|| {
+            let (dida, didak) = newdid();
+            let (didb, didbk) = newdid();
+            Members::set(Membership {
+                members: members(&[dida, didb]),
+                vote_requirement: 2,
+            });
+            let kv = (vec![4; 20], vec![5; 20]);
+            let call = TestCall::System(system::Call::<Test>::set_storage(vec![kv.clone()]));
+            let prefix = call.encode()[..2].to_vec();
+            MasterMod::set_timelock(Origin::root(), prefix.clone(), 5).unwrap();
+
+            let sc = vote(&call);
+            MasterMod::execute(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                map(&[(dida, sign(&sc, &didak)), (didb, sign(&sc, &didbk))]),
+            )
+            .unwrap();
+            let hash = <Test as system::Config>::Hashing::hash_of(&(&call, 0u64));
+            let scheduled = MasterMod::scheduled(hash).unwrap();
+            assert_eq!(scheduled.enact_at, 6);
+            assert_eq!(scheduled.round_no, 0);
+            assert_eq!(Round::get(), 1);
+            assert_eq!(sp_io::storage::get(&kv.0), None);
+
+            // only the scheduler may enact it
+            assert!(MasterMod::enact_scheduled(Origin::signed(0), hash).is_err());
+            // and not before the timelock has passed
+            assert_eq!(
+                MasterMod::enact_scheduled(Origin::root(), hash).unwrap_err(),
+                MasterError::<Test>::TimelockNotPassed.into()
+            );
+            assert_eq!(scheduled.weight, call.get_dispatch_info().weight);
+            assert_eq!(
+                Call::<Test>::enact_scheduled(hash)
+                    .get_dispatch_info()
+                    .weight,
+                MIN_WEIGHT + <Test as system::Config>::DbWeight::get().reads_writes(2, 1) + 101
+            );
+
+            run_to_block(5);
+            assert_eq!(sp_io::storage::get(&kv.0), None);
+            run_to_block(6);
+            assert_eq!(sp_io::storage::get(&kv.0), Some(kv.1.to_vec()));
+            assert!(MasterMod::scheduled(hash).is_none());
+            assert_eq!(Round::get(), 1);
+            assert_eq!(
+                master_events(),
+                vec![
+                    Event::<Test>::TimelockSet(prefix, 5),
+                    Event::<Test>::Scheduled(hash, sorted(vec![dida, didb]), 6),
+                    Event::<Test>::Executed(sorted(vec![dida, didb]), Box::new(call)),
+                ]
+            );
+        });
+    }
+
+    /// A quorum of members can stop a scheduled proposal.
+    #[test]
+    fn veto_scheduled() {
+        ext().execute_with(|| {
+            let (dida, didak) = newdid();
+            let (didb, didbk) = newdid();
+            let (didc, didck) = newdid();
+            Members::set(Membership {
+                members: members(&[dida, didb, didc]),
+                vote_requirement: 2,
+            });
+            let kv = (vec![4; 20], vec![5; 20]);
+            let call = TestCall::System(system::Call::<Test>::set_storage(vec![kv.clone()]));
+            MasterMod::set_timelock(Origin::root(), call.encode()[..1].to_vec(), 5).unwrap();
+
+            let sc = vote(&call);
+            MasterMod::execute(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                map(&[(dida, sign(&sc, &didak)), (didb, sign(&sc, &didbk))]),
+            )
+            .unwrap();
+            let hash = <Test as system::Config>::Hashing::hash_of(&(&call, 0u64));
+
+            let veto = StateChange::MasterVeto(Payload {
+                proposal: call.encode(),
+                round_no: 0,
+            });
+            let err = MasterMod::veto(Origin::signed(0), hash, map(&[(didc, sign(&veto, &didck))]))
+                .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::InsufficientVotes.into());
+            // a vote is not a veto
+            let err = MasterMod::veto(
+                Origin::signed(0),
+                hash,
+                map(&[(dida, sign(&sc, &didak)), (didc, sign(&sc, &didck))]),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::BadSig.into());
+            let err = MasterMod::veto(
+                Origin::signed(0),
+                H256::zero(),
+                map(&[(dida, sign(&veto, &didak)), (didc, sign(&veto, &didck))]),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::NoSuchScheduledProposal.into());
+
+            MasterMod::veto(
+                Origin::signed(0),
+                hash,
+                map(&[(dida, sign(&veto, &didak)), (didc, sign(&veto, &didck))]),
+            )
+            .unwrap();
+            assert!(MasterMod::scheduled(hash).is_none());
+
+            run_to_block(10);
+            assert_eq!(sp_io::storage::get(&kv.0), None);
+            assert_eq!(
+                master_events().last(),
+                Some(&Event::<Test>::Vetoed(hash, sorted(vec![dida, didc])))
+            );
+        });
+    }
+
+    /// The longest matching timelock applies and a delay of 0 removes a timelock.
+    #[test]
+    fn set_timelock() {
+        ext().execute_with(|| {
+            let call = TestCall::System(system::Call::<Test>::set_storage(vec![]));
+            let encoded = call.encode();
+            assert!(MasterMod::set_timelock(Origin::signed(0), vec![], 1).is_err());
+
+            MasterMod::set_timelock(Origin::root(), encoded[..1].to_vec(), 3).unwrap();
+            MasterMod::set_timelock(Origin::root(), encoded[..2].to_vec(), 7).unwrap();
+            MasterMod::set_timelock(Origin::root(), vec![0xff], 20).unwrap();
+            assert_eq!(MasterMod::timelock_for(&encoded), 7);
+
+            MasterMod::set_timelock(Origin::root(), encoded[..2].to_vec(), 0).unwrap();
+            assert_eq!(MasterMod::timelock_for(&encoded), 3);
+            assert_eq!(MasterMod::timelocks().len(), 2);
+
+            MasterMod::set_timelock(Origin::root(), encoded[..1].to_vec(), 0).unwrap();
+            assert_eq!(MasterMod::timelock_for(&encoded), 0);
+        });
+    }
+
+    /// Master's own timelock calls are always delayed by the longest timelock.
+    #[test]
+    fn timelock_calls_always_delayed() {
+        ext().execute_with(|| {
+            let set: TestCall = Call::<Test>::set_timelock(vec![0xff], 0).into();
+            let enact: TestCall = Call::<Test>::enact_scheduled(H256::zero()).into();
+            let other = TestCall::System(system::Call::<Test>::set_storage(vec![]));
+            assert_eq!(MasterMod::delay_for(&set), 1);
+            assert_eq!(MasterMod::delay_for(&enact), 1);
+            assert_eq!(MasterMod::delay_for(&other), 0);
+
+            MasterMod::set_timelock(Origin::root(), vec![0xff], 20).unwrap();
+            MasterMod::set_timelock(Origin::root(), other.encode()[..1].to_vec(), 3).unwrap();
+            assert_eq!(MasterMod::delay_for(&set), 20);
+            assert_eq!(MasterMod::delay_for(&enact), 20);
+            assert_eq!(MasterMod::delay_for(&other), 3);
+
+            // a proposal lifting a timelock is scheduled rather than run
+            let (dida, didak) = newdid();
+            Members::set(Membership {
+                members: members(&[dida]),
+                vote_requirement: 1,
+            });
+            let sc = vote(&set);
+            MasterMod::execute(
+                Origin::signed(0),
+                Box::new(set.clone()),
+                map(&[(dida, sign(&sc, &didak))]),
+            )
+            .unwrap();
+            assert_eq!(MasterMod::timelocks().len(), 2);
+            let hash = <Test as system::Config>::Hashing::hash_of(&(&set, 0u64));
+            assert_eq!(MasterMod::scheduled(hash).unwrap().enact_at, 21);
+        });
+    }
+
+    /// An approved proposal that can't be scheduled stays pending.
+    #[test]
+    fn scheduling_failure_keeps_proposal() {
+        ext().execute_with(|| {
+            let (dida, didak) = newdid();
+            let (didb, didbk) = newdid();
+            Members::set(Membership {
+                members: members(&[dida, didb]),
+                vote_requirement: 2,
+            });
+            let call = TestCall::System(system::Call::<Test>::set_storage(vec![]));
+            MasterMod::set_timelock(Origin::root(), call.encode()[..1].to_vec(), 5).unwrap();
+            let hash = <Test as system::Config>::Hashing::hash_of(&call);
+            let sc = vote(&call);
+            MasterMod::propose(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                dida,
+                sign(&sc, &didak),
+            )
+            .unwrap();
+
+            // a task with the name Master would use makes scheduling fail
+            let scheduled_hash = <Test as system::Config>::Hashing::hash_of(&(&call, 0u64));
+            let name = (MASTER_ID, scheduled_hash).encode();
+            <pallet_scheduler::Module<Test> as ScheduleNamed<_, _, _>>::schedule_named(
+                name.clone(),
+                DispatchTime::At(100),
+                None,
+                SCHEDULE_PRIORITY,
+                system::RawOrigin::Root.into(),
+                call.clone(),
+            )
+            .unwrap();
+            let err = MasterMod::approve(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                didb,
+                sign(&sc, &didbk),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::SchedulingFailed.into());
+            assert_eq!(MasterMod::proposals(hash).unwrap().approvals.len(), 1);
+            assert_eq!(Round::get(), 0);
+            assert!(MasterMod::scheduled(scheduled_hash).is_none());
+
+            // once the name is free the same vote schedules the proposal
+            <pallet_scheduler::Module<Test> as ScheduleNamed<_, _, _>>::cancel_named(name).unwrap();
+            MasterMod::approve(
+                Origin::signed(0),
+                Box::new(call.clone()),
+                didb,
+                sign(&sc, &didbk),
+            )
+            .unwrap();
+            assert!(MasterMod::proposals(hash).is_none());
+            assert_eq!(Round::get(), 1);
+            assert_eq!(MasterMod::scheduled(scheduled_hash).unwrap().enact_at, 6);
+        });
+    }
+
+    /// Wrapping a timelocked call in a batch doesn't skip its timelock.
+    #[test]
+    fn batched_timelock_calls_delayed() {
+        ext().execute_with(|| {
+            let set: TestCall = Call::<Test>::set_timelock(vec![0xff], 0).into();
+            let other = TestCall::System(system::Call::<Test>::set_storage(vec![]));
+            let remark = TestCall::System(system::Call::<Test>::remark(vec![]));
+            MasterMod::set_timelock(Origin::root(), vec![0xff], 20).unwrap();
+            MasterMod::set_timelock(Origin::root(), other.encode()[..2].to_vec(), 3).unwrap();
+            assert_eq!(MasterMod::delay_for(&remark), 0);
+            assert_eq!(
+                MasterMod::delay_for(&TestCall::Batch(vec![remark.clone()])),
+                0
+            );
+            assert_eq!(
+                MasterMod::delay_for(&TestCall::Batch(vec![remark.clone(), other.clone()])),
+                3
+            );
+            assert_eq!(
+                MasterMod::delay_for(&TestCall::Batch(vec![
+                    other.clone(),
+                    TestCall::Batch(vec![remark.clone(), set])
+                ])),
+                20
+            );
+
+            // a batch with a timelocked call is scheduled rather than run
+            let (dida, didak) = newdid();
+            Members::set(Membership {
+                members: members(&[dida]),
+                vote_requirement: 1,
+            });
+            let kv = (vec![4; 20], vec![5; 20]);
+            let batch = TestCall::Batch(vec![
+                remark,
+                TestCall::System(system::Call::<Test>::set_storage(vec![kv.clone()])),
+            ]);
+            let sc = vote(&batch);
+            MasterMod::execute(
+                Origin::signed(0),
+                Box::new(batch.clone()),
+                map(&[(dida, sign(&sc, &didak))]),
+            )
+            .unwrap();
+            assert_eq!(sp_io::storage::get(&kv.0), None);
+            let hash = <Test as system::Config>::Hashing::hash_of(&(&batch, 0u64));
+            assert_eq!(MasterMod::scheduled(hash).unwrap().enact_at, 4);
+        });
+    }
+
+    /// A dry run reports the outcome of a proposal without changing state.
+    #[test]
+    fn dry_run() {
+        ext().execute_with(|| {
+            let (dida, _) = newdid();
+            Members::set(Membership {
+                members: members(&[dida]),
+                vote_requirement: 1,
+            });
+            let kv = (vec![4; 20], vec![5; 20]);
+            let call = TestCall::System(system::Call::<Test>::set_storage(vec![kv.clone()]));
+            let dry_run = MasterMod::dry_run(call.encode()).unwrap();
+            assert_eq!(dry_run.result, Ok(()));
+            assert_eq!(dry_run.weight, call.get_dispatch_info().weight);
+            assert_eq!(dry_run.events, vec![]);
+            assert_eq!(sp_io::storage::get(&kv.0), None);
+
+            let new_members = Membership {
+                members: members(&[dida, newdid().0]),
+                vote_requirement: 2,
+            };
+            let call = TestCall::Master(Call::set_members(new_members.clone()));
+            let dry_run = MasterMod::dry_run(call.encode()).unwrap();
+            assert_eq!(dry_run.result, Ok(()));
+            assert_eq!(
+                dry_run.events,
+                vec![TestEvent::Master(Event::<Test>::UnderNewOwnership)]
+            );
+            assert_eq!(Members::get().vote_requirement, 1);
+            assert_eq!(Round::get(), 0);
+            assert_eq!(master_events(), vec![]);
+
+            let call = TestCall::Master(Call::set_members(Membership {
+                members: members(&[]),
+                vote_requirement: 1,
+            }));
+            let dry_run = MasterMod::dry_run(call.encode()).unwrap();
+            assert_eq!(
+                dry_run.result,
+                Err(MasterError::<Test>::VoteRequirementTooHigh.into())
+            );
+
+            assert!(MasterMod::dry_run(vec![0xff, 0xff]).is_none());
+            let mut trailing = call.encode();
+            trailing.push(0);
+            assert!(MasterMod::dry_run(trailing).is_none());
+        });
+    }
+
+    /// Signing payloads and votes can be checked without submitting them.
+    #[test]
+    fn vote_payload_and_verify_auth() {
+        ext().execute_with(|| {
+            let (dida, didak) = newdid();
+            let (didb, didbk) = newdid();
+            Members::set(Membership {
+                members: members(&[dida, didb]),
+                vote_requirement: 2,
+            });
+            Round::put(3);
+            let call = TestCall::System(system::Call::<Test>::set_storage(vec![]));
+            let payload = MasterMod::vote_payload(call.encode()).unwrap();
+            assert_eq!(payload, vote(&call).encode());
+            let mut trailing = call.encode();
+            trailing.push(0);
+            assert_eq!(
+                MasterMod::vote_payload(trailing),
+                Err(MasterError::<Test>::UndecodableProposal.into())
+            );
+            assert_eq!(
+                MasterMod::vote_payload(vec![0xff, 0xff]),
+                Err(MasterError::<Test>::UndecodableProposal.into())
+            );
+
+            let auth = map(&[(dida, sign(&vote(&call), &didak))]);
+            assert_eq!(
+                MasterMod::verify_auth(call.encode(), &auth),
+                Err(MasterError::<Test>::InsufficientVotes.into())
+            );
+            let auth = map(&[
+                (dida, sign(&vote(&call), &didak)),
+                (didb, sign(&vote(&call), &didak)),
+            ]);
+            assert_eq!(
+                MasterMod::verify_auth(call.encode(), &auth),
+                Err(MasterError::<Test>::BadSig.into())
+            );
+            let auth = map(&[
+                (dida, sign(&vote(&call), &didak)),
+                (didb, sign(&vote(&call), &didbk)),
+            ]);
+            assert_eq!(MasterMod::verify_auth(call.encode(), &auth), Ok(2));
+
+            // votes are only valid for the round they were made in
+            Round::put(4);
+            assert_eq!(
+                MasterMod::verify_auth(call.encode(), &auth),
+                Err(MasterError::<Test>::BadSig.into())
+            );
+        });
+    }
+
+    /// Members can be added and removed one at a time and the vote requirement changed as long as
+    /// the requirement stays satisfiable.
+    #[test]
+    fn incremental_membership() {
+        ext().execute_with(|| {
+            let (dida, _) = newdid();
+            let (didb, _) = newdid();
+            MasterMod::set_members(
+                Origin::root(),
+                Membership {
+                    members: members(&[dida]),
+                    vote_requirement: 1,
+                },
+            )
+            .unwrap();
+            assert_eq!(Round::get(), 1);
+
+            let err = MasterMod::add_member(Origin::signed(0), didb, MemberDetail::default())
+                .unwrap_err();
+            assert_eq!(err, DispatchError::BadOrigin.into());
+            let err =
+                MasterMod::add_member(Origin::root(), dida, MemberDetail::default()).unwrap_err();
+            assert_eq!(err, MasterError::<Test>::AlreadyMember.into());
+            let err = MasterMod::add_member(Origin::root(), [9; 32], MemberDetail::default())
+                .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::MemberDidNotFound.into());
+            let err = MasterMod::add_member(
+                Origin::root(),
+                didb,
+                MemberDetail {
+                    weight: 0,
+                    allowed_calls: None,
+                },
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::ZeroMemberWeight.into());
+            MasterMod::add_member(Origin::root(), didb, MemberDetail::default()).unwrap();
+            assert_eq!(Members::get().members, members(&[dida, didb]));
+            assert_eq!(Round::get(), 2);
+
+            let err = MasterMod::set_threshold(Origin::root(), 3).unwrap_err();
+            assert_eq!(err, MasterError::<Test>::VoteRequirementTooHigh.into());
+            let err = MasterMod::set_threshold(Origin::root(), 0).unwrap_err();
+            assert_eq!(err, MasterError::<Test>::ZeroVoteRequirement.into());
+            MasterMod::set_threshold(Origin::root(), 2).unwrap();
+            assert_eq!(Members::get().vote_requirement, 2);
+
+            // removing a member would leave the requirement unsatisfiable
+            let err = MasterMod::remove_member(Origin::root(), didb).unwrap_err();
+            assert_eq!(err, MasterError::<Test>::VoteRequirementTooHigh.into());
+            MasterMod::set_threshold(Origin::root(), 1).unwrap();
+            let err = MasterMod::remove_member(Origin::root(), [9; 32]).unwrap_err();
+            assert_eq!(err, MasterError::<Test>::NotMember.into());
+            MasterMod::remove_member(Origin::root(), didb).unwrap();
+            assert_eq!(Members::get().members, members(&[dida]));
+            assert_eq!(Round::get(), 5);
+
+            // members of a full replacement must exist too
+            let err = MasterMod::set_members(
+                Origin::root(),
+                Membership {
+                    members: members(&[dida, [9; 32]]),
+                    vote_requirement: 1,
+                },
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::MemberDidNotFound.into());
+
+            assert_eq!(
+                master_events(),
+                vec![
+                    Event::<Test>::UnderNewOwnership,
+                    Event::<Test>::MemberAdded(didb),
+                    Event::<Test>::ThresholdSet(2),
+                    Event::<Test>::ThresholdSet(1),
+                    Event::<Test>::MemberRemoved(didb),
+                ]
+            );
+        });
+    }
+
+    /// A new membership only takes effect once enough of its members confirm it.
+    #[test]
+    fn handover() {
+        ext().execute_with(|| {
+            let (dida, didak) = newdid();
+            let (didb, didbk) = newdid();
+            let (didc, didck) = newdid();
+            Members::set(Membership {
+                members: members(&[dida]),
+                vote_requirement: 1,
+            });
+            let new_membership = Membership {
+                members: members(&[didb, didc]),
+                vote_requirement: 2,
+            };
+            let confirm = StateChange::MasterHandover(Payload {
+                proposal: new_membership.encode(),
+                round_no: Round::get(),
+            });
+            let err = MasterMod::confirm_handover(
+                Origin::signed(0),
+                map(&[
+                    (didb, sign(&confirm, &didbk)),
+                    (didc, sign(&confirm, &didck)),
+                ]),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::NoPendingHandover.into());
+
+            let err = MasterMod::begin_handover(
+                Origin::root(),
+                Membership {
+                    members: members(&[didb]),
+                    vote_requirement: 2,
+                },
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::VoteRequirementTooHigh.into());
+            MasterMod::begin_handover(Origin::root(), new_membership.clone()).unwrap();
+            assert_eq!(MasterMod::pending_handover(), Some(new_membership.clone()));
+            assert_eq!(Members::get().members, members(&[dida]));
+
+            // only new members count and they must meet the new requirement
+            let err = MasterMod::confirm_handover(
+                Origin::signed(0),
+                map(&[
+                    (dida, sign(&confirm, &didak)),
+                    (didb, sign(&confirm, &didbk)),
+                ]),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::NotMember.into());
+            let err = MasterMod::confirm_handover(
+                Origin::signed(0),
+                map(&[(didb, sign(&confirm, &didbk))]),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::InsufficientVotes.into());
+            let err = MasterMod::confirm_handover(
+                Origin::signed(0),
+                map(&[
+                    (didb, sign(&confirm, &didbk)),
+                    (didc, sign(&confirm, &didbk)),
+                ]),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::BadSig.into());
+
+            MasterMod::confirm_handover(
+                Origin::signed(0),
+                map(&[
+                    (didb, sign(&confirm, &didbk)),
+                    (didc, sign(&confirm, &didck)),
+                ]),
+            )
+            .unwrap();
+            assert_eq!(Members::get(), new_membership);
+            assert_eq!(MasterMod::pending_handover(), None);
+            assert_eq!(Round::get(), 1);
+
+            MasterMod::begin_handover(Origin::root(), new_membership).unwrap();
+            MasterMod::cancel_handover(Origin::root()).unwrap();
+            assert_eq!(MasterMod::pending_handover(), None);
+            assert_eq!(
+                master_events(),
+                vec![
+                    Event::<Test>::HandoverStarted,
+                    Event::<Test>::UnderNewOwnership,
+                    Event::<Test>::HandoverStarted,
+                    Event::<Test>::HandoverCancelled,
+                ]
+            );
+        });
+    }
+
+    /// Membership changes drop the handover in progress and a handover to members whose DIDs were
+    /// removed can't be confirmed.
+    #[test]
+    fn handover_invalidated() {
+        ext().execute_with(|| {
+            let (dida, _) = newdid();
+            let (didb, didbk) = newdid();
+            let (didc, didck) = newdid();
+            Members::set(Membership {
+                members: members(&[dida]),
+                vote_requirement: 1,
+            });
+            let new_membership = Membership {
+                members: members(&[didb, didc]),
+                vote_requirement: 2,
+            };
+
+            MasterMod::begin_handover(Origin::root(), new_membership.clone()).unwrap();
+            MasterMod::add_member(Origin::root(), didb, MemberDetail::default()).unwrap();
+            assert_eq!(MasterMod::pending_handover(), None);
+            MasterMod::begin_handover(Origin::root(), new_membership.clone()).unwrap();
+            MasterMod::set_members(
+                Origin::root(),
+                Membership {
+                    members: members(&[dida]),
+                    vote_requirement: 1,
+                },
+            )
+            .unwrap();
+            assert_eq!(MasterMod::pending_handover(), None);
+
+            MasterMod::begin_handover(Origin::root(), new_membership.clone()).unwrap();
+            crate::did::Dids::<Test>::remove(didc);
+            let confirm = StateChange::MasterHandover(Payload {
+                proposal: new_membership.encode(),
+                round_no: Round::get(),
+            });
+            let err = MasterMod::confirm_handover(
+                Origin::signed(0),
+                map(&[
+                    (didb, sign(&confirm, &didbk)),
+                    (didc, sign(&confirm, &didck)),
+                ]),
+            )
+            .unwrap_err();
+            assert_eq!(err, MasterError::<Test>::MemberDidNotFound.into());
+            assert_eq!(Members::get().members, members(&[dida]));
+        });
+    }
+
+    /// Vote for `call` in the current round
+    fn vote(call: &TestCall) -> StateChange {
+        StateChange::MasterVote(Payload {
+            proposal: call.encode(),
+            round_no: Round::get(),
+        })
+    }
+
+    fn master_events() -> Vec<Event<Test>> {
+        system::Module::<Test>::events()
+            .iter()
+            .filter_map(|event_record| {
+                let system::EventRecord::<TestEvent, H256> {
+                    phase,
+                    event,
+                    topics,
+                } = event_record;
+                assert_eq!(phase, &system::Phase::Initialization);
+                assert_eq!(topics, &vec![]);
+                match event {
+                    TestEvent::Master(e) => Some(e.clone()),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    fn map<K: Ord, V>(slice: &[(K, V)]) -> BTreeMap<K, V>
+    where
+        (K, V): Clone,
+    {
+        slice.iter().cloned().collect()
+    }
+
+    /// Members with a voting weight of 1 and no call restrictions
+    fn members(dids: &[Did]) -> BTreeMap<Did, MemberDetail> {
+        dids.iter()
+            .map(|did| (*did, MemberDetail::default()))
+            .collect()
     }
 
     fn set<E: Clone + Ord>(slice: &[E]) -> BTreeSet<E> {
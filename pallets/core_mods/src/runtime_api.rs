@@ -0,0 +1,59 @@
+use crate::anchor::AnchorQuery;
+use crate::attest::{Attestation, AttestationRecord, Topic};
+use crate::did::Did;
+use crate::master::{DryRun, PMAuth};
+use alloc::vec::Vec;
+use codec::Codec;
+use sp_runtime::DispatchError;
+
+sp_api::decl_runtime_apis! {
+    pub trait AnchorApi<Hash, BlockNumber, Moment> where
+        Hash: Codec,
+        BlockNumber: Codec,
+        Moment: Codec, {
+
+        /// Return the block number in which the given anchor was posted and the DID that signed
+        /// it, if any. Returns `None` if the anchor does not exist.
+        fn anchor_lookup(query: AnchorQuery<Hash>) -> Option<(BlockNumber, Option<Did>)>;
+
+        /// Return the timestamp set by the block this call is made at. Used to get the wall-clock
+        /// time of the block an anchor was posted in.
+        fn timestamp() -> Moment;
+    }
+}
+
+sp_api::decl_runtime_apis! {
+    pub trait AttestApi<BlockNumber> where
+        BlockNumber: Codec, {
+
+        /// Return the current attestation of the DID on the topic.
+        fn attestation(did: Did, topic: Topic) -> Attestation;
+
+        /// Return the past attestations of the DID on the topic, oldest first. The last record is
+        /// the current attestation.
+        fn attestation_history(did: Did, topic: Topic) -> Vec<AttestationRecord<BlockNumber>>;
+    }
+}
+
+sp_api::decl_runtime_apis! {
+    pub trait MasterApi<Event> where
+        Event: Codec, {
+
+        /// Run the SCALE encoded Call as root against the state of the block this call is made at
+        /// and return its result, weight and events. No state change is kept. Returns `None` if
+        /// the Call can't be decoded.
+        fn dry_run(proposal: Vec<u8>) -> Option<DryRun<Event>>;
+    }
+}
+
+sp_api::decl_runtime_apis! {
+    pub trait MasterVoteApi {
+        /// Return the bytes a member signs to vote for the SCALE encoded Call in the current round.
+        /// Fails if the bytes are not exactly the encoding of a Call.
+        fn vote_payload(proposal: Vec<u8>) -> Result<Vec<u8>, DispatchError>;
+
+        /// Check the votes in `auth` for the SCALE encoded Call against the current members and
+        /// round. Returns the total weight of the votes if they authorize the Call.
+        fn verify_auth(proposal: Vec<u8>, auth: PMAuth) -> Result<u64, DispatchError>;
+    }
+}
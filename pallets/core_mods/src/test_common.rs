@@ -6,10 +6,10 @@ use codec::{Decode, Encode};
 use frame_support::{
     dispatch::{DispatchInfo, DispatchResultWithPostInfo, Dispatchable, PostDispatchInfo},
     impl_outer_origin, parameter_types,
-    traits::UnfilteredDispatchable,
+    traits::{IsSubType, UnfilteredDispatchable},
     weights::{DispatchClass, GetDispatchInfo, Pays, Weight},
 };
-use frame_system as system;
+use frame_system::{self as system, EnsureRoot};
 pub use rand::random;
 use sp_core::{sr25519, Pair, H256};
 use sp_runtime::{
@@ -28,6 +28,7 @@ impl_outer_origin! {
 pub enum TestCall {
     Master(crate::master::Call<Test>),
     System(system::Call<Test>),
+    Batch(Vec<TestCall>),
 }
 
 impl Dispatchable for TestCall {
@@ -36,10 +37,7 @@ impl Dispatchable for TestCall {
     type Info = ();
     type PostInfo = PostDispatchInfo;
     fn dispatch(self, origin: Self::Origin) -> sp_runtime::DispatchResultWithInfo<Self::PostInfo> {
-        match self {
-            TestCall::Master(mc) => mc.dispatch_bypass_filter(origin),
-            TestCall::System(sc) => sc.dispatch_bypass_filter(origin),
-        }
+        self.dispatch_bypass_filter(origin)
     }
 }
 
@@ -50,10 +48,49 @@ impl UnfilteredDispatchable for TestCall {
         match self {
             TestCall::Master(mc) => mc.dispatch_bypass_filter(origin),
             TestCall::System(sc) => sc.dispatch_bypass_filter(origin),
+            TestCall::Batch(calls) => {
+                for call in calls {
+                    call.dispatch_bypass_filter(origin.clone())?;
+                }
+                Ok(().into())
+            }
+        }
+    }
+}
+
+/// Finds the calls of `TestCall::Batch`
+pub struct TestWrappedCalls;
+
+impl crate::master::WrappedCalls<TestCall> for TestWrappedCalls {
+    fn wrapped_calls(call: &TestCall) -> Vec<&TestCall> {
+        match call {
+            TestCall::Batch(calls) => calls.iter().collect(),
+            _ => Vec::new(),
         }
     }
 }
 
+impl From<crate::master::Call<Test>> for TestCall {
+    fn from(other: crate::master::Call<Test>) -> Self {
+        Self::Master(other)
+    }
+}
+
+impl IsSubType<crate::master::Call<Test>> for TestCall {
+    fn is_sub_type(&self) -> Option<&crate::master::Call<Test>> {
+        match self {
+            TestCall::Master(mc) => Some(mc),
+            _ => None,
+        }
+    }
+}
+
+impl From<system::Call<Test>> for TestCall {
+    fn from(other: system::Call<Test>) -> Self {
+        Self::System(other)
+    }
+}
+
 impl GetDispatchInfo for TestCall {
     fn get_dispatch_info(&self) -> DispatchInfo {
         DispatchInfo {
@@ -68,6 +105,8 @@ impl GetDispatchInfo for TestCall {
 pub enum TestEvent {
     Master(crate::master::Event<Test>),
     Anchor(crate::anchor::Event<Test>),
+    Attest(crate::attest::Event),
+    Scheduler(pallet_scheduler::Event<Test>),
     Unknown,
 }
 
@@ -95,6 +134,18 @@ impl From<crate::anchor::Event<Test>> for TestEvent {
     }
 }
 
+impl From<crate::attest::Event> for TestEvent {
+    fn from(other: crate::attest::Event) -> Self {
+        Self::Attest(other)
+    }
+}
+
+impl From<pallet_scheduler::Event<Test>> for TestEvent {
+    fn from(other: pallet_scheduler::Event<Test>) -> Self {
+        Self::Scheduler(other)
+    }
+}
+
 #[derive(Clone, Eq, Debug, PartialEq)]
 pub struct Test;
 
@@ -143,17 +194,52 @@ impl crate::blob::Trait for Test {
     type StorageWeight = StorageWeight;
 }
 
+parameter_types! {
+    pub const MaximumSchedulerWeight: Weight = 1_000_000;
+}
+
+impl pallet_scheduler::Config for Test {
+    type Event = TestEvent;
+    type Origin = Origin;
+    type PalletsOrigin = OriginCaller;
+    type Call = TestCall;
+    type MaximumWeight = MaximumSchedulerWeight;
+    type ScheduleOrigin = EnsureRoot<u64>;
+    type MaxScheduledPerBlock = ();
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const ProposalLifetime: u64 = 10;
+}
+
 impl crate::master::Trait for Test {
     type Event = TestEvent;
     type Call = TestCall;
+    type ProposalLifetime = ProposalLifetime;
+    type Scheduler = pallet_scheduler::Module<Test>;
+    type PalletsOrigin = OriginCaller;
+    type WrappedCalls = TestWrappedCalls;
 }
 
 impl crate::anchor::Trait for Test {
     type Event = TestEvent;
 }
 
+parameter_types! {
+    pub const MaxAttestationHistory: u32 = 5;
+    pub const MaxIriSize: u32 = 64;
+    pub const MaxDataIriSize: u32 = 256;
+    pub const AllowedIriSchemes: &'static [&'static [u8]] = &[b"ipfs", b"https", b"data", b"did"];
+}
+
 impl crate::attest::Trait for Test {
+    type Event = TestEvent;
     type StorageWeight = StorageWeight;
+    type MaxAttestationHistory = MaxAttestationHistory;
+    type MaxIriSize = MaxIriSize;
+    type MaxDataIriSize = MaxDataIriSize;
+    type AllowedIriSchemes = AllowedIriSchemes;
 }
 
 pub const ABBA: u64 = 0;
@@ -197,6 +283,15 @@ pub fn block_no() -> u64 {
     system::Module::<Test>::block_number()
 }
 
+/// move to block `n`, running the tasks scheduled up to and including it
+pub fn run_to_block(n: u64) {
+    use frame_support::traits::OnInitialize;
+    while block_no() < n {
+        system::Module::<Test>::set_block_number(block_no() + 1);
+        pallet_scheduler::Module::<Test>::on_initialize(block_no());
+    }
+}
+
 /// create a OneOf policy
 pub fn oneof(dids: &[Did]) -> Policy {
     Policy::OneOf(dids.iter().cloned().collect())
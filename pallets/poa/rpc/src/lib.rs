@@ -3,7 +3,7 @@ use codec::Codec;
 use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
 use jsonrpc_derive::rpc;
 pub use poa::runtime_api::PoAApi as PoARuntimeApi;
-pub use poa::EpochNo;
+pub use poa::{EpochDetail, EpochNo, SlotNo, ValidatorStatsPerEpoch};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
 use sp_runtime::{
@@ -32,6 +32,65 @@ pub trait PoAApi<BlockHash, AccountId, Balance> {
         epoch_no: EpochNo,
         at: Option<BlockHash>,
     ) -> Result<Balance>;
+
+    /// Return the current epoch
+    #[rpc(name = "poa_currentEpoch")]
+    fn current_epoch(&self, at: Option<BlockHash>) -> Result<EpochNo>;
+
+    /// Return the last slot of the current epoch
+    #[rpc(name = "poa_epochEndsAt")]
+    fn epoch_ends_at(&self, at: Option<BlockHash>) -> Result<SlotNo>;
+
+    /// Return details of given epoch, `null` if the epoch has not started yet
+    #[rpc(name = "poa_getEpochDetail")]
+    fn get_epoch_detail(
+        &self,
+        epoch_no: EpochNo,
+        at: Option<BlockHash>,
+    ) -> Result<Option<EpochDetail<Balance>>>;
+
+    /// Return details of the started epochs from `from` to `to`, both inclusive. At most
+    /// `MAX_EPOCHS_PER_QUERY` epochs starting from `from` are looked at.
+    #[rpc(name = "poa_getEpochDetailsInRange")]
+    fn get_epoch_details_in_range(
+        &self,
+        from: EpochNo,
+        to: EpochNo,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(EpochNo, EpochDetail<Balance>)>>;
+
+    /// Return blocks and rewards of the validator in given epoch, `null` if it was not active in
+    /// that epoch
+    #[rpc(name = "poa_getValidatorStats")]
+    fn get_validator_stats(
+        &self,
+        epoch_no: EpochNo,
+        validator: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<Option<ValidatorStatsPerEpoch<Balance>>>;
+
+    /// Return blocks and rewards of the validator in the epochs from `from` to `to`, both inclusive,
+    /// in which it was active. At most `MAX_EPOCHS_PER_QUERY` epochs starting from `from` are looked at.
+    #[rpc(name = "poa_getValidatorStatsInRange")]
+    fn get_validator_stats_in_range(
+        &self,
+        validator: AccountId,
+        from: EpochNo,
+        to: EpochNo,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(EpochNo, ValidatorStatsPerEpoch<Balance>)>>;
+
+    /// Return validators queued to become active
+    #[rpc(name = "poa_queuedValidators")]
+    fn queued_validators(&self, at: Option<BlockHash>) -> Result<Vec<AccountId>>;
+
+    /// Return validators queued for removal
+    #[rpc(name = "poa_validatorsToRemove")]
+    fn validators_to_remove(&self, at: Option<BlockHash>) -> Result<Vec<AccountId>>;
+
+    /// Return the pending hot swap as (validator to swap out, validator to swap in)
+    #[rpc(name = "poa_hotSwap")]
+    fn hot_swap(&self, at: Option<BlockHash>) -> Result<Option<(AccountId, AccountId)>>;
 }
 
 /// A struct that implements the [`PoAApi`].
@@ -99,4 +158,138 @@ where
                 data: Some(format!("{:?}", e).into()),
             })
     }
+
+    fn current_epoch(&self, at: Option<<Block as BlockT>::Hash>) -> Result<EpochNo> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.get_current_epoch(&at).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(4),
+            message: "Unable to query current epoch.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn epoch_ends_at(&self, at: Option<<Block as BlockT>::Hash>) -> Result<SlotNo> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.get_epoch_ends_at(&at).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(5),
+            message: "Unable to query ending slot of current epoch.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn get_epoch_detail(
+        &self,
+        epoch_no: EpochNo,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<EpochDetail<Balance>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.get_epoch_detail(&at, epoch_no).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(6),
+            message: "Unable to query epoch detail.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn get_epoch_details_in_range(
+        &self,
+        from: EpochNo,
+        to: EpochNo,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(EpochNo, EpochDetail<Balance>)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.get_epoch_details_in_range(&at, from, to)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(7),
+                message: "Unable to query epoch details.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn get_validator_stats(
+        &self,
+        epoch_no: EpochNo,
+        validator: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<ValidatorStatsPerEpoch<Balance>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.get_validator_stats(&at, epoch_no, validator)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(8),
+                message: "Unable to query validator stats.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn get_validator_stats_in_range(
+        &self,
+        validator: AccountId,
+        from: EpochNo,
+        to: EpochNo,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(EpochNo, ValidatorStatsPerEpoch<Balance>)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.get_validator_stats_in_range(&at, validator, from, to)
+            .map_err(|e| RpcError {
+                code: ErrorCode::ServerError(9),
+                message: "Unable to query validator stats.".into(),
+                data: Some(format!("{:?}", e).into()),
+            })
+    }
+
+    fn queued_validators(&self, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<AccountId>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.get_queued_validators(&at).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(10),
+            message: "Unable to query queued validators.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn validators_to_remove(&self, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<AccountId>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.get_validators_to_remove(&at).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(11),
+            message: "Unable to query validators to remove.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn hot_swap(
+        &self,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<(AccountId, AccountId)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.get_hot_swap(&at).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(12),
+            message: "Unable to query hot swap.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
 }
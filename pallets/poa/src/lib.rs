@@ -18,8 +18,8 @@ use frame_support::{
     weights::{Pays, Weight},
 };
 
-use frame_system::{self as system, ensure_root, RawOrigin};
-use sp_std::prelude::Vec;
+use frame_system::{self as system, ensure_root, ensure_signed, RawOrigin};
+use sp_std::{ops::RangeInclusive, prelude::Vec};
 
 extern crate alloc;
 
@@ -31,7 +31,7 @@ pub mod runtime_api;
 
 pub type EpochNo = u32;
 type EpochLen = u32;
-type SlotNo = u64;
+pub type SlotNo = u64;
 
 type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Config>::AccountId>>::Balance;
 /// Negative imbalance used to transfer transaction fess to block author
@@ -43,6 +43,7 @@ mod tests;
 
 /// Details per epoch
 #[derive(Encode, Decode, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EpochDetail<Balance> {
     /// Count of active validators in the epoch
     pub validator_count: u8,
@@ -64,18 +65,41 @@ pub struct EpochDetail<Balance> {
 
 /// Details per epoch per validator
 #[derive(Encode, Decode, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ValidatorStatsPerEpoch<Balance> {
     /// Count of blocks authored by the validator in the epoch
     pub block_count: EpochLen,
     /// Amount of locked rewards earned by the validator in the epoch. This reward is added to the
-    /// reserved balance (by calling `Balances::reserve`) of the validator. When unlocking (during PoS),
-    /// we move this reserved balance to free balance (by calling `Balances::unreserve` and pass amount)
-    /// for each epoch since beginning, thus giving us the gradual release functionality.
+    /// reserved balance (by calling `Balances::reserve`) of the validator. It is released in equal parts
+    /// over `LockedRewardReleaseEpochs` epochs following this one and the validator moves the released
+    /// amount to free balance (by calling `Balances::unreserve`) with `claim_unlocked_rewards`.
     pub locked_reward: Option<Balance>,
     /// Amount of unlocked rewards earned by the validator in the epoch.
     pub unlocked_reward: Option<Balance>,
 }
 
+/// Progress of a validator in claiming its released locked rewards
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Default)]
+pub struct RewardClaim<Balance> {
+    /// Locked rewards of all epochs up to and including this one are fully released
+    pub fully_released_upto: EpochNo,
+    /// Sum of the locked rewards of the epochs up to `fully_released_upto`
+    pub fully_released: Balance,
+    /// Total locked rewards claimed so far
+    pub claimed: Balance,
+}
+
+/// Policy to detect validators that are offline and remove them. Set by Root, no detection is done
+/// unless set.
+#[derive(Encode, Decode, Clone, PartialEq, Debug, Default)]
+pub struct OfflinePolicy {
+    /// A validator missing more than this percentage of its slots in an epoch is considered offline
+    /// in that epoch
+    pub max_missed_slots_pc: u8,
+    /// A validator offline for these many consecutive epochs is queued for removal
+    pub max_offline_epochs: EpochNo,
+}
+
 impl<Balance> EpochDetail<Balance> {
     /// Create a new epoch detail object. This is called when epoch is starting
     pub fn new(validator_count: u8, starting_slot: SlotNo, expected_ending_slot: SlotNo) -> Self {
@@ -113,6 +137,13 @@ impl BlockCount {
     }
 }
 
+/// Maximum number of epochs looked at in one claim of locked rewards, after the epochs whose locked
+/// rewards are already fully released. Bounds the weight of `claim_unlocked_rewards`.
+const MAX_EPOCHS_PER_CLAIM: EpochNo = 100;
+
+/// Maximum number of epochs looked at by a range query over epochs
+pub const MAX_EPOCHS_PER_QUERY: EpochNo = 100;
+
 /// Hardcoded treasury id; used to create the special Treasury account
 /// Must be exactly 8 characters long
 const TREASURY_ID: ModuleId = ModuleId(*b"Treasury");
@@ -178,6 +209,10 @@ decl_storage! {
         /// with the 2nd one.
         HotSwap get(fn hot_swap): Option<(T::AccountId, T::AccountId)>;
 
+        /// Set once the active validators are elected by another pallet, like staking, rather than
+        /// being added, removed and swapped by Root. Cannot be unset.
+        ValidatorsElectedExternally get(fn validators_elected_externally): bool;
+
         /// Holds transaction fees for a block. Every transaction fees detected through the imbalance
         /// accumulates the fees in this storage item. Once a block is done (`on_finalize`), the accumulated
         /// fees is given to the block author and this item is zeroed out.
@@ -213,6 +248,22 @@ decl_storage! {
 
         /// Boolean flag determining whether to generate emission rewards or not
         EmissionStatus get(fn emission_status) config(): bool;
+
+        /// Number of epochs over which the locked rewards of an epoch are released, an equal part at the
+        /// end of each epoch. 0 means locked rewards are not released.
+        LockedRewardReleaseEpochs get(fn locked_reward_release_epochs): EpochNo;
+
+        /// Policy to detect offline validators. No detection is done when not set.
+        OfflinePolicyConfig get(fn offline_policy): Option<OfflinePolicy>;
+
+        /// For each validator found offline, the last epoch it was offline in and the number of consecutive
+        /// epochs, ending with that epoch, it was offline in.
+        OfflineEpochs get(fn offline_epochs):
+            map hasher(blake2_128_concat) T::AccountId => Option<(EpochNo, EpochNo)>;
+
+        /// Progress of each validator in claiming its released locked rewards
+        RewardClaims get(fn reward_claims):
+            map hasher(blake2_128_concat) T::AccountId => RewardClaim<BalanceOf<T>>;
     }
 }
 
@@ -244,6 +295,16 @@ decl_event!(
 
         // Txn fees given to block author for a block no, (block no, validator id, fees)
         TxnFeesGiven(BlockNumber, AccountId, Balance),
+
+        // Validator missed more than the allowed slots in the epoch, (validator id, epoch, missed slots,
+        // consecutive epochs offline)
+        ValidatorOffline(AccountId, EpochNo, EpochLen, EpochNo),
+
+        // Validator offline for too many consecutive epochs is queued for removal
+        OfflineValidatorQueuedForRemoval(AccountId),
+
+        // Released locked rewards moved to free balance of the validator, (validator id, amount)
+        LockedRewardsClaimed(AccountId, Balance),
     }
 );
 
@@ -260,7 +321,10 @@ decl_error! {
         SwapInFailed,
         PercentageGreaterThan100,
         InsufficientFreeBalance,
-        InsufficientReservedBalance
+        InsufficientReservedBalance,
+        NoUnlockedRewards,
+        OfflineEpochsCannotBe0,
+        ValidatorsElectedExternally
     }
 }
 
@@ -287,6 +351,10 @@ decl_module! {
         #[weight = T::DbWeight::get().reads_writes(2 + *short_circuit as Weight, 1 + *short_circuit as Weight)]
         pub fn add_validator(origin, validator_id: T::AccountId, short_circuit: bool) -> dispatch::DispatchResultWithPostInfo {
             ensure_root(origin)?;
+            ensure!(
+                !Self::validators_elected_externally(),
+                Error::<T>::ValidatorsElectedExternally
+            );
             Self::add_validator_(validator_id, short_circuit)?;
             Ok(Pays::No.into())
         }
@@ -308,6 +376,10 @@ decl_module! {
         #[weight = T::DbWeight::get().reads_writes(3 + *short_circuit as Weight, 1 + *short_circuit as Weight)]
         pub fn remove_validator(origin, validator_id: T::AccountId, short_circuit: bool) -> dispatch::DispatchResultWithPostInfo {
             ensure_root(origin)?;
+            ensure!(
+                !Self::validators_elected_externally(),
+                Error::<T>::ValidatorsElectedExternally
+            );
             Self::remove_validator_(validator_id, short_circuit)?;
             Ok(Pays::No.into())
         }
@@ -328,6 +400,10 @@ decl_module! {
         #[weight = T::DbWeight::get().reads_writes(1, 1)]
         pub fn swap_validator(origin, old_validator_id: T::AccountId, new_validator_id: T::AccountId) -> dispatch::DispatchResultWithPostInfo {
             ensure_root(origin)?;
+            ensure!(
+                !Self::validators_elected_externally(),
+                Error::<T>::ValidatorsElectedExternally
+            );
             Self::swap_validator_(old_validator_id, new_validator_id)?;
             Ok(Pays::No.into())
         }
@@ -443,6 +519,51 @@ decl_module! {
             Ok(Pays::No.into())
         }
 
+        /// Set the policy to detect offline validators, i.e. the ones missing more than a percentage of their
+        /// slots in an epoch, and remove them once offline for a number of consecutive epochs. Passing `None`
+        /// stops the detection. Takes effect from the end of the current epoch.
+        #[weight = T::DbWeight::get().writes(1)]
+        pub fn set_offline_policy(origin, policy: Option<OfflinePolicy>) -> dispatch::DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            if let Some(p) = &policy {
+                ensure!(
+                    p.max_missed_slots_pc <= 100,
+                    Error::<T>::PercentageGreaterThan100
+                );
+                ensure!(p.max_offline_epochs > 0, Error::<T>::OfflineEpochsCannotBe0);
+            }
+            OfflinePolicyConfig::set(policy);
+            Ok(Pays::No.into())
+        }
+
+        /// Set the number of epochs over which the locked rewards of an epoch are released. Applies to
+        /// locked rewards of past epochs as well. 0 stops the release.
+        #[weight = T::DbWeight::get().writes(1)]
+        pub fn set_locked_reward_release_epochs(origin, epochs: EpochNo) -> dispatch::DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            LockedRewardReleaseEpochs::put(epochs);
+            Ok(Pays::No.into())
+        }
+
+        /// Move the locked rewards of the sender released so far, and not claimed yet, from reserved to
+        /// free balance. Never unreserves more than the reserved balance, which might be lower than the
+        /// outstanding locked rewards if reserved balance was moved with `force_transfer_both`.
+        /// When nothing is claimable but more epochs were looked at, the progress is saved so that the
+        /// next claim looks at later epochs. Only a claim that unreserves rewards is free.
+        /// # <weight>
+        /// 1 read each for `LockedRewardReleaseEpochs`, `Epoch`, `RewardClaims` and reserved balance and
+        /// at most `MAX_EPOCHS_PER_CLAIM` reads of `ValidatorStats`. 1 write each to `RewardClaims` and balance.
+        /// # </weight>
+        #[weight = T::DbWeight::get().reads_writes(4 + MAX_EPOCHS_PER_CLAIM as Weight, 2)]
+        pub fn claim_unlocked_rewards(origin) -> dispatch::DispatchResultWithPostInfo {
+            let validator = ensure_signed(origin)?;
+            if Self::claim_unlocked_rewards_(validator)? {
+                Ok(Pays::No.into())
+            } else {
+                Ok(Pays::Yes.into())
+            }
+        }
+
         /// Awards the complete txn fees to the block author if any and increment block count for
         /// current epoch and who authored it.
         fn on_finalize(block_no: T::BlockNumber) {
@@ -656,6 +777,125 @@ impl<T: Trait> Module<T> {
                 .unwrap_or_else(|| BalanceOf::<T>::zero())
     }
 
+    /// Details of the given epoch if it has started
+    pub fn epoch_detail(epoch_no: EpochNo) -> Option<EpochDetail<BalanceOf<T>>> {
+        if Epochs::<T>::contains_key(epoch_no) {
+            Some(Self::get_epoch_detail(epoch_no))
+        } else {
+            None
+        }
+    }
+
+    /// Details of the started epochs from `from` to `to`, both inclusive. Looks at most `MAX_EPOCHS_PER_QUERY`
+    /// epochs starting from `from`.
+    pub fn epoch_details_in_range(
+        from: EpochNo,
+        to: EpochNo,
+    ) -> Vec<(EpochNo, EpochDetail<BalanceOf<T>>)> {
+        Self::epochs_to_query(from, to)
+            .filter_map(|epoch_no| Self::epoch_detail(epoch_no).map(|d| (epoch_no, d)))
+            .collect()
+    }
+
+    /// Stats of the validator in the given epoch if it was active in that epoch
+    pub fn validator_stats(
+        epoch_no: EpochNo,
+        validator: &T::AccountId,
+    ) -> Option<ValidatorStatsPerEpoch<BalanceOf<T>>> {
+        if ValidatorStats::<T>::contains_key(epoch_no, validator) {
+            Some(Self::get_validator_stats_for_epoch(epoch_no, validator))
+        } else {
+            None
+        }
+    }
+
+    /// Stats of the validator in the epochs from `from` to `to`, both inclusive, in which it was active.
+    /// Looks at most `MAX_EPOCHS_PER_QUERY` epochs starting from `from`.
+    pub fn validator_stats_in_range(
+        validator: &T::AccountId,
+        from: EpochNo,
+        to: EpochNo,
+    ) -> Vec<(EpochNo, ValidatorStatsPerEpoch<BalanceOf<T>>)> {
+        Self::epochs_to_query(from, to)
+            .filter_map(|epoch_no| {
+                Self::validator_stats(epoch_no, validator).map(|s| (epoch_no, s))
+            })
+            .collect()
+    }
+
+    /// Epochs from `from` to `to`, both inclusive, capped to `MAX_EPOCHS_PER_QUERY` epochs
+    fn epochs_to_query(from: EpochNo, to: EpochNo) -> RangeInclusive<EpochNo> {
+        from..=to.min(from.saturating_add(MAX_EPOCHS_PER_QUERY - 1))
+    }
+
+    /// Locked rewards of the validator released so far but not claimed yet. The locked reward of an epoch is
+    /// released in equal parts at the end of each of the `LockedRewardReleaseEpochs` epochs following it.
+    /// Looks at most `MAX_EPOCHS_PER_CLAIM` epochs after the fully released ones; later epochs are looked
+    /// at in subsequent claims. Returns the claim progress updated with newly fully released epochs and the
+    /// claimable amount.
+    pub fn claimable_locked_rewards(
+        validator: &T::AccountId,
+    ) -> (RewardClaim<BalanceOf<T>>, BalanceOf<T>) {
+        let mut claim = Self::reward_claims(validator);
+        let release_epochs = Self::locked_reward_release_epochs();
+        if release_epochs == 0 {
+            return (claim, BalanceOf::<T>::zero());
+        }
+        // Rewards of the current epoch are known only once it ends
+        let last_ended_epoch = Self::epoch().saturating_sub(1);
+        let last_epoch = last_ended_epoch.min(
+            claim
+                .fully_released_upto
+                .saturating_add(MAX_EPOCHS_PER_CLAIM),
+        );
+        let mut partially_released = BalanceOf::<T>::zero();
+        for epoch_no in (claim.fully_released_upto + 1)..=last_epoch {
+            let locked = Self::get_validator_stats_for_epoch(epoch_no, validator)
+                .locked_reward
+                .unwrap_or_else(|| BalanceOf::<T>::zero());
+            let epochs_ended_since = last_ended_epoch - epoch_no;
+            if epochs_ended_since >= release_epochs {
+                // Epochs are in increasing order so fully released epochs precede the partially released ones
+                claim.fully_released_upto = epoch_no;
+                claim.fully_released = claim.fully_released.saturating_add(locked);
+            } else {
+                partially_released = partially_released.saturating_add(
+                    locked.saturating_mul(epochs_ended_since.into())
+                        / <BalanceOf<T>>::from(release_epochs),
+                );
+            }
+        }
+        let claimable = claim
+            .fully_released
+            .saturating_add(partially_released)
+            .saturating_sub(claim.claimed);
+        (claim, claimable)
+    }
+
+    /// Claim released locked rewards. Returns true if rewards were unreserved, false if only the progress
+    /// was saved. Check documentation of `claim_unlocked_rewards` dispatchable
+    fn claim_unlocked_rewards_(validator: T::AccountId) -> Result<bool, dispatch::DispatchError> {
+        let scanned_upto = Self::reward_claims(&validator).fully_released_upto;
+        let (mut claim, claimable) = Self::claimable_locked_rewards(&validator);
+        let amount = claimable.min(T::Currency::reserved_balance(&validator));
+        if amount.is_zero() {
+            // Epochs without rewards, like the ones before the validator joined, would otherwise be looked
+            // at in every claim and block claims of later epochs
+            ensure!(
+                claim.fully_released_upto > scanned_upto,
+                Error::<T>::NoUnlockedRewards
+            );
+            <RewardClaims<T>>::insert(&validator, claim);
+            return Ok(false);
+        }
+        // Only count what was actually unreserved as claimed so that the rest can be claimed later
+        let amount = amount.saturating_sub(T::Currency::unreserve(&validator, amount));
+        claim.claimed = claim.claimed.saturating_add(amount);
+        <RewardClaims<T>>::insert(&validator, claim);
+        Self::deposit_event(RawEvent::LockedRewardsClaimed(validator, amount));
+        Ok(!amount.is_zero())
+    }
+
     /// Takes a validator id and a mutable vector of validator ids and remove any occurrence from
     /// the mutable vector. Returns number of removed occurrences
     fn remove_validator_id(id: &T::AccountId, validators: &mut Vec<T::AccountId>) -> usize {
@@ -667,7 +907,7 @@ impl<T: Trait> Module<T> {
     /// Get maximum active validators (allowed) on current epoch end and for the next epoch. Reads
     /// from the tentative value set in storage and if set (>0), "take" it, i.e. read and reset to 0.
     /// Updates `MaxActiveValidators` as well
-    fn get_and_set_max_active_validators_on_epoch_end() -> u8 {
+    pub fn get_and_set_max_active_validators_on_epoch_end() -> u8 {
         let max_v = MaxActiveValidatorsTentative::take();
         if max_v > 0 {
             MaxActiveValidators::put(max_v);
@@ -779,7 +1019,7 @@ impl<T: Trait> Module<T> {
 
     /// Set next epoch duration such that it is >= `MinEpochLength` and also a multiple of the
     /// number of active validators
-    fn set_next_epoch_end(current_slot_no: SlotNo, active_validator_count: u8) -> SlotNo {
+    pub fn set_next_epoch_end(current_slot_no: SlotNo, active_validator_count: u8) -> SlotNo {
         let min_epoch_len = Self::get_and_set_min_epoch_length_on_epoch_end();
         let active_validator_count = active_validator_count as EpochLen;
         let rem = min_epoch_len % active_validator_count;
@@ -819,7 +1059,7 @@ impl<T: Trait> Module<T> {
     }
 
     /// Return the current slot no if accessible
-    fn current_slot_no() -> Option<SlotNo> {
+    pub fn current_slot_no() -> Option<SlotNo> {
         let digest = <system::Module<T>>::digest();
         let logs = digest.logs();
         // The logs are added in `on_slot` function of slots pallet by the validator
@@ -1147,16 +1387,16 @@ impl<T: Trait> Module<T> {
         epoch_detail.emission_for_validators = Some(BalanceOf::<T>::zero());
     }
 
-    /// Mint emission rewards and disburse them among validators and treasury. Returns true if emission
-    /// rewards were minted, false otherwise.
+    /// Mint emission rewards and disburse them among validators and treasury as per the blocks authored
+    /// by each validator, as counted by `count_validator_blocks`. Returns true if emission rewards were
+    /// minted, false otherwise.
     fn mint_emission_rewards_if_needed(
         current_epoch_no: EpochNo,
         ending_slot: SlotNo,
         epoch_detail: &mut EpochDetail<BalanceOf<T>>,
+        max_blocks: &BlockCount,
+        validator_block_counts: BTreeMap<T::AccountId, EpochLen>,
     ) -> bool {
-        // Get blocks authored by each validator
-        let (max_blocks, validator_block_counts) = Self::count_validator_blocks(current_epoch_no);
-
         // If emission is disabled, return false after updating validators' block count and rewards
         if !Self::emission_status() {
             Self::update_epoch_detail_for_worthless_epoch(
@@ -1188,7 +1428,7 @@ impl<T: Trait> Module<T> {
 
         // Get slots received by each validator
         let slots_per_validator =
-            Self::get_slots_per_validator(&epoch_detail, ending_slot, &max_blocks);
+            Self::get_slots_per_validator(&epoch_detail, ending_slot, max_blocks);
         runtime_print!("slots_per_validator {}", slots_per_validator);
 
         // It might happen that `slots_per_validator` > `max_blocks` as the network went down for
@@ -1223,7 +1463,7 @@ impl<T: Trait> Module<T> {
     }
 
     /// Track epoch ending slot and rewards for validators and treasury and mint and disburse the rewards.
-    fn update_details_for_ending_epoch(current_slot_no: SlotNo) {
+    pub fn update_details_for_ending_epoch(current_slot_no: SlotNo) {
         let current_epoch_no = Self::epoch();
         if current_epoch_no == 0 {
             print("Starting up, no epoch to update");
@@ -1240,13 +1480,87 @@ impl<T: Trait> Module<T> {
 
         runtime_print!("Epoch {} ending at slot {}", current_epoch_no, ending_slot);
 
-        Self::mint_emission_rewards_if_needed(current_epoch_no, ending_slot, &mut epoch_detail);
+        // Get blocks authored by each validator
+        let (max_blocks, validator_block_counts) = Self::count_validator_blocks(current_epoch_no);
+        // Offline validators are detected from the same block counts
+        let offline_check =
+            Self::offline_policy().map(|policy| (policy, validator_block_counts.clone()));
+
+        Self::mint_emission_rewards_if_needed(
+            current_epoch_no,
+            ending_slot,
+            &mut epoch_detail,
+            &max_blocks,
+            validator_block_counts,
+        );
+
+        if let Some((policy, validator_block_counts)) = offline_check {
+            let slots_per_validator =
+                Self::get_slots_per_validator(&epoch_detail, ending_slot, &max_blocks);
+            Self::detect_offline_validators(
+                &policy,
+                current_epoch_no,
+                slots_per_validator,
+                validator_block_counts,
+            );
+        }
 
         Epochs::<T>::insert(current_epoch_no, epoch_detail);
 
         Self::deposit_event(RawEvent::EpochEnds(current_epoch_no, ending_slot));
     }
 
+    /// Find validators that missed more than `max_missed_slots_pc` percent of their slots in the ending epoch
+    /// and queue the ones offline for `max_offline_epochs` consecutive epochs for removal. The removal
+    /// is skipped if it would leave no validators.
+    fn detect_offline_validators(
+        policy: &OfflinePolicy,
+        current_epoch_no: EpochNo,
+        slots_per_validator: EpochLen,
+        validator_block_counts: BTreeMap<T::AccountId, EpochLen>,
+    ) {
+        if slots_per_validator == 0 {
+            return;
+        }
+        for (v, block_count) in validator_block_counts {
+            let missed = slots_per_validator.saturating_sub(block_count);
+            // Comparing as `missed / slots_per_validator > max_missed_slots_pc / 100`
+            let offline = (missed as u64) * 100
+                > (policy.max_missed_slots_pc as u64) * (slots_per_validator as u64);
+            if !offline {
+                <OfflineEpochs<T>>::remove(&v);
+                continue;
+            }
+            let consecutive = match Self::offline_epochs(&v) {
+                Some((last, count)) if last + 1 == current_epoch_no => count + 1,
+                _ => 1,
+            };
+            Self::deposit_event(RawEvent::ValidatorOffline(
+                v.clone(),
+                current_epoch_no,
+                missed,
+                consecutive,
+            ));
+            if consecutive >= policy.max_offline_epochs {
+                // Not short circuiting as the epoch is ending anyway. `RemoveValidators` is applied when
+                // the validator set is updated right after.
+                let queued = Self::validators_to_remove().contains(&v)
+                    || Self::remove_validator_(v.clone(), false)
+                        .map_err(|e| {
+                            print("Cannot remove offline validator");
+                            print(e);
+                        })
+                        .is_ok();
+                if queued {
+                    <OfflineEpochs<T>>::remove(&v);
+                    Self::deposit_event(RawEvent::OfflineValidatorQueuedForRemoval(v));
+                    continue;
+                }
+            }
+            <OfflineEpochs<T>>::insert(&v, (current_epoch_no, consecutive));
+        }
+    }
+
     /// Set the current epoch, starting slot, expected ending of the current epoch and active validator
     /// count for this epoch
     fn update_details_on_new_epoch(
@@ -1297,6 +1611,44 @@ impl<T: Trait> Module<T> {
         }
     }
 
+    /// Hand over electing the active validators to another pallet. Root can no longer add, remove
+    /// or swap validators and any queued additions, removals or swap are dropped. Returns the current
+    /// active validators which remain active until the elected ones are set.
+    pub fn hand_over_validator_set() -> Vec<T::AccountId> {
+        ValidatorsElectedExternally::put(true);
+        <QueuedValidators<T>>::kill();
+        <RemoveValidators<T>>::kill();
+        <HotSwap<T>>::kill();
+        Self::active_validators()
+    }
+
+    /// Make the elected validators active. Called at the end of an epoch by the pallet electing the
+    /// validators, which should stop electing the validators queued for removal, like the ones found
+    /// offline, before electing. Any still elected are skipped unless that leaves no validator. An empty election keeps the current validators. Rotates the session
+    /// if the validators changed and returns the count of active validators.
+    pub fn set_elected_validators(mut elected: Vec<T::AccountId>) -> u8 {
+        let validators_to_remove = <RemoveValidators<T>>::take();
+        if elected.iter().any(|v| !validators_to_remove.contains(v)) {
+            elected.retain(|v| !validators_to_remove.contains(v));
+        }
+        let active_validators = Self::active_validators();
+        if elected.is_empty() {
+            return active_validators.len() as u8;
+        }
+        let active_set = active_validators.iter().collect::<BTreeSet<_>>();
+        let elected_set = elected.iter().collect::<BTreeSet<_>>();
+        if active_set == elected_set {
+            return active_validators.len() as u8;
+        }
+        let active_validator_count = elected.len() as u8;
+        <ActiveValidators<T>>::put(elected);
+        // Same as in `should_end_session`, rotating now makes the new validators take effect in
+        // the next session.
+        ForcedSessionRotation::put(true);
+        <pallet_session::Module<T>>::rotate_session();
+        active_validator_count
+    }
+
     /// Accumulate txn fees for the block. Whenever a negative imbalance is creates (because txn
     /// fees is being paid), the fees gets added to `TxnFees` which is emptied (zeroed) when block is
     /// fully formed, i.e. `on_finalize`
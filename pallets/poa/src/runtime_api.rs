@@ -1,10 +1,12 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use super::EpochNo;
+use super::{EpochDetail, EpochNo, SlotNo, ValidatorStatsPerEpoch};
 use codec::Codec;
 use sp_runtime::traits::{MaybeDisplay, MaybeFromStr};
+use sp_std::prelude::Vec;
 
 sp_api::decl_runtime_apis! {
+    #[api_version(2)]
     pub trait PoAApi<AccountId, Balance> where
         AccountId: Codec + MaybeDisplay + MaybeFromStr,
         Balance: Codec + MaybeDisplay + MaybeFromStr, {
@@ -20,5 +22,39 @@ sp_api::decl_runtime_apis! {
 
         /// Return total (validators + treasury) emission rewards for given epoch
         fn get_total_emission_in_epoch(epoch_no: EpochNo) -> Balance;
+
+        /// Return the current epoch
+        fn get_current_epoch() -> EpochNo;
+
+        /// Return the last slot of the current epoch
+        fn get_epoch_ends_at() -> SlotNo;
+
+        /// Return details of given epoch, `None` if the epoch has not started yet
+        fn get_epoch_detail(epoch_no: EpochNo) -> Option<EpochDetail<Balance>>;
+
+        /// Return details of the started epochs from `from` to `to`, both inclusive. At most
+        /// `MAX_EPOCHS_PER_QUERY` epochs starting from `from` are looked at.
+        fn get_epoch_details_in_range(from: EpochNo, to: EpochNo) -> Vec<(EpochNo, EpochDetail<Balance>)>;
+
+        /// Return blocks and rewards of the validator in given epoch, `None` if it was not active
+        /// in that epoch
+        fn get_validator_stats(epoch_no: EpochNo, validator: AccountId) -> Option<ValidatorStatsPerEpoch<Balance>>;
+
+        /// Return blocks and rewards of the validator in the epochs from `from` to `to`, both inclusive,
+        /// in which it was active. At most `MAX_EPOCHS_PER_QUERY` epochs starting from `from` are looked at.
+        fn get_validator_stats_in_range(
+            validator: AccountId,
+            from: EpochNo,
+            to: EpochNo
+        ) -> Vec<(EpochNo, ValidatorStatsPerEpoch<Balance>)>;
+
+        /// Return validators queued to become active
+        fn get_queued_validators() -> Vec<AccountId>;
+
+        /// Return validators queued for removal
+        fn get_validators_to_remove() -> Vec<AccountId>;
+
+        /// Return the pending hot swap as (validator to swap out, validator to swap in)
+        fn get_hot_swap() -> Option<(AccountId, AccountId)>;
     }
 }
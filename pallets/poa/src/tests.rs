@@ -3,7 +3,7 @@
 use super::*;
 
 use frame_support::{
-    assert_err, assert_ok, impl_outer_origin, parameter_types,
+    assert_err, assert_ok, impl_outer_event, impl_outer_origin, parameter_types,
     sp_runtime::{
         testing::{Header, UintAuthorityId},
         traits::{BlakeTwo256, ConvertInto, IdentityLookup, OpaqueKeys},
@@ -21,6 +21,17 @@ impl_outer_origin! {
     pub enum Origin for TestRuntime {}
 }
 
+mod poa {
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for TestRuntime {
+        system<T>,
+        poa<T>,
+    }
+}
+
 #[derive(Clone, Eq, Debug, PartialEq)]
 pub struct TestRuntime;
 
@@ -51,7 +62,7 @@ impl system::Config for TestRuntime {
     type AccountId = u64;
     type Lookup = IdentityLookup<Self::AccountId>;
     type Header = Header;
-    type Event = ();
+    type Event = TestEvent;
     type BlockHashCount = BlockHashCount;
     type DbWeight = ();
     type BlockWeights = ();
@@ -76,7 +87,7 @@ impl balances::Config for TestRuntime {
 }
 
 impl Trait for TestRuntime {
-    type Event = ();
+    type Event = TestEvent;
     type Currency = Balances;
 }
 
@@ -1534,10 +1545,14 @@ fn emission_rewards_status() {
             PoAModule::increment_current_epoch_block_count(val_id3);
         }
         let mut epoch_detail = EpochDetail::new(3, 1, 39);
+        let (max_blocks, validator_block_counts) =
+            PoAModule::count_validator_blocks(current_epoch_no);
         assert!(PoAModule::mint_emission_rewards_if_needed(
             current_epoch_no,
             30,
-            &mut epoch_detail
+            &mut epoch_detail,
+            &max_blocks,
+            validator_block_counts
         ));
 
         // Emission rewards being generated
@@ -1562,10 +1577,14 @@ fn emission_rewards_status() {
         }
 
         let mut epoch_detail = EpochDetail::new(3, 40, 78);
+        let (max_blocks, validator_block_counts) =
+            PoAModule::count_validator_blocks(current_epoch_no);
         assert!(!PoAModule::mint_emission_rewards_if_needed(
             current_epoch_no,
             60,
-            &mut epoch_detail
+            &mut epoch_detail,
+            &max_blocks,
+            validator_block_counts
         ));
 
         // No emission rewards were generated
@@ -1739,6 +1758,377 @@ fn expected_treasury_account_id() {
     );
 }
 
+#[test]
+fn claim_unlocked_rewards() {
+    new_test_ext().execute_with(|| {
+        let val_id = 1;
+        let other_val_id = 2;
+        let stats = |locked_reward| ValidatorStatsPerEpoch {
+            block_count: 5,
+            locked_reward: Some(locked_reward),
+            unlocked_reward: Some(10),
+        };
+        // Validator earned locked rewards in epochs 1 and 2
+        <ValidatorStats<TestRuntime>>::insert(1, val_id, stats(400));
+        <ValidatorStats<TestRuntime>>::insert(2, val_id, stats(800));
+        PoAModule::credit_locked_emission_rewards_to_validator(&val_id, 1200);
+        assert_eq!(
+            <TestRuntime as Trait>::Currency::reserved_balance(&val_id),
+            1200
+        );
+
+        // Locked rewards are not released unless Root sets the release period
+        Epoch::put(5);
+        assert_eq!(PoAModule::claimable_locked_rewards(&val_id).1, 0);
+        assert_err!(
+            PoAModule::claim_unlocked_rewards(Origin::signed(val_id)),
+            Error::<TestRuntime>::NoUnlockedRewards
+        );
+        assert!(PoAModule::set_locked_reward_release_epochs(Origin::signed(val_id), 4).is_err());
+        assert_ok!(PoAModule::set_locked_reward_release_epochs(
+            RawOrigin::Root.into(),
+            4
+        ));
+
+        // Nothing is released until the epoch following the rewarding one ends
+        Epoch::put(2);
+        assert_eq!(PoAModule::claimable_locked_rewards(&val_id).1, 0);
+        assert_err!(
+            PoAModule::claim_unlocked_rewards(Origin::signed(val_id)),
+            Error::<TestRuntime>::NoUnlockedRewards
+        );
+
+        // 1/4th of epoch 1's locked reward
+        Epoch::put(3);
+        assert_eq!(PoAModule::claimable_locked_rewards(&val_id).1, 100);
+        assert_ok!(PoAModule::claim_unlocked_rewards(Origin::signed(val_id)));
+        assert_eq!(<TestRuntime as Trait>::Currency::free_balance(&val_id), 100);
+        assert_eq!(
+            <TestRuntime as Trait>::Currency::reserved_balance(&val_id),
+            1100
+        );
+        assert_eq!(PoAModule::reward_claims(&val_id).claimed, 100);
+        assert_eq!(PoAModule::claimable_locked_rewards(&val_id).1, 0);
+
+        // 3/4th of epoch 1's and half of epoch 2's locked reward, less the claimed
+        Epoch::put(5);
+        assert_eq!(PoAModule::claimable_locked_rewards(&val_id).1, 600);
+        assert_ok!(PoAModule::claim_unlocked_rewards(Origin::signed(val_id)));
+        assert_eq!(<TestRuntime as Trait>::Currency::free_balance(&val_id), 700);
+
+        // Part of the reserved balance is moved away so only the remaining reserved balance is claimed
+        let _ = <TestRuntime as Trait>::Currency::deposit_creating(&other_val_id, 1);
+        assert_ok!(PoAModule::force_transfer_both(
+            RawOrigin::Root.into(),
+            val_id,
+            other_val_id,
+            0,
+            100
+        ));
+        Epoch::put(7);
+        let (claim, claimable) = PoAModule::claimable_locked_rewards(&val_id);
+        assert_eq!(claimable, 500);
+        assert_eq!(claim.fully_released_upto, 2);
+        assert_eq!(claim.fully_released, 1200);
+        assert_ok!(PoAModule::claim_unlocked_rewards(Origin::signed(val_id)));
+        assert_eq!(
+            <TestRuntime as Trait>::Currency::free_balance(&val_id),
+            1100
+        );
+        assert_eq!(
+            <TestRuntime as Trait>::Currency::reserved_balance(&val_id),
+            0
+        );
+        assert_eq!(
+            PoAModule::reward_claims(&val_id),
+            RewardClaim {
+                fully_released_upto: 2,
+                fully_released: 1200,
+                claimed: 1100
+            }
+        );
+        assert_eq!(PoAModule::claimable_locked_rewards(&val_id).1, 100);
+        assert_err!(
+            PoAModule::claim_unlocked_rewards(Origin::signed(val_id)),
+            Error::<TestRuntime>::NoUnlockedRewards
+        );
+
+        // Claims start looking from the epoch after the fully released ones
+        <ValidatorStats<TestRuntime>>::insert(8, val_id, stats(40));
+        PoAModule::credit_locked_emission_rewards_to_validator(&val_id, 40);
+        Epoch::put(20);
+        assert_eq!(PoAModule::claimable_locked_rewards(&val_id).1, 140);
+        assert_ok!(PoAModule::claim_unlocked_rewards(Origin::signed(val_id)));
+        assert_eq!(
+            <TestRuntime as Trait>::Currency::free_balance(&val_id),
+            1140
+        );
+        assert_eq!(PoAModule::reward_claims(&val_id).fully_released_upto, 15);
+    });
+}
+
+#[test]
+fn claim_unlocked_rewards_after_late_join() {
+    new_test_ext().execute_with(|| {
+        let val_id = 1;
+        assert_ok!(PoAModule::set_locked_reward_release_epochs(
+            RawOrigin::Root.into(),
+            1
+        ));
+        // Validator earned its first locked reward in epoch 150
+        <ValidatorStats<TestRuntime>>::insert(
+            150,
+            val_id,
+            ValidatorStatsPerEpoch {
+                block_count: 5,
+                locked_reward: Some(300),
+                unlocked_reward: Some(10),
+            },
+        );
+        PoAModule::credit_locked_emission_rewards_to_validator(&val_id, 300);
+        Epoch::put(152);
+
+        // The first claim only looks at epochs 1 to 100 which have no rewards but its progress is kept.
+        // Nothing is claimed so it is not free.
+        assert_eq!(PoAModule::claimable_locked_rewards(&val_id).1, 0);
+        assert_eq!(
+            PoAModule::claim_unlocked_rewards(Origin::signed(val_id))
+                .unwrap()
+                .pays_fee,
+            Pays::Yes
+        );
+        assert_eq!(PoAModule::reward_claims(&val_id).fully_released_upto, 100);
+        assert_eq!(<TestRuntime as Trait>::Currency::free_balance(&val_id), 0);
+
+        // The next claim reaches epoch 150 and is free
+        assert_eq!(PoAModule::claimable_locked_rewards(&val_id).1, 300);
+        assert_eq!(
+            PoAModule::claim_unlocked_rewards(Origin::signed(val_id))
+                .unwrap()
+                .pays_fee,
+            Pays::No
+        );
+        assert_eq!(<TestRuntime as Trait>::Currency::free_balance(&val_id), 300);
+        assert_eq!(
+            PoAModule::reward_claims(&val_id),
+            RewardClaim {
+                fully_released_upto: 150,
+                fully_released: 300,
+                claimed: 300
+            }
+        );
+
+        // Nothing left to look at or claim
+        assert_err!(
+            PoAModule::claim_unlocked_rewards(Origin::signed(val_id)),
+            Error::<TestRuntime>::NoUnlockedRewards
+        );
+
+        // An account that was never a validator pays for its claims
+        let non_val_id = 9;
+        for _ in 0..2 {
+            assert_eq!(
+                PoAModule::claim_unlocked_rewards(Origin::signed(non_val_id))
+                    .unwrap()
+                    .pays_fee,
+                Pays::Yes
+            );
+        }
+        assert_eq!(
+            PoAModule::reward_claims(&non_val_id).fully_released_upto,
+            150
+        );
+        let err = PoAModule::claim_unlocked_rewards(Origin::signed(non_val_id)).unwrap_err();
+        assert_eq!(err.error, Error::<TestRuntime>::NoUnlockedRewards.into());
+        assert_eq!(err.post_info.pays_fee, Pays::Yes);
+    });
+}
+
+#[test]
+fn offline_validators() {
+    new_test_ext().execute_with(|| {
+        let policy = OfflinePolicy {
+            max_missed_slots_pc: 50,
+            max_offline_epochs: 2,
+        };
+        assert!(PoAModule::set_offline_policy(Origin::signed(1), Some(policy.clone())).is_err());
+        assert_err!(
+            PoAModule::set_offline_policy(
+                RawOrigin::Root.into(),
+                Some(OfflinePolicy {
+                    max_missed_slots_pc: 101,
+                    max_offline_epochs: 2,
+                })
+            ),
+            Error::<TestRuntime>::PercentageGreaterThan100
+        );
+        assert_err!(
+            PoAModule::set_offline_policy(
+                RawOrigin::Root.into(),
+                Some(OfflinePolicy {
+                    max_missed_slots_pc: 50,
+                    max_offline_epochs: 0,
+                })
+            ),
+            Error::<TestRuntime>::OfflineEpochsCannotBe0
+        );
+        assert_eq!(PoAModule::offline_policy(), None);
+        assert_ok!(PoAModule::set_offline_policy(
+            RawOrigin::Root.into(),
+            Some(policy.clone())
+        ));
+        assert_eq!(PoAModule::offline_policy(), Some(policy.clone()));
+
+        <ActiveValidators<TestRuntime>>::put(vec![1, 2, 3]);
+        let counts = |c: &[(u64, EpochLen)]| c.iter().cloned().collect::<BTreeMap<_, _>>();
+
+        // Validator 2 misses 6 of its 10 slots
+        PoAModule::detect_offline_validators(&policy, 1, 10, counts(&[(1, 10), (2, 4), (3, 10)]));
+        assert_eq!(PoAModule::offline_epochs(1), None);
+        assert_eq!(PoAModule::offline_epochs(2), Some((1, 1)));
+        assert_eq!(PoAModule::offline_epochs(3), None);
+
+        // Missing exactly half of the slots is not offline
+        PoAModule::detect_offline_validators(&policy, 2, 10, counts(&[(1, 10), (2, 5), (3, 10)]));
+        assert_eq!(PoAModule::offline_epochs(2), None);
+
+        PoAModule::detect_offline_validators(&policy, 3, 10, counts(&[(1, 2), (2, 0), (3, 10)]));
+        assert_eq!(PoAModule::offline_epochs(1), Some((3, 1)));
+        assert_eq!(PoAModule::offline_epochs(2), Some((3, 1)));
+
+        // Offline epochs not consecutive so count resets
+        PoAModule::detect_offline_validators(&policy, 5, 10, counts(&[(1, 0), (2, 10), (3, 10)]));
+        assert_eq!(PoAModule::offline_epochs(1), Some((5, 1)));
+        assert_eq!(PoAModule::offline_epochs(2), None);
+        assert!(PoAModule::validators_to_remove().is_empty());
+
+        // Validator 1 offline for 2 consecutive epochs and is queued for removal
+        PoAModule::detect_offline_validators(&policy, 6, 10, counts(&[(1, 0), (2, 10), (3, 10)]));
+        assert_eq!(PoAModule::offline_epochs(1), None);
+        assert_eq!(PoAModule::validators_to_remove(), vec![1]);
+
+        // The last validator is not removed
+        <ActiveValidators<TestRuntime>>::put(vec![4]);
+        <RemoveValidators<TestRuntime>>::put(Vec::<u64>::new());
+        PoAModule::detect_offline_validators(&policy, 7, 10, counts(&[(4, 0)]));
+        PoAModule::detect_offline_validators(&policy, 8, 10, counts(&[(4, 0)]));
+        assert!(PoAModule::validators_to_remove().is_empty());
+        assert_eq!(PoAModule::offline_epochs(4), Some((8, 2)));
+
+        // No detection when there are no slots
+        PoAModule::detect_offline_validators(&policy, 9, 0, counts(&[(4, 0)]));
+        assert_eq!(PoAModule::offline_epochs(4), Some((8, 2)));
+    });
+}
+
+#[test]
+fn epoch_and_validator_stats_queries() {
+    new_test_ext().execute_with(|| {
+        let val_id = 1;
+        let stats = |block_count| ValidatorStatsPerEpoch {
+            block_count,
+            locked_reward: None,
+            unlocked_reward: Some(10),
+        };
+        for epoch_no in 1..=150 {
+            Epochs::<TestRuntime>::insert(epoch_no, EpochDetail::new(2, epoch_no as u64 * 10, 0));
+            // Validator not active in even epochs
+            if epoch_no % 2 == 1 {
+                <ValidatorStats<TestRuntime>>::insert(epoch_no, val_id, stats(epoch_no));
+            }
+        }
+
+        assert_eq!(PoAModule::epoch_detail(0), None);
+        assert_eq!(PoAModule::epoch_detail(3), Some(EpochDetail::new(2, 30, 0)));
+        assert_eq!(PoAModule::epoch_detail(151), None);
+        assert_eq!(PoAModule::validator_stats(3, &val_id), Some(stats(3)));
+        assert_eq!(PoAModule::validator_stats(4, &val_id), None);
+        assert_eq!(PoAModule::validator_stats(3, &2), None);
+
+        let details = PoAModule::epoch_details_in_range(0, 4);
+        assert_eq!(
+            details.iter().map(|(e, _)| *e).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(details[1].1, EpochDetail::new(2, 20, 0));
+        assert!(PoAModule::epoch_details_in_range(5, 4).is_empty());
+        assert!(PoAModule::epoch_details_in_range(151, 200).is_empty());
+
+        // Range is capped to `MAX_EPOCHS_PER_QUERY` epochs
+        let details = PoAModule::epoch_details_in_range(10, 200);
+        assert_eq!(details.len(), MAX_EPOCHS_PER_QUERY as usize);
+        assert_eq!(details.last().unwrap().0, 109);
+        assert_eq!(
+            PoAModule::epoch_details_in_range(140, EpochNo::MAX).len(),
+            11
+        );
+
+        assert_eq!(
+            PoAModule::validator_stats_in_range(&val_id, 2, 7),
+            vec![(3, stats(3)), (5, stats(5)), (7, stats(7))]
+        );
+        assert_eq!(
+            PoAModule::validator_stats_in_range(&val_id, 1, 200).len(),
+            50
+        );
+        assert!(PoAModule::validator_stats_in_range(&2, 1, 200).is_empty());
+    });
+}
+
+#[test]
+fn offline_validator_removed_on_epoch_end() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(PoAModule::set_offline_policy(
+            RawOrigin::Root.into(),
+            Some(OfflinePolicy {
+                max_missed_slots_pc: 50,
+                max_offline_epochs: 1,
+            })
+        ));
+        for id in &[1, 2, 3] {
+            PoAModule::add_validator_(*id, false).unwrap();
+        }
+        PoAModule::update_active_validators_if_needed();
+        // Epoch of 27 slots gives each validator 9 slots
+        assert_eq!(PoAModule::set_next_epoch_end(1, 3), 27);
+        PoAModule::update_details_on_new_epoch(1, 1, 3);
+
+        // Validator 2 produces no blocks
+        for _ in 0..9 {
+            PoAModule::increment_current_epoch_block_count(1);
+            PoAModule::increment_current_epoch_block_count(3);
+        }
+        PoAModule::update_details_for_ending_epoch(28);
+
+        let events = System::events()
+            .into_iter()
+            .map(|r| r.event)
+            .collect::<Vec<_>>();
+        assert!(events.contains(&TestEvent::poa(RawEvent::ValidatorOffline(2, 1, 9, 1))));
+        assert!(
+            events.contains(&TestEvent::poa(RawEvent::OfflineValidatorQueuedForRemoval(
+                2
+            )))
+        );
+        // Only validator 2 was offline
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| matches!(e, TestEvent::poa(RawEvent::ValidatorOffline(..))))
+                .count(),
+            1
+        );
+        assert_eq!(PoAModule::validators_to_remove(), vec![2]);
+        assert_eq!(PoAModule::offline_epochs(2), None);
+
+        // The removal takes effect with the validator set update following the epoch end
+        assert_eq!(PoAModule::update_active_validators_if_needed(), (true, 2));
+        assert_eq!(PoAModule::active_validators(), vec![1, 3]);
+        assert!(PoAModule::validators_to_remove().is_empty());
+    });
+}
+
 #[test]
 fn force_transfer_both() {
     new_test_ext().execute_with(|| {
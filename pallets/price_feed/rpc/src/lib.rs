@@ -2,6 +2,7 @@ pub use self::gen_client::Client as PriceFeedClient;
 use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
 use jsonrpc_derive::rpc;
 pub use price_feed::runtime_api::PriceFeedApi as PriceFeedRuntimeApi;
+use price_feed::{CurrencySymbolPair, FixedPrice};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
 use sp_runtime::{generic::BlockId, traits::Block as BlockT};
@@ -9,13 +10,41 @@ use std::sync::Arc;
 
 #[rpc]
 pub trait PriceFeedApi<BlockHash> {
-    /// Gets the price of Dock/USD from pallet's storage
+    /// Gets the price of Dock/USD from pallet's storage, multiplied by 1000
     #[rpc(name = "price_feed_tokenUsdPrice")]
     fn token_usd_price(&self, at: Option<BlockHash>) -> Result<Option<u32>>;
 
-    /// Gets the price of Dock/USD from EVM contract
+    /// Gets the price of Dock/USD from EVM contract, multiplied by 1000
     #[rpc(name = "price_feed_tokenUsdPriceFromContract")]
     fn token_usd_price_from_contract(&self, at: Option<BlockHash>) -> Result<Option<u32>>;
+
+    /// Gets the price of the given pair from pallet's storage
+    #[rpc(name = "price_feed_price")]
+    fn price(&self, pair: CurrencySymbolPair, at: Option<BlockHash>) -> Result<Option<FixedPrice>>;
+
+    /// Gets the price of the given pair from its EVM contract
+    #[rpc(name = "price_feed_priceFromContract")]
+    fn price_from_contract(
+        &self,
+        pair: CurrencySymbolPair,
+        at: Option<BlockHash>,
+    ) -> Result<Option<FixedPrice>>;
+
+    /// Gets the time weighted average price of the given pair over the configured window
+    #[rpc(name = "price_feed_twapPrice")]
+    fn twap_price(
+        &self,
+        pair: CurrencySymbolPair,
+        at: Option<BlockHash>,
+    ) -> Result<Option<FixedPrice>>;
+
+    /// Gets the median price of the given pair over the configured window
+    #[rpc(name = "price_feed_medianPrice")]
+    fn median_price(
+        &self,
+        pair: CurrencySymbolPair,
+        at: Option<BlockHash>,
+    ) -> Result<Option<FixedPrice>>;
 }
 
 /// A struct that implements the [`PriceFeedApi`].
@@ -67,4 +96,68 @@ where
                 data: Some(format!("{:?}", e).into()),
             })
     }
+
+    fn price(
+        &self,
+        pair: CurrencySymbolPair,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<FixedPrice>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.price(&at, pair).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(3),
+            message: "Unable to query price of pair.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn price_from_contract(
+        &self,
+        pair: CurrencySymbolPair,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<FixedPrice>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.price_from_contract(&at, pair).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(4),
+            message: "Unable to query price of pair from contract.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn twap_price(
+        &self,
+        pair: CurrencySymbolPair,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<FixedPrice>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.twap_price(&at, pair).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(5),
+            message: "Unable to query time weighted average price of pair.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+
+    fn median_price(
+        &self,
+        pair: CurrencySymbolPair,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<FixedPrice>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+            // If the block hash is not supplied assume the best block.
+            self.client.info().best_hash));
+        api.median_price(&at, pair).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(6),
+            message: "Unable to query median price of pair.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
 }
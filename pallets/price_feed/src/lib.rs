@@ -1,23 +1,33 @@
-//! Periodically fetches price of DOCK/USD from smart contract running on EVM and stores the price in its storage.
-//! The periodicity and contract configuration like address, query method and return value type can be configured by root.  
+//! Periodically fetches prices of currency pairs like DOCK/USD from smart contracts running on EVM and stores the prices
+//! in its storage. Each pair has its own contract. The periodicity and contract configuration like address, query method
+//! and return value type can be configured by root.
+//! Alternatively, the price of a pair can be submitted by a set of oracle accounts configured by root. The submissions
+//! are aggregated by taking their median after rejecting outliers.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode};
 use frame_support::{
-    decl_error, decl_event, decl_module, decl_storage, dispatch, fail,
+    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure, fail,
+    storage::migration::take_storage_value,
     traits::Get,
     weights::{Pays, Weight},
+    IterableStorageMap,
 };
-use frame_system::{self as system, ensure_root};
+use frame_system::{self as system, ensure_root, ensure_signed};
 use pallet_evm::{GasWeightMapping, Runner};
 use sp_core::{H160, U256};
-use sp_runtime::traits::{UniqueSaturatedInto, Zero};
+use sp_runtime::{
+    traits::{UniqueSaturatedInto, Zero},
+    Permill,
+};
 use sp_std::{prelude::Vec, vec};
 
+pub use common::{CurrencySymbolPair, FixedPrice};
+
 pub mod runtime_api;
 pub mod util;
-use util::{decode as eth_decode, ParamType};
+use util::{decode as eth_decode, ParamType, Token};
 
 use common::PriceProvider;
 
@@ -29,6 +39,28 @@ mod tests;
 const DUMMY_SOURCE: H160 = H160::zero();
 const ZERO_VALUE: U256 = U256::zero();
 const GAS_LIMIT: u64 = u64::MAX;
+/// Prices were multiplied by 1000 before the number of decimals was explicit
+const LEGACY_PRICE_DECIMALS: u8 = 3;
+
+/// The price multiplied by 1000 as it was before the number of decimals was explicit. Returns `None` if it
+/// doesn't fit in a `u32`.
+pub fn to_legacy_price(price: FixedPrice) -> Option<u32> {
+    price
+        .amount_with_decimals(LEGACY_PRICE_DECIMALS)
+        .filter(|amount| *amount <= u32::MAX as u64)
+        .map(|amount| amount as u32)
+}
+
+/// Number of decimals in the price returned by the contract
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PriceDecimals {
+    /// The price always has these many decimals
+    Fixed(u8),
+    /// The decimals are read by calling the contract with this input, i.e. the encoded ABI of its
+    /// function `decimals` which returns an `uint8`
+    FromContract(Vec<u8>),
+}
 
 #[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -41,6 +73,26 @@ pub struct ContractConfig {
     /// ABI of the return type of function corresponding to `query_abi_encoded`.
     /// At the time of writing, this is `[uint(80), int(256), uint(256), uint(256), uint(80)]`
     pub return_val_abi: Vec<ParamType>,
+    /// Number of decimals in the price returned by `query_abi_encoded`.
+    pub decimals: PriceDecimals,
+}
+
+impl ContractConfig {
+    /// Contract config from human readable signatures. `function_signature` is the signature of the function to
+    /// get the price, like `latestRoundData()`, and `return_types` the types it returns, like `(uint80,int256)`.
+    pub fn from_signatures(
+        address: H160,
+        function_signature: &str,
+        return_types: &str,
+        decimals: PriceDecimals,
+    ) -> Result<Self, util::Error> {
+        Ok(ContractConfig {
+            address,
+            query_abi_encoded: util::encode_function_call(function_signature, &[])?,
+            return_val_abi: util::parse_types(return_types)?,
+            decimals,
+        })
+    }
 }
 
 impl Default for ContractConfig {
@@ -49,45 +101,134 @@ impl Default for ContractConfig {
             address: DUMMY_SOURCE,
             query_abi_encoded: vec![],
             return_val_abi: vec![],
+            decimals: PriceDecimals::Fixed(0),
         }
     }
 }
 
-pub trait Config: system::Config + pallet_evm::Config {
+/// Config of the oracles submitting the price of a pair
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OracleConfig<AccountId> {
+    /// Accounts allowed to submit the price
+    pub oracles: Vec<AccountId>,
+    /// Minimum number of submissions, after rejecting outliers, for the price to be updated
+    pub min_submissions: u32,
+    /// Submissions deviating from the median of all submissions by more than this fraction of the median
+    /// are rejected as outliers
+    pub max_deviation: Permill,
+    /// Number of decimals in the submitted prices
+    pub decimals: u8,
+}
+
+/// Where the price of a pair comes from
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PriceSource<AccountId> {
+    /// Price is read periodically from a contract on EVM
+    Contract(ContractConfig),
+    /// Price is submitted by oracles
+    Oracles(OracleConfig<AccountId>),
+}
+
+/// Contract config before the number of decimals was explicit. Used only for storage migration.
+#[derive(Decode)]
+struct ContractConfigV1 {
+    address: H160,
+    query_abi_encoded: Vec<u8>,
+    return_val_abi: Vec<ParamType>,
+}
+
+impl From<ContractConfigV1> for ContractConfig {
+    fn from(config: ContractConfigV1) -> Self {
+        ContractConfig {
+            address: config.address,
+            query_abi_encoded: config.query_abi_encoded,
+            return_val_abi: config.return_val_abi,
+            decimals: PriceDecimals::Fixed(LEGACY_PRICE_DECIMALS),
+        }
+    }
+}
+
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+enum Releases {
+    /// Price sources and fixed point prices are kept per currency pair
+    V1,
+}
+
+pub trait Config: system::Config + pallet_evm::Config + timestamp::Config {
     /// The overarching event type.
     type Event: From<Event> + Into<<Self as system::Config>::Event>;
+    /// Maximum number of past prices kept per pair. When exceeded, the oldest price is dropped
+    /// from the history.
+    type MaxPriceHistory: Get<u32>;
 }
 
 decl_storage! {
     trait Store for Module<T: Config> as PriceFeedModule {
-        /// Stores contract configuration for DOCK/USD pair. This is the only pair that is relevant right now.
-        /// If we need more pairs in future, we can change this to map with a runtime storage migration
-        pub ContractConfigStore get(fn contract_config): Option<ContractConfig>;
+        /// Stores the source, i.e. a contract or oracles, of each currency pair whose price is tracked
+        pub PriceSources get(fn price_source):
+            map hasher(blake2_128_concat) CurrencySymbolPair => Option<PriceSource<T::AccountId>>;
 
-        /// Price of DOCK/USD pair
-        pub Price get(fn price): Option<u32>;
+        /// Prices submitted by oracles for each pair since the submissions were last aggregated. Holds at
+        /// most one submission per oracle.
+        pub OracleSubmissions get(fn oracle_submissions):
+            map hasher(blake2_128_concat) CurrencySymbolPair => Vec<(T::AccountId, u64)>;
 
-        /// Last update to price by reading from contract was done at this block number
-        LastPriceUpdateAt get(fn last_price_update_at): Option<T::BlockNumber>;
+        /// Price of each currency pair
+        pub Price get(fn price): map hasher(blake2_128_concat) CurrencySymbolPair => Option<FixedPrice>;
+
+        /// Last update to price of the pair by reading from contract was done at this block number
+        LastPriceUpdateAt get(fn last_price_update_at):
+            map hasher(blake2_128_concat) CurrencySymbolPair => Option<T::BlockNumber>;
 
         /// Price update frequency. After every few blocks the price is read from the contract and
         /// the storage item `Price` is updated unless update frequency is set to `None` or 0.
         PriceUpdateFreq get(fn price_update_freq): Option<u32>;
+
+        /// Maximum age in seconds of the round read from the contract. A round last updated before
+        /// this is considered stale and the price is not updated. No check is done when set to `None` or 0.
+        MaxPriceAge get(fn max_price_age): Option<u64>;
+
+        /// Past prices of each pair as `(block number, price)`, oldest first, including the current one.
+        /// Holds at most `MaxPriceHistory` prices.
+        pub PriceHistory get(fn price_history):
+            map hasher(blake2_128_concat) CurrencySymbolPair => Vec<(T::BlockNumber, FixedPrice)>;
+
+        /// Number of blocks, ending at the current block, over which the time weighted average and median
+        /// prices are computed. The whole price history is used when set to `None` or 0.
+        PriceWindow get(fn price_window): Option<u32>;
+
+        // Storage version of the module. `None` means the contract config, price and last update
+        // block are still single values for DOCK/USD, the price being a `u32` multiplied by 1000
+        // and the contract config having no decimals. They are migrated to the DOCK/USD pair on
+        // runtime upgrade.
+        StorageVersion build(|_| Some(Releases::V1)): Option<Releases>;
     }
 
     add_extra_genesis {
-        config(contract_config): ContractConfig;
+        config(contract_configs): Vec<(CurrencySymbolPair, ContractConfig)>;
         build(|config| {
-            ContractConfigStore::put(config.contract_config.clone());
+            for (pair, contract_config) in config.contract_configs.iter() {
+                PriceSources::<T>::insert(pair, PriceSource::Contract(contract_config.clone()));
+            }
         })
     }
 }
 
 decl_event!(
     pub enum Event {
-        ContractConfigSet(ContractConfig),
-        PriceSet(u32),
+        ContractConfigSet(CurrencySymbolPair, ContractConfig),
+        OracleConfigSet(CurrencySymbolPair),
+        PriceSourceRemoved(CurrencySymbolPair),
+        PriceSubmitted(CurrencySymbolPair, FixedPrice),
+        PriceSet(CurrencySymbolPair, FixedPrice),
         UpdateFrequencySet(u32),
+        MaxPriceAgeSet(u64),
+        PriceWindowSet(u32),
+        /// The round read from the contract of the pair was stale, or too few oracle submissions were
+        /// left after rejecting outliers, so the previous price is kept
+        PriceStale(CurrencySymbolPair),
     }
 );
 
@@ -98,6 +239,23 @@ decl_error! {
         ResponseParsingFailed,
         ResponseDoesNotHaveIntegerPrice,
         PriceIsZero,
+        /// The round was not answered in itself, i.e. `answeredInRound` is less than `roundId`, or
+        /// was never updated
+        RoundIncomplete,
+        /// The round was last updated longer than `MaxPriceAge` ago
+        PriceTooOld,
+        /// The price returned by the contract is negative or does not fit in a `u64`
+        PriceOverflow,
+        /// The decimals returned by the contract do not fit in a `u8`
+        InvalidDecimals,
+        PriceSourceNotFound,
+        /// The price of the pair is not submitted by oracles
+        NotOracleSource,
+        /// The sender is not an oracle of the pair
+        NotAnOracle,
+        /// There must be at least 1 oracle and the minimum number of submissions can't be 0 or more than
+        /// the number of oracles
+        InvalidOracleConfig,
     }
 }
 
@@ -107,17 +265,67 @@ decl_module! {
 
         type Error = Error<T>;
 
-        /// Set the config of the contract that has the price of DOCK/USD pair. Only callable by Root.
-        #[weight = T::DbWeight::get().writes(1)]
-        pub fn set_contract_config(origin, config: ContractConfig) -> dispatch::DispatchResultWithPostInfo {
+        /// Set the config of the contract that has the price of the given pair. Only callable by Root.
+        /// Replaces any existing source of the pair.
+        #[weight = T::DbWeight::get().writes(2)]
+        pub fn set_contract_config(origin, pair: CurrencySymbolPair, config: ContractConfig) -> dispatch::DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            PriceSources::<T>::insert(&pair, PriceSource::Contract(config.clone()));
+            OracleSubmissions::<T>::remove(&pair);
+            Self::deposit_event(Event::ContractConfigSet(pair, config));
+            Ok(Pays::No.into())
+        }
+
+        /// Set the oracles that submit the price of the given pair. Replaces any existing source of the pair and
+        /// discards submissions not aggregated yet. Only callable by Root.
+        #[weight = T::DbWeight::get().writes(2)]
+        pub fn set_oracle_config(origin, pair: CurrencySymbolPair, config: OracleConfig<T::AccountId>) -> dispatch::DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            ensure!(
+                config.min_submissions > 0 && config.min_submissions as usize <= config.oracles.len(),
+                Error::<T>::InvalidOracleConfig
+            );
+            PriceSources::<T>::insert(&pair, PriceSource::Oracles(config));
+            OracleSubmissions::<T>::remove(&pair);
+            Self::deposit_event(Event::OracleConfigSet(pair));
+            Ok(Pays::No.into())
+        }
+
+        /// Stop tracking the price of the given pair, removing its source and price. Only callable by Root.
+        #[weight = T::DbWeight::get().writes(5)]
+        pub fn remove_price_source(origin, pair: CurrencySymbolPair) -> dispatch::DispatchResultWithPostInfo {
             ensure_root(origin)?;
-            ContractConfigStore::put(config.clone());
-            Self::deposit_event(Event::ContractConfigSet(config));
+            PriceSources::<T>::take(&pair).ok_or_else(|| Error::<T>::PriceSourceNotFound)?;
+            OracleSubmissions::<T>::remove(&pair);
+            Price::remove(&pair);
+            LastPriceUpdateAt::<T>::remove(&pair);
+            PriceHistory::<T>::remove(&pair);
+            Self::deposit_event(Event::PriceSourceRemoved(pair));
+            Ok(Pays::No.into())
+        }
+
+        /// Submit the price of the given pair, with the decimals set in the pair's oracle config. Only callable by
+        /// the pair's oracles. Replaces the sender's earlier submission if the submissions were not aggregated since.
+        #[weight = T::DbWeight::get().reads_writes(2, 1)]
+        pub fn submit_price(origin, pair: CurrencySymbolPair, price: u64) -> dispatch::DispatchResultWithPostInfo {
+            let oracle = ensure_signed(origin)?;
+            let config = match Self::price_source(&pair) {
+                Some(PriceSource::Oracles(config)) => config,
+                Some(PriceSource::Contract(_)) => fail!(Error::<T>::NotOracleSource),
+                None => fail!(Error::<T>::PriceSourceNotFound),
+            };
+            ensure!(config.oracles.contains(&oracle), Error::<T>::NotAnOracle);
+            ensure!(price > 0, Error::<T>::PriceIsZero);
+            OracleSubmissions::<T>::mutate(&pair, |submissions| {
+                submissions.retain(|(o, _)| *o != oracle);
+                submissions.push((oracle, price));
+            });
+            Self::deposit_event(Event::PriceSubmitted(pair, FixedPrice::new(price, config.decimals)));
             Ok(Pays::No.into())
         }
 
         /// Set the price update frequency in terms of number of blocks. After every `freq` number of blocks,
-        /// the price of DOCK/USD pair is read from contract and storage item `Price` is updated.
+        /// the price of each pair is read from its contract and storage item `Price` is updated.
         /// Only callable by Root.
         #[weight = T::DbWeight::get().writes(1)]
         pub fn set_update_frequency(origin, freq: u32) -> dispatch::DispatchResultWithPostInfo {
@@ -127,44 +335,240 @@ decl_module! {
             Ok(Pays::No.into())
         }
 
+        /// Set the maximum age in seconds of the round read from the contract for its price to be accepted.
+        /// Setting it to 0 disables the check. Only callable by Root.
+        #[weight = T::DbWeight::get().writes(1)]
+        pub fn set_max_price_age(origin, max_age: u64) -> dispatch::DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            MaxPriceAge::put(max_age);
+            Self::deposit_event(Event::MaxPriceAgeSet(max_age));
+            Ok(Pays::No.into())
+        }
+
+        /// Set the number of blocks over which the time weighted average and median prices are computed.
+        /// Setting it to 0 uses the whole price history. Only callable by Root.
+        #[weight = T::DbWeight::get().writes(1)]
+        pub fn set_price_window(origin, window: u32) -> dispatch::DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            PriceWindow::put(window);
+            Self::deposit_event(Event::PriceWindowSet(window));
+            Ok(Pays::No.into())
+        }
+
         fn on_initialize(current_block_no: T::BlockNumber) -> Weight {
-            Self::update_price_if_stale(current_block_no).unwrap_or_else(|e| {
-                sp_runtime::print(e);
-                // Using larger weight than would occur most times to avoid code complexity
-                T::DbWeight::get().reads(2)
-            })
+            let sources: Vec<(CurrencySymbolPair, PriceSource<T::AccountId>)> = PriceSources::<T>::iter().collect();
+            let mut weight = T::DbWeight::get().reads(sources.len() as u64);
+            for (pair, source) in sources {
+                let w = match source {
+                    PriceSource::Contract(_) => Self::update_price_if_stale(&pair, current_block_no).unwrap_or_else(|e| {
+                        sp_runtime::print(e);
+                        // Using larger weight than would occur most times to avoid code complexity
+                        T::DbWeight::get().reads(2)
+                    }),
+                    PriceSource::Oracles(config) => Self::aggregate_submissions(&pair, &config),
+                };
+                weight = weight.saturating_add(w);
+            }
+            weight
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            if StorageVersion::get().is_none() {
+                let weight = Self::migrate_to_pairs();
+                StorageVersion::put(Releases::V1);
+                weight + T::DbWeight::get().reads_writes(1, 1)
+            } else {
+                T::DbWeight::get().reads(1)
+            }
         }
     }
 }
 
 impl<T: Config> Module<T> {
-    /// Update price in pallet's storage from contract if the price is stale
+    /// Update price of the pair in pallet's storage from contract if the price is stale
     fn update_price_if_stale(
+        pair: &CurrencySymbolPair,
         current_block_no: T::BlockNumber,
     ) -> Result<Weight, dispatch::DispatchError> {
-        let (stale, mut weight) = Self::is_price_stale(current_block_no);
+        let (stale, mut weight) = Self::is_price_stale(pair, current_block_no);
         if stale {
-            weight = weight.saturating_add(Self::update_price_from_contract()?);
+            weight = weight.saturating_add(Self::update_price_from_contract(pair)?);
             Ok(weight)
         } else {
             Ok(weight)
         }
     }
 
-    /// Gets price of pair DOCK/USD from contract and update pallet storage item `Price`
-    fn update_price_from_contract() -> Result<Weight, dispatch::DispatchError> {
-        let (price, weight) = Self::get_price_from_contract()?;
-        Price::put(price);
-        LastPriceUpdateAt::<T>::put(<system::Module<T>>::block_number());
-        Self::deposit_event(Event::PriceSet(price));
-        Ok(weight + T::DbWeight::get().writes(2))
+    /// Gets price of the pair from contract and update pallet storage item `Price`. If the round read
+    /// from the contract is stale, the previous price is kept and event `PriceStale` is emitted.
+    fn update_price_from_contract(
+        pair: &CurrencySymbolPair,
+    ) -> Result<Weight, dispatch::DispatchError> {
+        match Self::get_price_from_contract(pair) {
+            Ok((price, weight)) => Ok(weight.saturating_add(Self::set_price(pair, price))),
+            Err(e)
+                if e == dispatch::DispatchError::from(Error::<T>::RoundIncomplete)
+                    || e == dispatch::DispatchError::from(Error::<T>::PriceTooOld) =>
+            {
+                // Try again after the update frequency rather than reading the contract in every block
+                LastPriceUpdateAt::<T>::insert(pair, <system::Module<T>>::block_number());
+                Self::deposit_event(Event::PriceStale(pair.clone()));
+                // The gas used by the contract call isn't returned with the error. Stale rounds should be rare
+                // so accounting only for the storage accesses.
+                Ok(T::DbWeight::get().reads_writes(3, 1))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Contract config of the pair if its price is read from a contract
+    pub fn contract_config(pair: &CurrencySymbolPair) -> Option<ContractConfig> {
+        match Self::price_source(pair) {
+            Some(PriceSource::Contract(config)) => Some(config),
+            _ => None,
+        }
+    }
+
+    /// Update the price of the pair to the median of the oracle submissions if there are at least `min_submissions`
+    /// of them. Submissions deviating from their median by more than `max_deviation` are rejected first and if too
+    /// few are left, the previous price is kept and event `PriceStale` is emitted. The submissions are cleared in
+    /// either case.
+    fn aggregate_submissions(
+        pair: &CurrencySymbolPair,
+        config: &OracleConfig<T::AccountId>,
+    ) -> Weight {
+        let submissions = Self::oracle_submissions(pair);
+        let min = config.min_submissions as usize;
+        if submissions.len() < min {
+            return T::DbWeight::get().reads(1);
+        }
+        OracleSubmissions::<T>::remove(pair);
+        let weight = T::DbWeight::get().reads_writes(1, 1);
+
+        let mut prices: Vec<u64> = submissions.into_iter().map(|(_, p)| p).collect();
+        prices.sort_unstable();
+        let median = Self::median_of_sorted(&prices);
+        let max_deviation = config.max_deviation.mul_floor(median);
+        prices.retain(|p| {
+            let deviation = if *p > median { p - median } else { median - p };
+            deviation <= max_deviation
+        });
+        if prices.len() < min {
+            Self::deposit_event(Event::PriceStale(pair.clone()));
+            return weight;
+        }
+        let price = FixedPrice::new(Self::median_of_sorted(&prices), config.decimals);
+        weight.saturating_add(Self::set_price(pair, price))
+    }
+
+    /// Median of the given non-empty sorted amounts. For an even number of amounts, the mean of the middle two
+    /// is taken.
+    fn median_of_sorted(amounts: &[u64]) -> u64 {
+        let mid = amounts.len() / 2;
+        if amounts.len() % 2 == 0 {
+            ((amounts[mid - 1] as u128 + amounts[mid] as u128) / 2) as u64
+        } else {
+            amounts[mid]
+        }
     }
 
-    /// Return if price is stale and need to be updated and the weight consumed while finding that out
-    pub fn is_price_stale(current_block_no: T::BlockNumber) -> (bool, Weight) {
+    /// Store the price of the pair read at the current block and add it to the pair's price history
+    fn set_price(pair: &CurrencySymbolPair, price: FixedPrice) -> Weight {
+        let current_block_no = <system::Module<T>>::block_number();
+        Price::insert(pair, price);
+        LastPriceUpdateAt::<T>::insert(pair, current_block_no);
+        PriceHistory::<T>::mutate(pair, |history| {
+            // Only the last price read in a block is kept
+            if history.last().map(|(b, _)| *b) == Some(current_block_no) {
+                history.pop();
+            }
+            history.push((current_block_no, price));
+            let max = T::MaxPriceHistory::get() as usize;
+            if history.len() > max {
+                history.drain(..history.len() - max);
+            }
+        });
+        Self::deposit_event(Event::PriceSet(pair.clone(), price));
+        T::DbWeight::get().reads_writes(1, 3)
+    }
+
+    /// Prices of the pair in effect during the window ending at the current block, as `(block number, price)`
+    /// oldest first. The first price might have been set before the window started. Also returns the
+    /// current block number and the block number at which the window starts.
+    fn prices_in_window(pair: &CurrencySymbolPair) -> (Vec<(u64, FixedPrice)>, u64, u64) {
+        let now: u64 = <system::Module<T>>::block_number().unique_saturated_into();
+        let window = Self::price_window().unwrap_or(0) as u64;
+        let start = if window == 0 {
+            0
+        } else {
+            now.saturating_sub(window)
+        };
+        let history = Self::price_history(pair);
+        // The price set last at or before the start of the window is still in effect when the window starts
+        let first = history
+            .iter()
+            .position(|(b, _)| UniqueSaturatedInto::<u64>::unique_saturated_into(*b) > start)
+            .unwrap_or(history.len())
+            .saturating_sub(1);
+        let prices: Vec<(u64, FixedPrice)> = history[first..]
+            .iter()
+            .map(|(b, p)| ((*b).unique_saturated_into(), *p))
+            .collect();
+        (prices, now, start)
+    }
+
+    /// Time weighted average price of the pair over the window ending at the current block. Each price is
+    /// weighted by the number of blocks it was in effect during the window. The average has the decimals of
+    /// the latest price. Returns `None` if the pair has no price history or a price can't be expressed with
+    /// those decimals.
+    pub fn twap_price(pair: &CurrencySymbolPair) -> Option<FixedPrice> {
+        let (prices, now, start) = Self::prices_in_window(pair);
+        let (_, latest) = prices.last()?;
+        let decimals = latest.decimals;
+        let mut weighted_sum: u128 = 0;
+        let mut total_blocks: u64 = 0;
+        for (i, (block, price)) in prices.iter().enumerate() {
+            let from = sp_std::cmp::max(*block, start);
+            let to = prices.get(i + 1).map(|(b, _)| *b).unwrap_or(now);
+            let blocks = to.saturating_sub(from);
+            weighted_sum += price.amount_with_decimals(decimals)? as u128 * blocks as u128;
+            total_blocks += blocks;
+        }
+        if total_blocks == 0 {
+            // All prices were set in the current block
+            Some(*latest)
+        } else {
+            // Average can't be more than the largest amount so it fits in u64
+            Some(FixedPrice::new(
+                (weighted_sum / total_blocks as u128) as u64,
+                decimals,
+            ))
+        }
+    }
+
+    /// Median of the prices of the pair in effect during the window ending at the current block. For an even
+    /// number of prices, the mean of the middle two is taken. The median has the decimals of the latest price.
+    /// Returns `None` if the pair has no price history or a price can't be expressed with those decimals.
+    pub fn median_price(pair: &CurrencySymbolPair) -> Option<FixedPrice> {
+        let (prices, _, _) = Self::prices_in_window(pair);
+        let (_, latest) = prices.last()?;
+        let decimals = latest.decimals;
+        let mut amounts = prices
+            .iter()
+            .map(|(_, p)| p.amount_with_decimals(decimals))
+            .collect::<Option<Vec<u64>>>()?;
+        amounts.sort_unstable();
+        Some(FixedPrice::new(Self::median_of_sorted(&amounts), decimals))
+    }
+
+    /// Return if price of the pair is stale and need to be updated and the weight consumed while finding that out
+    pub fn is_price_stale(
+        pair: &CurrencySymbolPair,
+        current_block_no: T::BlockNumber,
+    ) -> (bool, Weight) {
         let freq = Self::price_update_freq().unwrap_or(0);
         if freq > 0 {
-            let last_update = Self::last_price_update_at().unwrap_or(T::BlockNumber::zero());
+            let last_update = Self::last_price_update_at(pair).unwrap_or(T::BlockNumber::zero());
             (
                 current_block_no >= (last_update + T::BlockNumber::from(freq)),
                 T::DbWeight::get().reads(2),
@@ -174,20 +578,33 @@ impl<T: Config> Module<T> {
         }
     }
 
-    /// Gets price of pair DOCK/USD by reading from the contract. Returns the weight consumed in
+    /// Gets price of the pair by reading from its contract. Returns the weight consumed in
     /// this function as well
-    pub fn get_price_from_contract() -> Result<(u32, Weight), dispatch::DispatchError> {
+    pub fn get_price_from_contract(
+        pair: &CurrencySymbolPair,
+    ) -> Result<(FixedPrice, Weight), dispatch::DispatchError> {
         let contract_config =
-            Self::contract_config().ok_or_else(|| Error::<T>::ContractCallFailed)?;
-        let (evm_resp, used_gas) = Self::get_evm_call_response(
+            Self::contract_config(pair).ok_or_else(|| Error::<T>::ContractConfigNotFound)?;
+        let (evm_resp, mut used_gas) = Self::get_evm_call_response(
             contract_config.address,
             contract_config.query_abi_encoded,
         )?;
         // Ignoring the weight of this operation as its in-memory and cheap
-        let price = Self::decode_evm_response_to_price(&contract_config.return_val_abi, &evm_resp)?;
+        let decoded = Self::decode_evm_response(&contract_config.return_val_abi, &evm_resp)?;
+        Self::ensure_round_is_fresh(&decoded)?;
+        let amount = Self::price_from_decoded_response(&decoded)?;
+        let decimals = match contract_config.decimals {
+            PriceDecimals::Fixed(decimals) => decimals,
+            PriceDecimals::FromContract(decimals_abi_encoded) => {
+                let (resp, gas) =
+                    Self::get_evm_call_response(contract_config.address, decimals_abi_encoded)?;
+                used_gas = used_gas.saturating_add(gas);
+                Self::decode_evm_response_to_decimals(&resp)?
+            }
+        };
         Ok((
-            price,
-            T::DbWeight::get().reads(1)
+            FixedPrice::new(amount, decimals),
+            T::DbWeight::get().reads(3)
                 + <T as pallet_evm::Config>::GasWeightMapping::gas_to_weight(
                     used_gas.unique_saturated_into(),
                 ),
@@ -220,23 +637,90 @@ impl<T: Config> Module<T> {
         }
     }
 
-    /// Decode return value of contract call to price.
+    /// Decode return value of contract call to the amount of the price, i.e. without decimals.
     pub fn decode_evm_response_to_price(
         return_val_abi: &[ParamType],
         return_val: &[u8],
-    ) -> Result<u32, dispatch::DispatchError> {
+    ) -> Result<u64, dispatch::DispatchError> {
+        let decoded = Self::decode_evm_response(return_val_abi, return_val)?;
+        Self::price_from_decoded_response(&decoded)
+    }
+
+    /// Decode return value of contract call to tokens. The response must have at least 2 tokens as
+    /// the 2nd one is the price.
+    fn decode_evm_response(
+        return_val_abi: &[ParamType],
+        return_val: &[u8],
+    ) -> Result<Vec<Token>, dispatch::DispatchError> {
         let decoded = eth_decode(return_val_abi, return_val)
             .map_err(|_| Error::<T>::ResponseParsingFailed)?;
         if decoded.len() < 2 {
             fail!(Error::<T>::ResponseParsingFailed)
         }
+        Ok(decoded)
+    }
+
+    /// Check that the round returned by `latestRoundData` was answered in itself and, if `MaxPriceAge`
+    /// is set, was updated recently enough as per `pallet_timestamp`. The response is expected to be
+    /// `(roundId, answer, startedAt, updatedAt, answeredInRound)`; no check is done for responses with
+    /// fewer tokens.
+    pub fn ensure_round_is_fresh(decoded: &[Token]) -> dispatch::DispatchResult {
+        if decoded.len() < 5 {
+            return Ok(());
+        }
+        let uint_at = |i: usize| {
+            decoded[i]
+                .clone()
+                .into_uint()
+                .ok_or_else(|| Error::<T>::ResponseParsingFailed)
+        };
+        let round_id = uint_at(0)?;
+        let updated_at = uint_at(3)?;
+        let answered_in_round = uint_at(4)?;
+        if answered_in_round < round_id || updated_at.is_zero() {
+            fail!(Error::<T>::RoundIncomplete)
+        }
+
+        let max_age = Self::max_price_age().unwrap_or(0);
+        if max_age > 0 {
+            // Timestamp is in milliseconds and `updatedAt` in seconds
+            let now_ms: u64 = <timestamp::Module<T>>::get().unique_saturated_into();
+            let now = U256::from(now_ms / 1000);
+            if updated_at.saturating_add(U256::from(max_age)) < now {
+                fail!(Error::<T>::PriceTooOld)
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode return value of contract's `decimals` call.
+    pub fn decode_evm_response_to_decimals(
+        return_val: &[u8],
+    ) -> Result<u8, dispatch::DispatchError> {
+        let decoded = eth_decode(&[ParamType::Uint(8)], return_val)
+            .map_err(|_| Error::<T>::ResponseParsingFailed)?;
+        let decimals = decoded
+            .into_iter()
+            .next()
+            .and_then(|t| t.into_uint())
+            .ok_or_else(|| Error::<T>::ResponseParsingFailed)?;
+        if decimals > U256::from(u8::MAX) {
+            fail!(Error::<T>::InvalidDecimals)
+        }
+        Ok(decimals.low_u32() as u8)
+    }
 
-        // Using `low_u32` as its assumed that price always fit in 4 bytes
+    /// Get the amount of the price from the decoded return value of contract call.
+    fn price_from_decoded_response(decoded: &[Token]) -> Result<u64, dispatch::DispatchError> {
         let price = decoded[1]
             .clone()
             .into_int()
-            .ok_or_else(|| Error::<T>::ResponseDoesNotHaveIntegerPrice)?
-            .low_u32();
+            .ok_or_else(|| Error::<T>::ResponseDoesNotHaveIntegerPrice)?;
+        // A negative price is in two's complement so has the most significant bit set and is caught here as well
+        if price > U256::from(u64::MAX) {
+            fail!(Error::<T>::PriceOverflow)
+        }
+        let price = price.low_u64();
         if price == 0 {
             fail!(Error::<T>::PriceIsZero)
         }
@@ -244,14 +728,52 @@ impl<T: Config> Module<T> {
     }
 }
 
+impl<T: Config> Module<T> {
+    /// Move the contract config, price and last update block of the only pair kept before prices
+    /// were kept per pair to the DOCK/USD pair. The contract config becomes the price source of the
+    /// pair and the price, which was multiplied by 1000, a fixed point price.
+    fn migrate_to_pairs() -> Weight {
+        let module = b"PriceFeedModule";
+        let pair = CurrencySymbolPair::dock_usd();
+        if let Some(config) =
+            take_storage_value::<ContractConfigV1>(module, b"ContractConfigStore", &[])
+        {
+            PriceSources::<T>::insert(&pair, PriceSource::Contract(config.into()));
+        }
+        if let Some(price) = take_storage_value::<u32>(module, b"Price", &[]) {
+            Price::insert(&pair, FixedPrice::new(price as u64, LEGACY_PRICE_DECIMALS));
+        }
+        if let Some(block) = take_storage_value::<T::BlockNumber>(module, b"LastPriceUpdateAt", &[])
+        {
+            LastPriceUpdateAt::<T>::insert(&pair, block);
+        }
+        T::DbWeight::get().reads_writes(3, 6)
+    }
+}
+
 impl<T: Config> PriceProvider for Module<T> {
-    /// Gets the price of Dock/USD from pallet's storage
-    fn get_dock_usd_price() -> Option<(u32, u64)> {
-        Self::get_price_from_contract().map_or(None, |v| Some(v))
+    /// Gets the price of the pair from EVM contract or, if the price is submitted by oracles, from pallet's storage
+    fn get_price(pair: &CurrencySymbolPair) -> Option<(FixedPrice, u64)> {
+        match Self::price_source(pair) {
+            Some(PriceSource::Oracles(_)) => {
+                Self::price(pair).map(|p| (p, T::DbWeight::get().reads(2)))
+            }
+            _ => Self::get_price_from_contract(pair).map_or(None, |v| Some(v)),
+        }
+    }
+
+    /// Gets the price of the pair from pallet's storage
+    fn optimized_get_price(pair: &CurrencySymbolPair) -> Option<(FixedPrice, u64)> {
+        Self::price(pair).map(|p| (p, T::DbWeight::get().reads(1)))
+    }
+
+    /// Gets the time weighted average price of the pair from pallet's storage
+    fn get_twap_price(pair: &CurrencySymbolPair) -> Option<(FixedPrice, u64)> {
+        Self::twap_price(pair).map(|p| (p, T::DbWeight::get().reads(2)))
     }
 
-    /// Gets the price of Dock/USD from EVM contract
-    fn optimized_get_dock_usd_price() -> Option<(u32, u64)> {
-        Self::price().map(|p| (p, T::DbWeight::get().reads(1)))
+    /// Gets the median price of the pair from pallet's storage
+    fn get_median_price(pair: &CurrencySymbolPair) -> Option<(FixedPrice, u64)> {
+        Self::median_price(pair).map(|p| (p, T::DbWeight::get().reads(2)))
     }
 }
@@ -32,6 +32,7 @@ parameter_types! {
     pub const SS58Prefix: u8 = 21;
     pub const DockChainId: u64 = 2021;
     pub const MinimumPeriod: u64 = 1000;
+    pub const MaxPriceHistory: u32 = 5;
 }
 
 impl system::Config for Test {
@@ -48,7 +49,7 @@ impl system::Config for Test {
     type AccountId = u64;
     type Lookup = IdentityLookup<Self::AccountId>;
     type Header = Header;
-    type Event = ();
+    type Event = Event;
     type BlockHashCount = BlockHashCount;
     type Version = ();
     type PalletInfo = ();
@@ -147,7 +148,8 @@ impl poa::Trait for TestRuntime {
 }*/
 
 impl price_feed::Config for Test {
-    type Event = ();
+    type Event = Event;
+    type MaxPriceHistory = MaxPriceHistory;
 }
 
 // Build genesis storage according to the mock runtime.
@@ -1,11 +1,26 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use crate::{CurrencySymbolPair, FixedPrice};
+
 sp_api::decl_runtime_apis! {
+    #[api_version(2)]
     pub trait PriceFeedApi {
-        /// Gets the price of Dock/USD from pallet's storage
+        /// Gets the price of Dock/USD from pallet's storage, multiplied by 1000. `None` if it doesn't fit in `u32`
         fn token_usd_price() -> Option<u32>;
 
-        /// Gets the price of Dock/USD from EVM contract
+        /// Gets the price of Dock/USD from EVM contract, multiplied by 1000. `None` if it doesn't fit in `u32`
         fn token_usd_price_from_contract() -> Option<u32>;
+
+        /// Gets the price of the given pair from pallet's storage
+        fn price(pair: CurrencySymbolPair) -> Option<FixedPrice>;
+
+        /// Gets the price of the given pair from its EVM contract
+        fn price_from_contract(pair: CurrencySymbolPair) -> Option<FixedPrice>;
+
+        /// Gets the time weighted average price of the given pair over the configured window
+        fn twap_price(pair: CurrencySymbolPair) -> Option<FixedPrice>;
+
+        /// Gets the median price of the given pair over the configured window
+        fn median_price(pair: CurrencySymbolPair) -> Option<FixedPrice>;
     }
 }
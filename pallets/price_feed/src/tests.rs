@@ -1,11 +1,28 @@
-use crate::{mock::*, ContractConfig, Error, ParamType, DUMMY_SOURCE, GAS_LIMIT, ZERO_VALUE};
+use crate::{
+    mock::*, ContractConfig, CurrencySymbolPair, Error, FixedPrice, OracleConfig, ParamType,
+    PriceDecimals, PriceSource, DUMMY_SOURCE, GAS_LIMIT, ZERO_VALUE,
+};
 use common::PriceProvider;
-use frame_support::{assert_err, StorageValue};
+use frame_support::{
+    assert_err,
+    traits::{OnInitialize, OnRuntimeUpgrade},
+    StorageMap, StorageValue,
+};
 use pallet_evm::Runner;
 use sp_core::{H160, U256};
 
-/// Deploy contract and set contract config in this pallet
-fn setup_contract() -> H160 {
+/// Price with the 3 decimals of the test contract
+fn fixed(amount: u64) -> FixedPrice {
+    FixedPrice::new(amount, 3)
+}
+
+/// Deploy contract and set contract config for the pair in this pallet
+fn setup_contract(pair: &CurrencySymbolPair) -> H160 {
+    setup_contract_with_decimals(pair, PriceDecimals::Fixed(3))
+}
+
+/// Deploy contract and set contract config with the given decimals for the pair in this pallet
+fn setup_contract_with_decimals(pair: &CurrencySymbolPair, decimals: PriceDecimals) -> H160 {
     let evm_config = <Test as pallet_evm::Config>::config();
 
     // constructor call with arguments `10, 15, 1200, 1200, 10`
@@ -34,9 +51,11 @@ fn setup_contract() -> H160 {
         address: contract_address,
         query_abi_encoded: query_abi,
         return_val_abi,
+        decimals,
     };
 
-    PriceFeedModule::set_contract_config(Origin::root(), contract_config.clone()).unwrap();
+    PriceFeedModule::set_contract_config(Origin::root(), pair.clone(), contract_config.clone())
+        .unwrap();
 
     contract_address
 }
@@ -44,17 +63,33 @@ fn setup_contract() -> H160 {
 #[test]
 fn set_contract_config() {
     new_test_ext().execute_with(|| {
+        let pair = CurrencySymbolPair::dock_usd();
         let config = ContractConfig::default();
 
-        assert!(PriceFeedModule::contract_config().is_none());
+        assert!(PriceFeedModule::contract_config(&pair).is_none());
 
         // Non root cannot set config
-        assert!(PriceFeedModule::set_contract_config(Origin::signed(10), config.clone()).is_err());
-        assert!(PriceFeedModule::set_contract_config(Origin::signed(1), config.clone()).is_err());
-        assert!(PriceFeedModule::set_contract_config(Origin::signed(500), config.clone()).is_err());
-
-        PriceFeedModule::set_contract_config(Origin::root(), config.clone()).unwrap();
-        assert!(PriceFeedModule::contract_config().is_some());
+        assert!(PriceFeedModule::set_contract_config(
+            Origin::signed(10),
+            pair.clone(),
+            config.clone()
+        )
+        .is_err());
+        assert!(PriceFeedModule::set_contract_config(
+            Origin::signed(1),
+            pair.clone(),
+            config.clone()
+        )
+        .is_err());
+        assert!(PriceFeedModule::set_contract_config(
+            Origin::signed(500),
+            pair.clone(),
+            config.clone()
+        )
+        .is_err());
+
+        PriceFeedModule::set_contract_config(Origin::root(), pair.clone(), config.clone()).unwrap();
+        assert!(PriceFeedModule::contract_config(&pair).is_some());
     })
 }
 
@@ -78,21 +113,22 @@ fn set_update_frequency() {
 #[test]
 fn price_stale() {
     new_test_ext().execute_with(|| {
+        let pair = CurrencySymbolPair::dock_usd();
         assert!(PriceFeedModule::price_update_freq().is_none());
-        assert!(PriceFeedModule::last_price_update_at().is_none());
+        assert!(PriceFeedModule::last_price_update_at(&pair).is_none());
 
         // Price stale check should return false until both update frequency and last updated at are set
-        assert!(!PriceFeedModule::is_price_stale(1).0);
+        assert!(!PriceFeedModule::is_price_stale(&pair, 1).0);
         PriceFeedModule::set_update_frequency(Origin::root(), 5).unwrap();
-        assert!(!PriceFeedModule::is_price_stale(1).0);
+        assert!(!PriceFeedModule::is_price_stale(&pair, 1).0);
 
-        crate::LastPriceUpdateAt::<Test>::put(10);
-        assert!(!PriceFeedModule::is_price_stale(10).0);
-        assert!(!PriceFeedModule::is_price_stale(11).0);
-        assert!(!PriceFeedModule::is_price_stale(12).0);
-        assert!(!PriceFeedModule::is_price_stale(14).0);
+        crate::LastPriceUpdateAt::<Test>::insert(&pair, 10);
+        assert!(!PriceFeedModule::is_price_stale(&pair, 10).0);
+        assert!(!PriceFeedModule::is_price_stale(&pair, 11).0);
+        assert!(!PriceFeedModule::is_price_stale(&pair, 12).0);
+        assert!(!PriceFeedModule::is_price_stale(&pair, 14).0);
 
-        assert!(PriceFeedModule::is_price_stale(15).0);
+        assert!(PriceFeedModule::is_price_stale(&pair, 15).0);
 
         // Note: More checks on weight could be done here
     })
@@ -101,18 +137,19 @@ fn price_stale() {
 #[test]
 fn get_price_from_contract() {
     new_test_ext().execute_with(|| {
+        let pair = CurrencySymbolPair::dock_usd();
         // Bytecodes used in this test are taken from Readme which contains the contract as well
 
         // Contract isn't deployed on EVM yet
-        assert!(PriceFeedModule::get_price_from_contract().is_err());
-        assert!(PriceFeedModule::price().is_none());
+        assert!(PriceFeedModule::get_price_from_contract(&pair).is_err());
+        assert!(PriceFeedModule::price(&pair).is_none());
 
-        let contract_address = setup_contract();
+        let contract_address = setup_contract(&pair);
 
-        let (price, _) = PriceFeedModule::get_price_from_contract().unwrap();
-        assert_eq!(price, 15);
+        let (price, _) = PriceFeedModule::get_price_from_contract(&pair).unwrap();
+        assert_eq!(price, fixed(15));
         // Price is not updated in this pallet's storage
-        assert!(PriceFeedModule::price().is_none());
+        assert!(PriceFeedModule::price(&pair).is_none());
 
         let evm_config = <Test as pallet_evm::Config>::config();
 
@@ -129,9 +166,12 @@ fn get_price_from_contract() {
             evm_config,
         ).unwrap();
 
-        let (price, _) = PriceFeedModule::get_price_from_contract().unwrap();
-        assert_eq!(price, 25);
-        assert!(PriceFeedModule::price().is_none());
+        // Round 11 was answered in round 10
+        assert_err!(
+            PriceFeedModule::get_price_from_contract(&pair),
+            Error::<Test>::RoundIncomplete
+        );
+        assert!(PriceFeedModule::price(&pair).is_none());
 
         // call `setData(12, 30, 1400, 1400, 11)`
         let set_call_2 = hex::decode("6444bd16000000000000000000000000000000000000000000000000000000000000000c000000000000000000000000000000000000000000000000000000000000001e00000000000000000000000000000000000000000000000000000000000005780000000000000000000000000000000000000000000000000000000000000578000000000000000000000000000000000000000000000000000000000000000b").unwrap();
@@ -146,9 +186,12 @@ fn get_price_from_contract() {
             evm_config,
         ).unwrap();
 
-        let (price, _) = PriceFeedModule::get_price_from_contract().unwrap();
-        assert_eq!(price, 30);
-        assert!(PriceFeedModule::price().is_none());
+        // Round 12 was answered in round 11
+        assert_err!(
+            PriceFeedModule::get_price_from_contract(&pair),
+            Error::<Test>::RoundIncomplete
+        );
+        assert!(PriceFeedModule::price(&pair).is_none());
 
         // call `setData(13, 40, 1410, 1400, 13)`
         let set_call_3 = hex::decode("6444bd16000000000000000000000000000000000000000000000000000000000000000d000000000000000000000000000000000000000000000000000000000000002800000000000000000000000000000000000000000000000000000000000005820000000000000000000000000000000000000000000000000000000000000578000000000000000000000000000000000000000000000000000000000000000d").unwrap();
@@ -163,33 +206,34 @@ fn get_price_from_contract() {
             evm_config,
         ).unwrap();
 
-        let (price, _) = PriceFeedModule::get_price_from_contract().unwrap();
-        assert_eq!(price, 40);
+        let (price, _) = PriceFeedModule::get_price_from_contract(&pair).unwrap();
+        assert_eq!(price, fixed(40));
 
         // Fetching from contract does not update the price storage of this pallet
-        assert!(PriceFeedModule::price().is_none());
+        assert!(PriceFeedModule::price(&pair).is_none());
     })
 }
 
 #[test]
 fn storage_price_update() {
     new_test_ext().execute_with(|| {
+        let pair = CurrencySymbolPair::dock_usd();
         // Contract isn't deployed on EVM yet
-        assert!(PriceFeedModule::update_price_from_contract().is_err());
-        assert!(PriceFeedModule::price().is_none());
+        assert!(PriceFeedModule::update_price_from_contract(&pair).is_err());
+        assert!(PriceFeedModule::price(&pair).is_none());
 
-        let contract_address = setup_contract();
+        let contract_address = setup_contract(&pair);
 
         PriceFeedModule::set_update_frequency(Origin::root(), 10).unwrap();
         System::set_block_number(10);
 
         // Update pallet's stored price from contract. This is the first run of the update price and `LastPriceUpdateAt` isn't set
-        assert!(PriceFeedModule::last_price_update_at().is_none());
-        PriceFeedModule::update_price_if_stale(10).unwrap();
+        assert!(PriceFeedModule::last_price_update_at(&pair).is_none());
+        PriceFeedModule::update_price_if_stale(&pair, 10).unwrap();
 
         // Pallet's storage is updated
-        assert_eq!(PriceFeedModule::price().unwrap(), 15);
-        assert_eq!(PriceFeedModule::last_price_update_at().unwrap(), 10);
+        assert_eq!(PriceFeedModule::price(&pair).unwrap(), fixed(15));
+        assert_eq!(PriceFeedModule::last_price_update_at(&pair).unwrap(), 10);
 
         System::set_block_number(14);
 
@@ -209,20 +253,260 @@ fn storage_price_update() {
         ).unwrap();
 
         // Price stored in this pallet unchanged when contract updated
-        assert_eq!(PriceFeedModule::price().unwrap(), 15);
+        assert_eq!(PriceFeedModule::price(&pair).unwrap(), fixed(15));
 
         // Price unchanged when it isn't stale
-        PriceFeedModule::update_price_if_stale(14).unwrap();
-        assert_eq!(PriceFeedModule::price().unwrap(), 15);
+        PriceFeedModule::update_price_if_stale(&pair, 14).unwrap();
+        assert_eq!(PriceFeedModule::price(&pair).unwrap(), fixed(15));
 
         System::set_block_number(21);
 
         // Price changed when its stale
-        PriceFeedModule::update_price_if_stale(21).unwrap();
-        assert_eq!(PriceFeedModule::price().unwrap(), 40);
+        PriceFeedModule::update_price_if_stale(&pair, 21).unwrap();
+        assert_eq!(PriceFeedModule::price(&pair).unwrap(), fixed(40));
+    })
+}
+
+#[test]
+fn stale_round() {
+    new_test_ext().execute_with(|| {
+        let pair = CurrencySymbolPair::dock_usd();
+        // Round was updated at 1200 seconds
+        let contract_address = setup_contract(&pair);
+
+        PriceFeedModule::set_update_frequency(Origin::root(), 10).unwrap();
+        System::set_block_number(10);
+        PriceFeedModule::update_price_if_stale(&pair, 10).unwrap();
+        assert_eq!(PriceFeedModule::price(&pair).unwrap(), fixed(15));
+
+        // Non root cannot set max age
+        assert!(PriceFeedModule::set_max_price_age(Origin::signed(1), 500).is_err());
+
+        // Round is older than max age at 2000 seconds
+        timestamp::Module::<Test>::set_timestamp(2_000_000);
+        PriceFeedModule::set_max_price_age(Origin::root(), 500).unwrap();
+        assert_eq!(PriceFeedModule::max_price_age(), Some(500));
+        assert_err!(
+            PriceFeedModule::get_price_from_contract(&pair),
+            Error::<Test>::PriceTooOld
+        );
+        assert!(PriceFeedModule::get_price(&pair).is_none());
+
+        // Round is not older than max age
+        PriceFeedModule::set_max_price_age(Origin::root(), 800).unwrap();
+        assert_eq!(PriceFeedModule::get_price_from_contract(&pair).unwrap().0, fixed(15));
+
+        // Disabling the check
+        PriceFeedModule::set_max_price_age(Origin::root(), 500).unwrap();
+        assert!(PriceFeedModule::get_price_from_contract(&pair).is_err());
+        PriceFeedModule::set_max_price_age(Origin::root(), 0).unwrap();
+        assert_eq!(PriceFeedModule::get_price_from_contract(&pair).unwrap().0, fixed(15));
+
+        let evm_config = <Test as pallet_evm::Config>::config();
+        // call `setData(11, 25, 1300, 1300, 10)`, i.e. round 11 answered in round 10
+        let set_call = hex::decode("6444bd16000000000000000000000000000000000000000000000000000000000000000b000000000000000000000000000000000000000000000000000000000000001900000000000000000000000000000000000000000000000000000000000005140000000000000000000000000000000000000000000000000000000000000514000000000000000000000000000000000000000000000000000000000000000a").unwrap();
+        <Test as pallet_evm::Config>::Runner::call(
+            DUMMY_SOURCE,
+            contract_address,
+            set_call,
+            ZERO_VALUE,
+            GAS_LIMIT,
+            None,
+            None,
+            evm_config,
+        ).unwrap();
+
+        // Previous price is kept when the round is stale but the update is not tried again until stale
+        System::set_block_number(20);
+        PriceFeedModule::update_price_if_stale(&pair, 20).unwrap();
+        assert_eq!(PriceFeedModule::price(&pair).unwrap(), fixed(15));
+        assert_eq!(PriceFeedModule::last_price_update_at(&pair).unwrap(), 20);
+        assert!(!PriceFeedModule::is_price_stale(&pair, 25).0);
+        assert!(System::events().iter().any(|record| record.event
+            == Event::price_feed(crate::Event::PriceStale(pair.clone()))));
+
+        // Stale round on the aggregator does not change the stored price for consumers
+        assert_eq!(PriceFeedModule::optimized_get_dock_usd_price().unwrap().0, fixed(15));
+        assert!(PriceFeedModule::get_dock_usd_price().is_none());
+
+        // The update on a fresh round succeeds
+        PriceFeedModule::set_max_price_age(Origin::root(), 1000).unwrap();
+        // call `setData(13, 40, 1410, 1400, 13)`
+        let set_call = hex::decode("6444bd16000000000000000000000000000000000000000000000000000000000000000d000000000000000000000000000000000000000000000000000000000000002800000000000000000000000000000000000000000000000000000000000005820000000000000000000000000000000000000000000000000000000000000578000000000000000000000000000000000000000000000000000000000000000d").unwrap();
+        <Test as pallet_evm::Config>::Runner::call(
+            DUMMY_SOURCE,
+            contract_address,
+            set_call,
+            ZERO_VALUE,
+            GAS_LIMIT,
+            None,
+            None,
+            evm_config,
+        ).unwrap();
+        System::set_block_number(30);
+        PriceFeedModule::update_price_if_stale(&pair, 30).unwrap();
+        assert_eq!(PriceFeedModule::price(&pair).unwrap(), fixed(40));
     })
 }
 
+#[test]
+fn price_history_twap_and_median() {
+    new_test_ext().execute_with(|| {
+        let pair = CurrencySymbolPair::dock_usd();
+        assert!(PriceFeedModule::twap_price(&pair).is_none());
+        assert!(PriceFeedModule::median_price(&pair).is_none());
+        assert!(PriceFeedModule::get_twap_price(&pair).is_none());
+
+        System::set_block_number(10);
+        PriceFeedModule::set_price(&pair, fixed(100));
+        // Only price was set in the current block
+        assert_eq!(PriceFeedModule::twap_price(&pair), Some(fixed(100)));
+        assert_eq!(PriceFeedModule::median_price(&pair), Some(fixed(100)));
+
+        System::set_block_number(20);
+        PriceFeedModule::set_price(&pair, fixed(200));
+        // Price set in the current block was not in effect for any block yet
+        assert_eq!(PriceFeedModule::twap_price(&pair), Some(fixed(100)));
+        assert_eq!(PriceFeedModule::median_price(&pair), Some(fixed(150)));
+
+        System::set_block_number(30);
+        assert_eq!(PriceFeedModule::twap_price(&pair), Some(fixed(150)));
+        PriceFeedModule::set_price(&pair, fixed(600));
+
+        System::set_block_number(40);
+        // (100 * 10 + 200 * 10 + 600 * 10) / 30
+        assert_eq!(PriceFeedModule::twap_price(&pair), Some(fixed(300)));
+        assert_eq!(PriceFeedModule::median_price(&pair), Some(fixed(200)));
+        assert_eq!(
+            PriceFeedModule::get_twap_price(&pair).unwrap().0,
+            fixed(300)
+        );
+        assert_eq!(
+            PriceFeedModule::get_median_price(&pair).unwrap().0,
+            fixed(200)
+        );
+        // Latest price is unaffected
+        assert_eq!(
+            PriceFeedModule::optimized_get_price(&pair).unwrap().0,
+            fixed(600)
+        );
+
+        // Non root cannot set window
+        assert!(PriceFeedModule::set_price_window(Origin::signed(1), 15).is_err());
+
+        // Window starts at block 25 when price 200 was in effect, (200 * 5 + 600 * 10) / 15
+        PriceFeedModule::set_price_window(Origin::root(), 15).unwrap();
+        assert_eq!(PriceFeedModule::twap_price(&pair), Some(fixed(466)));
+        assert_eq!(PriceFeedModule::median_price(&pair), Some(fixed(400)));
+
+        // Window starts at the last price
+        PriceFeedModule::set_price_window(Origin::root(), 10).unwrap();
+        assert_eq!(PriceFeedModule::twap_price(&pair), Some(fixed(600)));
+        assert_eq!(PriceFeedModule::median_price(&pair), Some(fixed(600)));
+
+        // Only the last price of a block is kept
+        PriceFeedModule::set_price(&pair, fixed(700));
+        PriceFeedModule::set_price(&pair, fixed(800));
+        assert_eq!(
+            PriceFeedModule::price_history(&pair),
+            vec![
+                (10, fixed(100)),
+                (20, fixed(200)),
+                (30, fixed(600)),
+                (40, fixed(800))
+            ]
+        );
+
+        // History is bounded, the oldest prices are dropped
+        for block in 41..=43 {
+            System::set_block_number(block);
+            PriceFeedModule::set_price(&pair, fixed(block));
+        }
+        assert_eq!(
+            PriceFeedModule::price_history(&pair),
+            vec![
+                (30, fixed(600)),
+                (40, fixed(800)),
+                (41, fixed(41)),
+                (42, fixed(42)),
+                (43, fixed(43))
+            ]
+        );
+        assert_eq!(PriceFeedModule::price(&pair), Some(fixed(43)));
+
+        // Prices of other pairs are tracked separately
+        assert!(PriceFeedModule::twap_price(&CurrencySymbolPair::new("ETH", "USD")).is_none());
+    })
+}
+
+#[test]
+fn decimals_from_contract() {
+    new_test_ext().execute_with(|| {
+        let pair = CurrencySymbolPair::dock_usd();
+        // Using `roundId()`, which returns 10, of the test contract in place of `decimals()`
+        let contract_address =
+            setup_contract_with_decimals(&pair, PriceDecimals::FromContract(vec![140, 210, 33, 201]));
+        assert_eq!(
+            PriceFeedModule::get_price_from_contract(&pair).unwrap().0,
+            FixedPrice::new(15, 10)
+        );
+        PriceFeedModule::update_price_from_contract(&pair).unwrap();
+        assert_eq!(PriceFeedModule::price(&pair), Some(FixedPrice::new(15, 10)));
+
+        let evm_config = <Test as pallet_evm::Config>::config();
+        // call `setData(13, 40, 1410, 1400, 13)`
+        let set_call = hex::decode("6444bd16000000000000000000000000000000000000000000000000000000000000000d000000000000000000000000000000000000000000000000000000000000002800000000000000000000000000000000000000000000000000000000000005820000000000000000000000000000000000000000000000000000000000000578000000000000000000000000000000000000000000000000000000000000000d").unwrap();
+        <Test as pallet_evm::Config>::Runner::call(
+            DUMMY_SOURCE,
+            contract_address,
+            set_call,
+            ZERO_VALUE,
+            GAS_LIMIT,
+            None,
+            None,
+            evm_config,
+        ).unwrap();
+        System::set_block_number(5);
+        PriceFeedModule::update_price_from_contract(&pair).unwrap();
+        assert_eq!(PriceFeedModule::price(&pair), Some(FixedPrice::new(40, 13)));
+
+        // Average is in the decimals of the latest price, (15 * 10^3 * 5 + 40 * 5) / 10
+        System::set_block_number(10);
+        assert_eq!(
+            PriceFeedModule::twap_price(&pair),
+            Some(FixedPrice::new(7520, 13))
+        );
+
+        // Using `updatedAt()`, which returns 1400, in place of `decimals()`
+        setup_contract_with_decimals(&pair, PriceDecimals::FromContract(vec![117, 25, 171, 80]));
+        assert_err!(
+            PriceFeedModule::get_price_from_contract(&pair),
+            Error::<Test>::InvalidDecimals
+        );
+    })
+}
+
+#[test]
+fn fixed_price_decimals() {
+    let price = FixedPrice::new(52500, 6);
+    assert_eq!(price.amount_with_decimals(4), Some(525));
+    assert_eq!(price.amount_with_decimals(2), Some(5));
+    assert_eq!(price.amount_with_decimals(0), Some(0));
+    assert_eq!(price.amount_with_decimals(8), Some(5250000));
+    assert_eq!(price.with_decimals(3), Some(FixedPrice::new(52, 3)));
+    assert_eq!(price.amount_with_decimals(30), None);
+    assert_eq!(FixedPrice::new(u64::MAX, 0).amount_with_decimals(1), None);
+    assert_eq!(
+        FixedPrice::new(u64::MAX, 30).amount_with_decimals(0),
+        Some(0)
+    );
+    assert_eq!(crate::to_legacy_price(FixedPrice::new(525, 4)), Some(52));
+    assert_eq!(
+        crate::to_legacy_price(FixedPrice::new(u32::MAX as u64 + 1, 3)),
+        None
+    );
+}
+
 #[test]
 fn decoding_emv_resp() {
     new_test_ext().execute_with(|| {
@@ -266,25 +550,69 @@ fn decoding_emv_resp() {
             PriceFeedModule::decode_evm_response_to_price(&ret_params, &ret_value_2),
             Error::<Test>::ResponseParsingFailed
         );
+
+        // Response of `latestRoundData` with the given answer
+        let response = |answer: U256| {
+            let mut resp = vec![];
+            for word in [
+                U256::from(32),
+                answer,
+                U256::from(1200),
+                U256::from(1200),
+                U256::from(32),
+            ]
+            .iter()
+            {
+                let mut bytes = [0u8; 32];
+                word.to_big_endian(&mut bytes);
+                resp.extend_from_slice(&bytes);
+            }
+            resp
+        };
+        // Prices larger than `u32` are not truncated
+        assert_eq!(
+            PriceFeedModule::decode_evm_response_to_price(
+                &ret_params,
+                &response(U256::from(u64::MAX))
+            )
+            .unwrap(),
+            u64::MAX
+        );
+        assert_err!(
+            PriceFeedModule::decode_evm_response_to_price(
+                &ret_params,
+                &response(U256::from(u64::MAX) + 1)
+            ),
+            Error::<Test>::PriceOverflow
+        );
+        // -1 in two's complement
+        assert_err!(
+            PriceFeedModule::decode_evm_response_to_price(
+                &ret_params,
+                &response(U256::max_value())
+            ),
+            Error::<Test>::PriceOverflow
+        );
     });
 }
 
 #[test]
 fn price_provider_api() {
     new_test_ext().execute_with(|| {
+        let pair = CurrencySymbolPair::dock_usd();
         assert!(PriceFeedModule::optimized_get_dock_usd_price().is_none());
 
-        let contract_address = setup_contract();
+        let contract_address = setup_contract(&pair);
 
         assert!(PriceFeedModule::optimized_get_dock_usd_price().is_none());
 
-        PriceFeedModule::update_price_from_contract().unwrap();
+        PriceFeedModule::update_price_from_contract(&pair).unwrap();
 
         let (price_optmz, _) = PriceFeedModule::optimized_get_dock_usd_price().unwrap();
         let (price, _) = PriceFeedModule::get_dock_usd_price().unwrap();
-        assert_eq!(PriceFeedModule::price().unwrap(), 15);
-        assert_eq!(price_optmz, 15);
-        assert_eq!(price, 15);
+        assert_eq!(PriceFeedModule::price(&pair).unwrap(), fixed(15));
+        assert_eq!(price_optmz, fixed(15));
+        assert_eq!(price, fixed(15));
 
         // Update the price in contract
         let evm_config = <Test as pallet_evm::Config>::config();
@@ -303,12 +631,264 @@ fn price_provider_api() {
 
         let (price_optmz, _) = PriceFeedModule::optimized_get_dock_usd_price().unwrap();
         let (price, _) = PriceFeedModule::get_dock_usd_price().unwrap();
-        assert_eq!(PriceFeedModule::price().unwrap(), 15);
+        assert_eq!(PriceFeedModule::price(&pair).unwrap(), fixed(15));
         // Optimized get call still fetches the price from storage
-        assert_eq!(price_optmz, 15);
+        assert_eq!(price_optmz, fixed(15));
         // Non-optimized get fetches from contract
-        let (price_ctr, _) = PriceFeedModule::get_price_from_contract().unwrap();
-        assert_eq!(price_ctr, 40);
-        assert_eq!(price, 40);
+        let (price_ctr, _) = PriceFeedModule::get_price_from_contract(&pair).unwrap();
+        assert_eq!(price_ctr, fixed(40));
+        assert_eq!(price, fixed(40));
     })
 }
+
+#[test]
+fn multiple_pairs() {
+    new_test_ext().execute_with(|| {
+        let dock_usd = CurrencySymbolPair::dock_usd();
+        let eth_usd = CurrencySymbolPair::new("ETH", "USD");
+
+        let dock_usd_contract = setup_contract(&dock_usd);
+        setup_contract(&eth_usd);
+        assert!(PriceFeedModule::contract_config(&CurrencySymbolPair::new("DOCK", "EUR")).is_none());
+
+        PriceFeedModule::set_update_frequency(Origin::root(), 10).unwrap();
+        System::set_block_number(10);
+        <PriceFeedModule as OnInitialize<u64>>::on_initialize(10);
+        assert_eq!(PriceFeedModule::price(&dock_usd).unwrap(), fixed(15));
+        assert_eq!(PriceFeedModule::price(&eth_usd).unwrap(), fixed(15));
+
+        let evm_config = <Test as pallet_evm::Config>::config();
+        // call `setData(13, 40, 1410, 1400, 13)` only on the DOCK/USD contract
+        let set_call = hex::decode("6444bd16000000000000000000000000000000000000000000000000000000000000000d000000000000000000000000000000000000000000000000000000000000002800000000000000000000000000000000000000000000000000000000000005820000000000000000000000000000000000000000000000000000000000000578000000000000000000000000000000000000000000000000000000000000000d").unwrap();
+        <Test as pallet_evm::Config>::Runner::call(
+            DUMMY_SOURCE,
+            dock_usd_contract,
+            set_call,
+            ZERO_VALUE,
+            GAS_LIMIT,
+            None,
+            None,
+            evm_config,
+        ).unwrap();
+
+        System::set_block_number(20);
+        <PriceFeedModule as OnInitialize<u64>>::on_initialize(20);
+        assert_eq!(PriceFeedModule::price(&dock_usd).unwrap(), fixed(40));
+        assert_eq!(PriceFeedModule::price(&eth_usd).unwrap(), fixed(15));
+        assert_eq!(PriceFeedModule::optimized_get_price(&eth_usd).unwrap().0, fixed(15));
+        assert_eq!(PriceFeedModule::optimized_get_dock_usd_price().unwrap().0, fixed(40));
+
+        // Only root can stop tracking a pair
+        assert!(PriceFeedModule::remove_price_source(Origin::signed(1), eth_usd.clone()).is_err());
+        PriceFeedModule::remove_price_source(Origin::root(), eth_usd.clone()).unwrap();
+        assert!(PriceFeedModule::contract_config(&eth_usd).is_none());
+        assert!(PriceFeedModule::price(&eth_usd).is_none());
+        assert!(PriceFeedModule::last_price_update_at(&eth_usd).is_none());
+        assert!(PriceFeedModule::price_history(&eth_usd).is_empty());
+        assert_err!(
+            PriceFeedModule::remove_price_source(Origin::root(), eth_usd.clone()),
+            Error::<Test>::PriceSourceNotFound
+        );
+        assert_eq!(PriceFeedModule::price(&dock_usd).unwrap(), fixed(40));
+    })
+}
+
+/// Contract config, price and last update block of DOCK/USD are moved to maps keyed by pair, the
+/// contract config becoming a price source and the price, multiplied by 1000, a fixed point price.
+#[test]
+fn migrate_to_pairs() {
+    use frame_support::storage::migration::{get_storage_value, put_storage_value};
+
+    new_test_ext().execute_with(|| {
+        let pair = CurrencySymbolPair::dock_usd();
+        let address = H160::repeat_byte(7);
+        let query_abi_encoded = vec![254, 175, 150, 140];
+        let return_val_abi = vec![ParamType::Uint(80), ParamType::Int(256)];
+        // Contract config had no decimals
+        put_storage_value(
+            b"PriceFeedModule",
+            b"ContractConfigStore",
+            &[],
+            (address, query_abi_encoded.clone(), return_val_abi.clone()),
+        );
+        put_storage_value(b"PriceFeedModule", b"Price", &[], 25u32);
+        put_storage_value(b"PriceFeedModule", b"LastPriceUpdateAt", &[], 9u64);
+
+        crate::StorageVersion::kill();
+
+        PriceFeedModule::on_runtime_upgrade();
+
+        assert_eq!(crate::StorageVersion::get(), Some(crate::Releases::V1));
+        let config = ContractConfig {
+            address,
+            query_abi_encoded,
+            return_val_abi,
+            decimals: PriceDecimals::Fixed(3),
+        };
+        assert_eq!(
+            PriceFeedModule::price_source(&pair),
+            Some(PriceSource::Contract(config.clone()))
+        );
+        assert_eq!(PriceFeedModule::contract_config(&pair), Some(config));
+        assert_eq!(PriceFeedModule::price(&pair), Some(fixed(25)));
+        assert_eq!(PriceFeedModule::last_price_update_at(&pair), Some(9));
+        // Old values are gone
+        assert!(get_storage_value::<ContractConfig>(
+            b"PriceFeedModule",
+            b"ContractConfigStore",
+            &[]
+        )
+        .is_none());
+        assert!(get_storage_value::<u32>(b"PriceFeedModule", b"Price", &[]).is_none());
+
+        // Migration runs only once
+        put_storage_value(b"PriceFeedModule", b"Price", &[], 30u32);
+        PriceFeedModule::on_runtime_upgrade();
+        assert_eq!(PriceFeedModule::price(&pair), Some(fixed(25)));
+    })
+}
+
+#[test]
+fn oracle_submissions() {
+    new_test_ext().execute_with(|| {
+        let pair = CurrencySymbolPair::new("DOCK", "EUR");
+        let config = OracleConfig {
+            oracles: vec![1, 2, 3, 4, 5],
+            min_submissions: 3,
+            max_deviation: sp_runtime::Permill::from_percent(10),
+            decimals: 2,
+        };
+
+        // Only root can set oracles and the config must be valid
+        assert!(PriceFeedModule::set_oracle_config(
+            Origin::signed(1),
+            pair.clone(),
+            config.clone()
+        )
+        .is_err());
+        for (oracles, min_submissions) in vec![(vec![1, 2], 3), (vec![1, 2], 0), (vec![], 0)] {
+            assert_err!(
+                PriceFeedModule::set_oracle_config(
+                    Origin::root(),
+                    pair.clone(),
+                    OracleConfig {
+                        oracles,
+                        min_submissions,
+                        ..config.clone()
+                    }
+                ),
+                Error::<Test>::InvalidOracleConfig
+            );
+        }
+
+        assert_err!(
+            PriceFeedModule::submit_price(Origin::signed(1), pair.clone(), 100),
+            Error::<Test>::PriceSourceNotFound
+        );
+        PriceFeedModule::set_oracle_config(Origin::root(), pair.clone(), config.clone()).unwrap();
+        assert_eq!(
+            PriceFeedModule::price_source(&pair),
+            Some(PriceSource::Oracles(config))
+        );
+        assert!(PriceFeedModule::contract_config(&pair).is_none());
+
+        assert_err!(
+            PriceFeedModule::submit_price(Origin::signed(6), pair.clone(), 100),
+            Error::<Test>::NotAnOracle
+        );
+        assert_err!(
+            PriceFeedModule::submit_price(Origin::signed(1), pair.clone(), 0),
+            Error::<Test>::PriceIsZero
+        );
+
+        // Later submission from the same oracle replaces the earlier one
+        PriceFeedModule::submit_price(Origin::signed(1), pair.clone(), 90).unwrap();
+        PriceFeedModule::submit_price(Origin::signed(1), pair.clone(), 100).unwrap();
+        PriceFeedModule::submit_price(Origin::signed(2), pair.clone(), 102).unwrap();
+        assert_eq!(
+            PriceFeedModule::oracle_submissions(&pair),
+            vec![(1, 100), (2, 102)]
+        );
+
+        // Too few submissions to aggregate
+        System::set_block_number(1);
+        <PriceFeedModule as OnInitialize<u64>>::on_initialize(1);
+        assert!(PriceFeedModule::price(&pair).is_none());
+        assert_eq!(PriceFeedModule::oracle_submissions(&pair).len(), 2);
+
+        // The outlier 200 is rejected
+        PriceFeedModule::submit_price(Origin::signed(3), pair.clone(), 98).unwrap();
+        PriceFeedModule::submit_price(Origin::signed(4), pair.clone(), 200).unwrap();
+        System::set_block_number(2);
+        <PriceFeedModule as OnInitialize<u64>>::on_initialize(2);
+        assert_eq!(PriceFeedModule::price(&pair), Some(FixedPrice::new(100, 2)));
+        assert_eq!(
+            PriceFeedModule::get_price(&pair).unwrap().0,
+            FixedPrice::new(100, 2)
+        );
+        assert!(PriceFeedModule::oracle_submissions(&pair).is_empty());
+
+        // Too few submissions are left after rejecting outliers so the price is kept
+        PriceFeedModule::submit_price(Origin::signed(1), pair.clone(), 100).unwrap();
+        PriceFeedModule::submit_price(Origin::signed(2), pair.clone(), 300).unwrap();
+        PriceFeedModule::submit_price(Origin::signed(3), pair.clone(), 500).unwrap();
+        System::set_block_number(3);
+        <PriceFeedModule as OnInitialize<u64>>::on_initialize(3);
+        assert_eq!(PriceFeedModule::price(&pair), Some(FixedPrice::new(100, 2)));
+        assert!(PriceFeedModule::oracle_submissions(&pair).is_empty());
+
+        // Switching to a contract discards the submissions and stops accepting them
+        PriceFeedModule::submit_price(Origin::signed(1), pair.clone(), 100).unwrap();
+        setup_contract(&pair);
+        assert!(PriceFeedModule::oracle_submissions(&pair).is_empty());
+        assert_err!(
+            PriceFeedModule::submit_price(Origin::signed(1), pair.clone(), 100),
+            Error::<Test>::NotOracleSource
+        );
+    })
+}
+
+#[test]
+fn contract_config_from_signatures() {
+    let address = H160::repeat_byte(7);
+    let decimals = PriceDecimals::FromContract(
+        crate::util::function_selector("decimals()")
+            .unwrap()
+            .to_vec(),
+    );
+    assert_eq!(
+        decimals,
+        PriceDecimals::FromContract(vec![0x31, 0x3c, 0xe5, 0x67])
+    );
+    assert_eq!(
+        ContractConfig::from_signatures(
+            address,
+            "latestRoundData()",
+            "(uint80,int256,uint256,uint256,uint80)",
+            decimals.clone()
+        )
+        .unwrap(),
+        ContractConfig {
+            address,
+            query_abi_encoded: vec![254, 175, 150, 140],
+            return_val_abi: vec![
+                ParamType::Uint(80),
+                ParamType::Int(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(80),
+            ],
+            decimals: decimals.clone(),
+        }
+    );
+    assert!(ContractConfig::from_signatures(
+        address,
+        "latestRoundData",
+        "(uint80)",
+        decimals.clone()
+    )
+    .is_err());
+    assert!(
+        ContractConfig::from_signatures(address, "latestRoundData()", "uint80", decimals).is_err()
+    );
+}
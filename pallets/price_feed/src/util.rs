@@ -1,10 +1,11 @@
 // ethabi crate cannot be directly used as its not `no_std` compatible. Also tried ethers-core and web3 but same issue.
-// Adaptions of https://github.com/rust-ethereum/ethabi/blob/master/ethabi/src/decoder.rs.
-// Lot of the code is intentionally commented as the relevant contract data is only of types uint256, int256
+// Adaptions of https://github.com/rust-ethereum/ethabi/blob/master/ethabi/src/decoder.rs, encoder.rs, token/mod.rs
+// and param_type/reader.rs. Strings are kept as bytes (checked to be utf-8) to avoid `alloc::string::String`.
 
 use codec::{Decode, Encode};
 
-use sp_std::prelude::Vec;
+use ethereum_types::H256;
+use sp_std::{boxed::Box, prelude::Vec, str::FromStr, vec};
 
 /// Ethabi errors
 #[derive(Debug)]
@@ -18,14 +19,14 @@ pub enum Error {
 /// ABI word.
 pub type Word = [u8; 32];
 
-/*/// ABI address.
+/// ABI address.
 pub type Address = ethereum_types::Address;
 
 /// ABI fixed bytes.
 pub type FixedBytes = Vec<u8>;
 
 /// ABI bytes.
-pub type Bytes = Vec<u8>;*/
+pub type Bytes = Vec<u8>;
 
 /// ABI signed integer.
 pub type Int = ethereum_types::U256;
@@ -39,60 +40,213 @@ struct DecodeResult {
 }
 
 /// Function and event param types.
+// New variants must only be appended as this type is part of the stored `ContractConfig`
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ParamType {
-    /*/// Address.
-    Address,
-    /// Bytes.
-    Bytes,*/
     /// Signed integer. u16 is sufficient as largest EVM integer type is 256 bit
     Int(u16),
     /// Unsigned integer. u16 is sufficient as largest EVM integer type is 256 bit
     Uint(u16),
-    /*/// Boolean.
+    /// Address.
+    Address,
+    /// Bytes.
+    Bytes,
+    /// Boolean.
     Bool,
     /// String.
     String,
     /// Array of unknown size.
     Array(Box<ParamType>),
-    /// Vector of bytes with fixed size.
-    FixedBytes(usize),
+    /// Vector of bytes with fixed size. u8 is sufficient as largest is `bytes32`
+    FixedBytes(u8),
     /// Array with fixed size.
-    FixedArray(Box<ParamType>, usize),
+    FixedArray(Box<ParamType>, u32),
     /// Tuple containing different types
-    Tuple(Vec<ParamType>),*/
+    Tuple(Vec<ParamType>),
 }
 
 impl ParamType {
     /// returns whether a zero length byte slice (`0x`) is
     /// a valid encoded form of this param type
     pub fn is_empty_bytes_valid_encoding(&self) -> bool {
-        /*match self {
+        match self {
             ParamType::FixedBytes(len) => *len == 0,
             ParamType::FixedArray(_, len) => *len == 0,
+            ParamType::Tuple(params) => params.is_empty(),
             _ => false,
-        }*/
-        false
+        }
     }
 
     /// returns whether a ParamType is dynamic
     /// used to decide how the ParamType should be encoded
     pub fn is_dynamic(&self) -> bool {
-        /*match self {
-            ParamType::Bytes | ParamType::Array(_) => true,
+        match self {
+            ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
             ParamType::FixedArray(elem_type, _) => elem_type.is_dynamic(),
             ParamType::Tuple(params) => params.iter().any(|param| param.is_dynamic()),
             _ => false,
-        }*/
-        true
+        }
+    }
+
+    /// returns whether a ParamType fits in a single word, i.e. is not an array, tuple, bytes or string.
+    /// Only such indexed event params can be recovered from the topics, others are hashed.
+    pub fn is_value_type(&self) -> bool {
+        match self {
+            ParamType::Int(_)
+            | ParamType::Uint(_)
+            | ParamType::Address
+            | ParamType::Bool
+            | ParamType::FixedBytes(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Write the canonical name of the type as used in function and event signatures, like
+    /// `uint256`, `bytes32[]` or `(address,bool)`.
+    pub fn write_canonical(&self, out: &mut Vec<u8>) {
+        match self {
+            ParamType::Int(size) => {
+                out.extend_from_slice(b"int");
+                write_number(*size as u32, out);
+            }
+            ParamType::Uint(size) => {
+                out.extend_from_slice(b"uint");
+                write_number(*size as u32, out);
+            }
+            ParamType::Address => out.extend_from_slice(b"address"),
+            ParamType::Bytes => out.extend_from_slice(b"bytes"),
+            ParamType::Bool => out.extend_from_slice(b"bool"),
+            ParamType::String => out.extend_from_slice(b"string"),
+            ParamType::Array(elem_type) => {
+                elem_type.write_canonical(out);
+                out.extend_from_slice(b"[]");
+            }
+            ParamType::FixedBytes(len) => {
+                out.extend_from_slice(b"bytes");
+                write_number(*len as u32, out);
+            }
+            ParamType::FixedArray(elem_type, len) => {
+                elem_type.write_canonical(out);
+                out.push(b'[');
+                write_number(*len, out);
+                out.push(b']');
+            }
+            ParamType::Tuple(params) => write_canonical_list(params, out),
+        }
+    }
+}
+
+/// Parses a human readable type like `uint80`, `address[]` or `(int256,bool)`. `int` and `uint` are
+/// taken as `int256` and `uint256`.
+impl FromStr for ParamType {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        if name.ends_with(')') {
+            if !name.starts_with('(') {
+                return Err(Error::InvalidName);
+            }
+            return parse_type_list(&name[1..name.len() - 1]).map(ParamType::Tuple);
+        }
+        if name.ends_with(']') {
+            let pos = name.rfind('[').ok_or(Error::InvalidName)?;
+            let elem_type = Box::new(name[..pos].parse()?);
+            let len = &name[pos + 1..name.len() - 1];
+            return if len.is_empty() {
+                Ok(ParamType::Array(elem_type))
+            } else {
+                let len = len.parse().map_err(|_| Error::InvalidName)?;
+                Ok(ParamType::FixedArray(elem_type, len))
+            };
+        }
+        let param = match name {
+            "address" => ParamType::Address,
+            "bytes" => ParamType::Bytes,
+            "bool" => ParamType::Bool,
+            "string" => ParamType::String,
+            "int" => ParamType::Int(256),
+            "uint" => ParamType::Uint(256),
+            s if s.starts_with("int") => ParamType::Int(parse_int_size(&s[3..])?),
+            s if s.starts_with("uint") => ParamType::Uint(parse_int_size(&s[4..])?),
+            s if s.starts_with("bytes") => {
+                let len: u8 = s[5..].parse().map_err(|_| Error::InvalidName)?;
+                if len == 0 || len > 32 {
+                    return Err(Error::InvalidName);
+                }
+                ParamType::FixedBytes(len)
+            }
+            _ => return Err(Error::InvalidName),
+        };
+        Ok(param)
+    }
+}
+
+/// Integer sizes are multiples of 8 from 8 to 256
+fn parse_int_size(size: &str) -> Result<u16, Error> {
+    let size: u16 = size.parse().map_err(|_| Error::InvalidName)?;
+    if size == 0 || size > 256 || size % 8 != 0 {
+        return Err(Error::InvalidName);
+    }
+    Ok(size)
+}
+
+/// Parses comma separated types, like the content of a tuple, splitting only at the top level commas.
+fn parse_type_list(list: &str) -> Result<Vec<ParamType>, Error> {
+    let mut params = Vec::new();
+    if list.is_empty() {
+        return Ok(params);
     }
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1).ok_or(Error::InvalidName)?,
+            ',' if depth == 0 => {
+                params.push(list[start..i].parse()?);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    if depth != 0 {
+        return Err(Error::InvalidName);
+    }
+    params.push(list[start..].parse()?);
+    Ok(params)
+}
+
+fn write_number(n: u32, out: &mut Vec<u8>) {
+    let mut digits = [0u8; 10];
+    let mut n = n;
+    let mut i = digits.len();
+    loop {
+        i -= 1;
+        digits[i] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    out.extend_from_slice(&digits[i..]);
+}
+
+fn write_canonical_list(params: &[ParamType], out: &mut Vec<u8>) {
+    out.push(b'(');
+    for (i, param) in params.iter().enumerate() {
+        if i > 0 {
+            out.push(b',');
+        }
+        param.write_canonical(out);
+    }
+    out.push(b')');
 }
 
 /// Ethereum ABI params.
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
-    /*/// Address.
+    /// Address.
     ///
     /// solidity name: address
     /// Encoded to left padded [0u8; 32].
@@ -108,7 +262,7 @@ pub enum Token {
     /// Encoded in two parts.
     /// Init part: offset of 'closing part`.
     /// Closing part: encoded length followed by encoded right padded bytes.
-    Bytes(Bytes),*/
+    Bytes(Bytes),
     /// Signed integer.
     ///
     /// solidity name: int
@@ -117,16 +271,16 @@ pub enum Token {
     ///
     /// solidity name: uint
     Uint(Uint),
-    /*/// Boolean value.
+    /// Boolean value.
     ///
     /// solidity name: bool
     /// Encoded as left padded [0u8; 32], where last bit represents boolean value.
     Bool(bool),
-    /// String.
+    /// String, as utf-8 bytes.
     ///
     /// solidity name: string
     /// Encoded in the same way as bytes. Must be utf8 compliant.
-    String(String),
+    String(Vec<u8>),
     /// Array with known size.
     ///
     /// solidity name eg.: int[3], bool[3], address[][8]
@@ -139,7 +293,7 @@ pub enum Token {
     /// Tuple of params of variable types.
     ///
     /// solidity name: tuple
-    Tuple(Vec<Token>),*/
+    Tuple(Vec<Token>),
 }
 
 impl Token {
@@ -156,6 +310,67 @@ impl Token {
             _ => None,
         }
     }
+
+    pub fn into_address(self) -> Option<Address> {
+        match self {
+            Token::Address(address) => Some(address),
+            _ => None,
+        }
+    }
+
+    pub fn into_bool(self) -> Option<bool> {
+        match self {
+            Token::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Check whether the type of the token matches the given parameter type.
+    pub fn type_check(&self, param_type: &ParamType) -> bool {
+        match (self, param_type) {
+            (Token::Address(_), ParamType::Address)
+            | (Token::Bytes(_), ParamType::Bytes)
+            | (Token::Int(_), ParamType::Int(_))
+            | (Token::Uint(_), ParamType::Uint(_))
+            | (Token::Bool(_), ParamType::Bool)
+            | (Token::String(_), ParamType::String) => true,
+            (Token::FixedBytes(bytes), ParamType::FixedBytes(len)) => bytes.len() <= *len as usize,
+            (Token::Array(tokens), ParamType::Array(elem_type)) => {
+                tokens.iter().all(|t| t.type_check(elem_type))
+            }
+            (Token::FixedArray(tokens), ParamType::FixedArray(elem_type, len)) => {
+                tokens.len() == *len as usize && tokens.iter().all(|t| t.type_check(elem_type))
+            }
+            (Token::Tuple(tokens), ParamType::Tuple(param_types)) => {
+                tokens.len() == param_types.len()
+                    && tokens
+                        .iter()
+                        .zip(param_types.iter())
+                        .all(|(t, p)| t.type_check(p))
+            }
+            _ => false,
+        }
+    }
+
+    /// Check if all the types of the tokens match the given parameter types.
+    pub fn types_check(tokens: &[Token], param_types: &[ParamType]) -> bool {
+        tokens.len() == param_types.len()
+            && tokens
+                .iter()
+                .zip(param_types.iter())
+                .all(|(t, p)| t.type_check(p))
+    }
+
+    /// returns whether a Token is dynamic
+    pub fn is_dynamic(&self) -> bool {
+        match self {
+            Token::Bytes(_) | Token::String(_) | Token::Array(_) => true,
+            Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+                tokens.iter().any(|t| t.is_dynamic())
+            }
+            _ => false,
+        }
+    }
 }
 
 /// Converts a vector of bytes with len equal n * 32, to a vector of slices.
@@ -179,6 +394,61 @@ fn peek(slices: &[Word], position: usize) -> Result<&Word, Error> {
     slices.get(position).ok_or(Error::InvalidData)
 }
 
+fn as_u32(slice: &Word) -> Result<u32, Error> {
+    if !slice[..28].iter().all(|x| *x == 0) {
+        return Err(Error::InvalidData);
+    }
+
+    let result = ((slice[28] as u32) << 24)
+        + ((slice[29] as u32) << 16)
+        + ((slice[30] as u32) << 8)
+        + (slice[31] as u32);
+
+    Ok(result)
+}
+
+fn as_bool(slice: &Word) -> Result<bool, Error> {
+    if !slice[..31].iter().all(|x| *x == 0) {
+        return Err(Error::InvalidData);
+    }
+
+    Ok(slice[31] == 1)
+}
+
+struct BytesTaken {
+    bytes: Vec<u8>,
+    new_offset: usize,
+}
+
+fn take_bytes(slices: &[Word], position: usize, len: usize) -> Result<BytesTaken, Error> {
+    let slices_len = (len + 31) / 32;
+
+    let mut bytes_slices = Vec::with_capacity(slices_len);
+    for i in 0..slices_len {
+        let slice = peek(slices, position + i)?;
+        bytes_slices.push(slice);
+    }
+
+    let bytes = bytes_slices
+        .into_iter()
+        .flat_map(|slice| slice.to_vec())
+        .take(len)
+        .collect();
+
+    let taken = BytesTaken {
+        bytes,
+        new_offset: position + slices_len,
+    };
+
+    Ok(taken)
+}
+
+/// Tail of the data starting at the word pointed to by the offset at `offset`
+fn tail_at(slices: &[Word], offset: usize) -> Result<&[Word], Error> {
+    let tail_offset = as_u32(peek(slices, offset)?)? as usize / 32;
+    slices.get(tail_offset..).ok_or(Error::InvalidData)
+}
+
 /// Decodes ABI compliant vector of bytes into vector of tokens described by types param.
 pub fn decode(types: &[ParamType], data: &[u8]) -> Result<Vec<Token>, Error> {
     let is_empty_bytes_valid_encoding = types.iter().all(|t| t.is_empty_bytes_valid_encoding());
@@ -198,15 +468,18 @@ pub fn decode(types: &[ParamType], data: &[u8]) -> Result<Vec<Token>, Error> {
 
 fn decode_param(param: &ParamType, slices: &[Word], offset: usize) -> Result<DecodeResult, Error> {
     match *param {
-        /*ParamType::Address => {
+        ParamType::Address => {
             let slice = peek(slices, offset)?;
             let mut address = [0u8; 20];
             address.copy_from_slice(&slice[12..]);
 
-            let result = DecodeResult { token: Token::Address(address.into()), new_offset: offset + 1 };
+            let result = DecodeResult {
+                token: Token::Address(address.into()),
+                new_offset: offset + 1,
+            };
 
             Ok(result)
-        }*/
+        }
         ParamType::Int(_) => {
             let slice = peek(slices, offset)?;
 
@@ -226,118 +499,379 @@ fn decode_param(param: &ParamType, slices: &[Word], offset: usize) -> Result<Dec
             };
 
             Ok(result)
-        } /*ParamType::Bool => {
-              let slice = peek(slices, offset)?;
-
-              let b = as_bool(slice)?;
-
-              let result = DecodeResult { token: Token::Bool(b), new_offset: offset + 1 };
-              Ok(result)
-          }
-          ParamType::FixedBytes(len) => {
-              // FixedBytes is anything from bytes1 to bytes32. These values
-              // are padded with trailing zeros to fill 32 bytes.
-              let taken = take_bytes(slices, offset, len)?;
-              let result = DecodeResult { token: Token::FixedBytes(taken.bytes), new_offset: taken.new_offset };
-              Ok(result)
-          }
-          ParamType::Bytes => {
-              let offset_slice = peek(slices, offset)?;
-              let len_offset = (as_u32(offset_slice)? / 32) as usize;
-
-              let len_slice = peek(slices, len_offset)?;
-              let len = as_u32(len_slice)? as usize;
-
-              let taken = take_bytes(slices, len_offset + 1, len)?;
-
-              let result = DecodeResult { token: Token::Bytes(taken.bytes), new_offset: offset + 1 };
-              Ok(result)
-          }
-          ParamType::String => {
-              let offset_slice = peek(slices, offset)?;
-              let len_offset = (as_u32(offset_slice)? / 32) as usize;
-
-              let len_slice = peek(slices, len_offset)?;
-              let len = as_u32(len_slice)? as usize;
-
-              let taken = take_bytes(slices, len_offset + 1, len)?;
-
-              let result = DecodeResult { token: Token::String(String::from_utf8(taken.bytes)?), new_offset: offset + 1 };
-              Ok(result)
-          }
-          ParamType::Array(ref t) => {
-              let offset_slice = peek(slices, offset)?;
-              let len_offset = (as_u32(offset_slice)? / 32) as usize;
-              let len_slice = peek(slices, len_offset)?;
-              let len = as_u32(len_slice)? as usize;
-
-              let tail = &slices[len_offset + 1..];
-              let mut tokens = Vec::with_capacity(len);
-              let mut new_offset = 0;
-
-              for _ in 0..len {
-                  let res = decode_param(t, &tail, new_offset)?;
-                  new_offset = res.new_offset;
-                  tokens.push(res.token);
-              }
-
-              let result = DecodeResult { token: Token::Array(tokens), new_offset: offset + 1 };
-
-              Ok(result)
-          }
-          ParamType::FixedArray(ref t, len) => {
-              let mut tokens = Vec::with_capacity(len);
-              let is_dynamic = param.is_dynamic();
-
-              let (tail, mut new_offset) = if is_dynamic {
-                  (&slices[(as_u32(peek(slices, offset)?)? as usize / 32)..], 0)
-              } else {
-                  (slices, offset)
-              };
-
-              for _ in 0..len {
-                  let res = decode_param(t, &tail, new_offset)?;
-                  new_offset = res.new_offset;
-                  tokens.push(res.token);
-              }
-
-              let result = DecodeResult {
-                  token: Token::FixedArray(tokens),
-                  new_offset: if is_dynamic { offset + 1 } else { new_offset },
-              };
-
-              Ok(result)
-          }
-          ParamType::Tuple(ref t) => {
-              let is_dynamic = param.is_dynamic();
-
-              // The first element in a dynamic Tuple is an offset to the Tuple's data
-              // For a static Tuple the data begins right away
-              let (tail, mut new_offset) = if is_dynamic {
-                  (&slices[(as_u32(peek(slices, offset)?)? as usize / 32)..], 0)
-              } else {
-                  (slices, offset)
-              };
-
-              let len = t.len();
-              let mut tokens = Vec::with_capacity(len);
-              for param in t {
-                  let res = decode_param(param, &tail, new_offset)?;
-                  new_offset = res.new_offset;
-                  tokens.push(res.token);
-              }
-
-              // The returned new_offset depends on whether the Tuple is dynamic
-              // dynamic Tuple -> follows the prefixed Tuple data offset element
-              // static Tuple  -> follows the last data element
-              let result = DecodeResult {
-                  token: Token::Tuple(tokens),
-                  new_offset: if is_dynamic { offset + 1 } else { new_offset },
-              };
-
-              Ok(result)
-          }*/
+        }
+        ParamType::Bool => {
+            let slice = peek(slices, offset)?;
+
+            let b = as_bool(slice)?;
+
+            let result = DecodeResult {
+                token: Token::Bool(b),
+                new_offset: offset + 1,
+            };
+            Ok(result)
+        }
+        ParamType::FixedBytes(len) => {
+            // FixedBytes is anything from bytes1 to bytes32. These values
+            // are padded with trailing zeros to fill 32 bytes.
+            let taken = take_bytes(slices, offset, len as usize)?;
+            let result = DecodeResult {
+                token: Token::FixedBytes(taken.bytes),
+                new_offset: taken.new_offset,
+            };
+            Ok(result)
+        }
+        ParamType::Bytes | ParamType::String => {
+            let offset_slice = peek(slices, offset)?;
+            let len_offset = (as_u32(offset_slice)? / 32) as usize;
+
+            let len_slice = peek(slices, len_offset)?;
+            let len = as_u32(len_slice)? as usize;
+
+            let taken = take_bytes(slices, len_offset + 1, len)?;
+
+            let token = if *param == ParamType::String {
+                sp_std::str::from_utf8(&taken.bytes).map_err(|_| Error::InvalidData)?;
+                Token::String(taken.bytes)
+            } else {
+                Token::Bytes(taken.bytes)
+            };
+            let result = DecodeResult {
+                token,
+                new_offset: offset + 1,
+            };
+            Ok(result)
+        }
+        ParamType::Array(ref t) => {
+            let offset_slice = peek(slices, offset)?;
+            let len_offset = (as_u32(offset_slice)? / 32) as usize;
+            let len_slice = peek(slices, len_offset)?;
+            let len = as_u32(len_slice)? as usize;
+
+            let tail = &slices[len_offset + 1..];
+            // Each element takes at least 1 word so this bounds the allocation by the size of the data
+            let mut tokens = Vec::with_capacity(len.min(tail.len()));
+            let mut new_offset = 0;
+
+            for _ in 0..len {
+                let res = decode_param(t, &tail, new_offset)?;
+                new_offset = res.new_offset;
+                tokens.push(res.token);
+            }
+
+            let result = DecodeResult {
+                token: Token::Array(tokens),
+                new_offset: offset + 1,
+            };
+
+            Ok(result)
+        }
+        ParamType::FixedArray(ref t, len) => {
+            let len = len as usize;
+            let is_dynamic = param.is_dynamic();
+
+            let (tail, mut new_offset) = if is_dynamic {
+                (tail_at(slices, offset)?, 0)
+            } else {
+                (slices, offset)
+            };
+
+            let mut tokens = Vec::with_capacity(len.min(tail.len()));
+            for _ in 0..len {
+                let res = decode_param(t, &tail, new_offset)?;
+                new_offset = res.new_offset;
+                tokens.push(res.token);
+            }
+
+            let result = DecodeResult {
+                token: Token::FixedArray(tokens),
+                new_offset: if is_dynamic { offset + 1 } else { new_offset },
+            };
+
+            Ok(result)
+        }
+        ParamType::Tuple(ref t) => {
+            let is_dynamic = param.is_dynamic();
+
+            // The first element in a dynamic Tuple is an offset to the Tuple's data
+            // For a static Tuple the data begins right away
+            let (tail, mut new_offset) = if is_dynamic {
+                (tail_at(slices, offset)?, 0)
+            } else {
+                (slices, offset)
+            };
+
+            let len = t.len();
+            let mut tokens = Vec::with_capacity(len);
+            for param in t {
+                let res = decode_param(param, &tail, new_offset)?;
+                new_offset = res.new_offset;
+                tokens.push(res.token);
+            }
+
+            // The returned new_offset depends on whether the Tuple is dynamic
+            // dynamic Tuple -> follows the prefixed Tuple data offset element
+            // static Tuple  -> follows the last data element
+            let result = DecodeResult {
+                token: Token::Tuple(tokens),
+                new_offset: if is_dynamic { offset + 1 } else { new_offset },
+            };
+
+            Ok(result)
+        }
+    }
+}
+
+fn pad_u32(value: u32) -> Word {
+    let mut padded = [0u8; 32];
+    padded[28..32].copy_from_slice(&value.to_be_bytes());
+    padded
+}
+
+fn pad_bytes(bytes: &[u8]) -> Vec<Word> {
+    let mut result = vec![pad_u32(bytes.len() as u32)];
+    result.extend(pad_fixed_bytes(bytes));
+    result
+}
+
+fn pad_fixed_bytes(bytes: &[u8]) -> Vec<Word> {
+    let len = (bytes.len() + 31) / 32;
+    let mut result = Vec::with_capacity(len);
+    for i in 0..len {
+        let mut padded = [0u8; 32];
+
+        let to_copy = match i == len - 1 {
+            false => 32,
+            true => match bytes.len() % 32 {
+                0 => 32,
+                x => x,
+            },
+        };
+
+        let offset = 32 * i;
+        padded[..to_copy].copy_from_slice(&bytes[offset..offset + to_copy]);
+        result.push(padded);
+    }
+
+    result
+}
+
+/// Intermediate encoding of a token, split into the head and the tail of the ABI encoding
+enum Mediate {
+    Raw(Vec<Word>),
+    RawTuple(Vec<Mediate>),
+    Prefixed(Vec<Word>),
+    PrefixedArray(Vec<Mediate>),
+    PrefixedArrayWithLength(Vec<Mediate>),
+    PrefixedTuple(Vec<Mediate>),
+}
+
+impl Mediate {
+    fn head_len(&self) -> u32 {
+        match self {
+            Mediate::Raw(raw) => 32 * raw.len() as u32,
+            Mediate::RawTuple(mediates) => mediates.iter().map(|m| m.head_len()).sum(),
+            Mediate::Prefixed(_)
+            | Mediate::PrefixedArray(_)
+            | Mediate::PrefixedArrayWithLength(_)
+            | Mediate::PrefixedTuple(_) => 32,
+        }
+    }
+
+    fn tail_len(&self) -> u32 {
+        match self {
+            Mediate::Raw(_) | Mediate::RawTuple(_) => 0,
+            Mediate::Prefixed(pre) => pre.len() as u32 * 32,
+            Mediate::PrefixedArray(mediates) | Mediate::PrefixedTuple(mediates) => mediates
+                .iter()
+                .fold(0, |acc, m| acc + m.head_len() + m.tail_len()),
+            Mediate::PrefixedArrayWithLength(mediates) => mediates
+                .iter()
+                .fold(32, |acc, m| acc + m.head_len() + m.tail_len()),
+        }
+    }
+
+    fn head(&self, suffix_offset: u32) -> Vec<Word> {
+        match self {
+            Mediate::Raw(raw) => raw.clone(),
+            Mediate::RawTuple(mediates) => mediates.iter().flat_map(|m| m.head(0)).collect(),
+            Mediate::Prefixed(_)
+            | Mediate::PrefixedArray(_)
+            | Mediate::PrefixedArrayWithLength(_)
+            | Mediate::PrefixedTuple(_) => vec![pad_u32(suffix_offset)],
+        }
     }
+
+    fn tail(&self) -> Vec<Word> {
+        match self {
+            Mediate::Raw(_) | Mediate::RawTuple(_) => vec![],
+            Mediate::Prefixed(raw) => raw.clone(),
+            Mediate::PrefixedArray(mediates) | Mediate::PrefixedTuple(mediates) => {
+                encode_head_tail(mediates)
+            }
+            Mediate::PrefixedArrayWithLength(mediates) => {
+                let mut result = vec![pad_u32(mediates.len() as u32)];
+                result.extend(encode_head_tail(mediates));
+                result
+            }
+        }
+    }
+}
+
+fn encode_head_tail(mediates: &[Mediate]) -> Vec<Word> {
+    let heads_len = mediates.iter().fold(0, |acc, m| acc + m.head_len());
+
+    let mut result = Vec::new();
+    let mut offset = heads_len;
+    for m in mediates {
+        result.extend(m.head(offset));
+        offset += m.tail_len();
+    }
+    for m in mediates {
+        result.extend(m.tail());
+    }
+    result
+}
+
+fn encode_token(token: &Token) -> Mediate {
+    match token {
+        Token::Address(address) => {
+            let mut padded = [0u8; 32];
+            padded[12..].copy_from_slice(address.as_bytes());
+            Mediate::Raw(vec![padded])
+        }
+        Token::Bytes(bytes) | Token::String(bytes) => Mediate::Prefixed(pad_bytes(bytes)),
+        Token::FixedBytes(bytes) => Mediate::Raw(pad_fixed_bytes(bytes)),
+        Token::Int(int) => Mediate::Raw(vec![(*int).into()]),
+        Token::Uint(uint) => Mediate::Raw(vec![(*uint).into()]),
+        Token::Bool(b) => {
+            let mut value = [0u8; 32];
+            if *b {
+                value[31] = 1;
+            }
+            Mediate::Raw(vec![value])
+        }
+        Token::Array(tokens) => {
+            Mediate::PrefixedArrayWithLength(tokens.iter().map(encode_token).collect())
+        }
+        Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+            let mediates = tokens.iter().map(encode_token).collect();
+            match (token.is_dynamic(), token) {
+                (true, Token::FixedArray(_)) => Mediate::PrefixedArray(mediates),
+                (true, _) => Mediate::PrefixedTuple(mediates),
+                (false, _) => Mediate::RawTuple(mediates),
+            }
+        }
+    }
+}
+
+/// Encodes vector of tokens into ABI compliant vector of bytes.
+pub fn encode(tokens: &[Token]) -> Vec<u8> {
+    let mediates: Vec<Mediate> = tokens.iter().map(encode_token).collect();
+    encode_head_tail(&mediates)
+        .iter()
+        .flat_map(|word| word.to_vec())
+        .collect()
+}
+
+/// Parses a comma separated list of types in parentheses, like `(uint80,int256)`, as used for return values.
+pub fn parse_types(types: &str) -> Result<Vec<ParamType>, Error> {
+    match types.parse()? {
+        ParamType::Tuple(params) => Ok(params),
+        _ => Err(Error::InvalidName),
+    }
+}
+
+/// Parses a human readable function or event signature like `setData(uint80,int256)` into the name and
+/// parameter types.
+pub fn parse_signature(signature: &str) -> Result<(&str, Vec<ParamType>), Error> {
+    let pos = signature.find('(').ok_or(Error::InvalidName)?;
+    let name = &signature[..pos];
+    if name.is_empty() {
+        return Err(Error::InvalidName);
+    }
+    Ok((name, parse_types(&signature[pos..])?))
+}
+
+/// Keccak-256 hash of the canonical signature made from the name and parameter types
+fn signature_hash(name: &str, params: &[ParamType]) -> [u8; 32] {
+    let mut canonical = Vec::from(name.as_bytes());
+    write_canonical_list(params, &mut canonical);
+    sp_io::hashing::keccak_256(&canonical)
+}
+
+/// The 4 byte selector of the function with the given signature, like `0xfeaf968c` for `latestRoundData()`.
+pub fn function_selector(signature: &str) -> Result<[u8; 4], Error> {
+    let (name, params) = parse_signature(signature)?;
+    let mut selector = [0u8; 4];
+    selector.copy_from_slice(&signature_hash(name, &params)[..4]);
+    Ok(selector)
+}
+
+/// Encodes a call of the function with the given signature, like `setData(uint80,int256)`, with the given
+/// arguments to the calldata for EVM. The arguments must match the parameter types of the signature.
+pub fn encode_function_call(signature: &str, args: &[Token]) -> Result<Vec<u8>, Error> {
+    let (_, params) = parse_signature(signature)?;
+    if !Token::types_check(args, &params) {
+        return Err(Error::InvalidData);
+    }
+    let mut calldata = function_selector(signature)?.to_vec();
+    calldata.extend(encode(args));
+    Ok(calldata)
+}
+
+/// The first topic of logs of the (non anonymous) event with the given signature, like
+/// `AnswerUpdated(int256,uint256,uint256)`.
+pub fn event_topic(signature: &str) -> Result<H256, Error> {
+    let (name, params) = parse_signature(signature)?;
+    Ok(H256(signature_hash(name, &params)))
+}
+
+/// Decodes a log of the event with the given signature. `indexed` tells for each parameter if it is indexed
+/// and thus in the topics rather than in the data. Indexed parameters which are not value types are
+/// hashed in the topics so are returned as `FixedBytes` of the hash. Returns tokens of all parameters in
+/// the order of the signature.
+pub fn decode_log(
+    signature: &str,
+    indexed: &[bool],
+    topics: &[H256],
+    data: &[u8],
+) -> Result<Vec<Token>, Error> {
+    let (name, params) = parse_signature(signature)?;
+    if indexed.len() != params.len() {
+        return Err(Error::InvalidName);
+    }
+    let indexed_count = indexed.iter().filter(|i| **i).count();
+    if topics.len() != indexed_count + 1 || topics[0] != H256(signature_hash(name, &params)) {
+        return Err(Error::InvalidData);
+    }
+
+    let non_indexed_params: Vec<ParamType> = params
+        .iter()
+        .zip(indexed.iter())
+        .filter(|(_, i)| !**i)
+        .map(|(p, _)| p.clone())
+        .collect();
+    let mut non_indexed = decode(&non_indexed_params, data)?.into_iter();
+    let mut topics = topics[1..].iter();
+
+    let mut tokens = Vec::with_capacity(params.len());
+    for (param, is_indexed) in params.iter().zip(indexed.iter()) {
+        let token = if *is_indexed {
+            let topic = topics.next().ok_or(Error::InvalidData)?;
+            if param.is_value_type() {
+                decode(&[param.clone()], topic.as_bytes())?
+                    .pop()
+                    .ok_or(Error::InvalidData)?
+            } else {
+                Token::FixedBytes(topic.as_bytes().to_vec())
+            }
+        } else {
+            non_indexed.next().ok_or(Error::InvalidData)?
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
 }
 
 #[cfg(test)]
@@ -368,4 +902,171 @@ mod tests {
         assert_eq!(decoded[0].clone().into_uint().unwrap().low_u32(), 32);
         assert_eq!(decoded[1].clone().into_int().unwrap().low_u32(), 34);
     }
+
+    #[test]
+    fn parsing_types() {
+        assert_eq!("uint80".parse::<ParamType>().unwrap(), ParamType::Uint(80));
+        assert_eq!("int".parse::<ParamType>().unwrap(), ParamType::Int(256));
+        assert_eq!(
+            "bytes32".parse::<ParamType>().unwrap(),
+            ParamType::FixedBytes(32)
+        );
+        assert_eq!(
+            "(address,bool[])[2]".parse::<ParamType>().unwrap(),
+            ParamType::FixedArray(
+                Box::new(ParamType::Tuple(vec![
+                    ParamType::Address,
+                    ParamType::Array(Box::new(ParamType::Bool))
+                ])),
+                2
+            )
+        );
+        for invalid in &[
+            "uint7", "uint264", "bytes33", "bytes0", "foo", "(uint256", "uint256 ", "int[x]",
+        ] {
+            assert!(invalid.parse::<ParamType>().is_err());
+        }
+
+        let (name, params) = parse_signature("latestRoundData()").unwrap();
+        assert_eq!(name, "latestRoundData");
+        assert!(params.is_empty());
+        assert_eq!(
+            parse_types("(uint80,int,(string,bytes))").unwrap(),
+            vec![
+                ParamType::Uint(80),
+                ParamType::Int(256),
+                ParamType::Tuple(vec![ParamType::String, ParamType::Bytes])
+            ]
+        );
+        assert!(parse_signature("(uint256)").is_err());
+        assert!(parse_types("uint256").is_err());
+
+        let mut canonical = vec![];
+        ParamType::Tuple(vec![
+            ParamType::Int(256),
+            ParamType::FixedArray(Box::new(ParamType::FixedBytes(4)), 10),
+        ])
+        .write_canonical(&mut canonical);
+        assert_eq!(canonical, b"(int256,bytes4[10])".to_vec());
+    }
+
+    #[test]
+    fn selectors() {
+        assert_eq!(
+            function_selector("latestRoundData()").unwrap(),
+            [0xfe, 0xaf, 0x96, 0x8c]
+        );
+        assert_eq!(
+            function_selector("decimals()").unwrap(),
+            [0x31, 0x3c, 0xe5, 0x67]
+        );
+        // `int` and `uint` are made canonical before hashing
+        assert_eq!(
+            function_selector("setData(uint80,int,uint,uint256,uint80)").unwrap(),
+            [0x64, 0x44, 0xbd, 0x16]
+        );
+        assert!(function_selector("latestRoundData").is_err());
+    }
+
+    #[test]
+    fn encoding() {
+        // `setData(13, 40, 1410, 1400, 13)`
+        let calldata = encode_function_call(
+            "setData(uint80,int256,uint256,uint256,uint80)",
+            &[
+                Token::Uint(13.into()),
+                Token::Int(40.into()),
+                Token::Uint(1410.into()),
+                Token::Uint(1400.into()),
+                Token::Uint(13.into()),
+            ],
+        )
+        .unwrap();
+        assert_eq!(calldata, hex::decode("6444bd16000000000000000000000000000000000000000000000000000000000000000d000000000000000000000000000000000000000000000000000000000000002800000000000000000000000000000000000000000000000000000000000005820000000000000000000000000000000000000000000000000000000000000578000000000000000000000000000000000000000000000000000000000000000d").unwrap());
+
+        // Arguments must match the signature
+        assert!(encode_function_call("setData(uint80)", &[Token::Bool(true)]).is_err());
+        assert!(encode_function_call("setData(uint80)", &[]).is_err());
+
+        assert_eq!(
+            encode_function_call("latestRoundData()", &[]).unwrap(),
+            vec![254, 175, 150, 140]
+        );
+
+        let string = encode(&[Token::String(b"gavofyork".to_vec())]);
+        assert_eq!(string, hex::decode("000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000096761766f66796f726b0000000000000000000000000000000000000000000000").unwrap());
+
+        let addresses = encode(&[Token::Array(vec![
+            Token::Address(Address::repeat_byte(0x11)),
+            Token::Address(Address::repeat_byte(0x22)),
+        ])]);
+        assert_eq!(addresses, hex::decode("0000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000200000000000000000000000011111111111111111111111111111111111111110000000000000000000000002222222222222222222222222222222222222222").unwrap());
+    }
+
+    #[test]
+    fn encoding_round_trip() {
+        let types = parse_types("(address,bytes,uint256[],(bool,string),bytes4,int8[2])").unwrap();
+        let tokens = vec![
+            Token::Address(Address::repeat_byte(7)),
+            Token::Bytes(vec![1; 40]),
+            Token::Array(vec![Token::Uint(1.into()), Token::Uint(2.into())]),
+            Token::Tuple(vec![Token::Bool(true), Token::String(b"dock".to_vec())]),
+            Token::FixedBytes(vec![1, 2, 3, 4]),
+            Token::FixedArray(vec![Token::Int(3.into()), Token::Int(4.into())]),
+        ];
+        assert!(Token::types_check(&tokens, &types));
+        let encoded = encode(&tokens);
+        assert_eq!(decode(&types, &encoded).unwrap(), tokens);
+
+        // Invalid utf-8 is not a string
+        let encoded = encode(&[Token::String(vec![0xff, 0xfe])]);
+        assert!(decode(&[ParamType::String], &encoded).is_err());
+        assert!(decode(&[ParamType::Bytes], &encoded).is_ok());
+    }
+
+    #[test]
+    fn decoding_logs() {
+        let signature = "AnswerUpdated(int256,uint256,uint256)";
+        let topic = event_topic(signature).unwrap();
+        assert_eq!(
+            topic,
+            H256::from_slice(
+                &hex::decode("0559884fd3a460db3073b7fc896cc77986f16e378210ded43186175bf646fc5f")
+                    .unwrap()
+            )
+        );
+
+        let current = H256::from_low_u64_be(2500);
+        let round_id = H256::from_low_u64_be(13);
+        let data = encode(&[Token::Uint(1400.into())]);
+        let indexed = [true, true, false];
+        assert_eq!(
+            decode_log(signature, &indexed, &[topic, current, round_id], &data).unwrap(),
+            vec![
+                Token::Int(2500.into()),
+                Token::Uint(13.into()),
+                Token::Uint(1400.into())
+            ]
+        );
+
+        // Wrong event, missing topic or missing data
+        assert!(decode_log(signature, &indexed, &[round_id, current, round_id], &data).is_err());
+        assert!(decode_log(signature, &indexed, &[topic, current], &data).is_err());
+        assert!(decode_log(signature, &indexed, &[topic, current, round_id], &[]).is_err());
+        assert!(decode_log(signature, &[true], &[topic, current, round_id], &data).is_err());
+
+        // Indexed dynamic params are hashed
+        let signature = "Named(string)";
+        let hash = H256::repeat_byte(9);
+        assert_eq!(
+            decode_log(
+                signature,
+                &[true],
+                &[event_topic(signature).unwrap(), hash],
+                &[]
+            )
+            .unwrap(),
+            vec![Token::FixedBytes(hash.as_bytes().to_vec())]
+        );
+    }
 }
@@ -0,0 +1,400 @@
+//! Pallet for the transition from proof of authority to proof of stake. Accounts bond tokens to become
+//! validator candidates and once Root switches over, the top `MaxActiveValidators` candidates by bond
+//! become the active validators at the end of every epoch. Until the switch, the PoA pallet's Root managed
+//! validator set continues to be used.
+//! Epochs, emission rewards and transaction fees are still handled by the PoA pallet and this pallet only
+//! replaces Root in deciding the active validators. To use it, the runtime sets this pallet as the session
+//! pallet's `ShouldEndSession` and `SessionManager` in place of the PoA pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Encode;
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,
+    sp_runtime::{
+        print,
+        traits::{Convert, OpaqueKeys, Saturating, Zero},
+    },
+    storage::migration::have_storage_value,
+    traits::{Currency, Get, LockIdentifier, LockableCurrency, PalletInfo, WithdrawReasons},
+    weights::{Pays, Weight},
+    StorageHasher, Twox64Concat,
+};
+use frame_system::{self as system, ensure_root, ensure_signed};
+use poa::EpochNo;
+use sp_std::prelude::Vec;
+
+#[cfg(test)]
+mod tests;
+
+const STAKING_ID: LockIdentifier = *b"dockstak";
+
+type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Config>::AccountId>>::Balance;
+
+pub trait Trait: system::Config + poa::Trait {
+    /// The overarching event type.
+    type Event: From<Event<Self>> + Into<<Self as system::Config>::Event>;
+
+    /// Currency bonded by the candidates. The bond is locked, not reserved.
+    type Currency: LockableCurrency<Self::AccountId, Moment = Self::BlockNumber>;
+
+    /// Minimum bond to be a validator candidate
+    type MinCandidateBond: Get<BalanceOf<Self>>;
+
+    /// Maximum number of validator candidates. Bounds the work done in electing validators at the
+    /// end of an epoch.
+    type MaxCandidates: Get<u32>;
+
+    /// Number of epochs, after the current one, for which an unbonded amount remains locked
+    type UnbondingEpochs: Get<EpochNo>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as Staking {
+        /// Bond of each validator candidate
+        Candidates get(fn candidate_bond):
+            map hasher(blake2_128_concat) T::AccountId => Option<BalanceOf<T>>;
+
+        /// Number of validator candidates
+        CandidateCount get(fn candidate_count): u32;
+
+        /// Amount unbonded by an account and the epoch after which it can be withdrawn. Unbonding
+        /// again adds to the amount and moves the epoch ahead.
+        Unbonding get(fn unbonding):
+            map hasher(blake2_128_concat) T::AccountId => Option<(BalanceOf<T>, EpochNo)>;
+    }
+}
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as system::Config>::AccountId,
+        Balance = BalanceOf<T>,
+    {
+        // Account became a validator candidate, (candidate, bond)
+        CandidacyDeclared(AccountId, Balance),
+
+        // Candidate increased its bond, (candidate, total bond)
+        Bonded(AccountId, Balance),
+
+        // Part of the bond unbonded, (account, amount, epoch after which it can be withdrawn)
+        Unbonded(AccountId, Balance, EpochNo),
+
+        // Candidate unbonded all of its bond and is no longer a candidate
+        CandidacyWithdrawn(AccountId),
+
+        // Validator found offline is no longer a candidate and its whole bond is unbonding
+        CandidateChilled(AccountId),
+
+        // Unbonded amount unlocked, (account, amount)
+        Withdrawn(AccountId, Balance),
+
+        // Active validators are now elected by bond, (validators that became candidates on switching)
+        SwitchedToProofOfStake(Vec<AccountId>),
+    }
+);
+
+decl_error! {
+    /// Errors for the module.
+    pub enum Error for Module<T: Trait> {
+        AlreadyCandidate,
+        NotCandidate,
+        BondTooLow,
+        TooManyCandidates,
+        InsufficientFreeBalance,
+        InvalidBondAmount,
+        InvalidUnbondAmount,
+        NothingToWithdraw,
+        UnbondingNotOver,
+        AlreadySwitched
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Become a validator candidate by bonding at least `MinCandidateBond`. The candidate's
+        /// session keys are set as well since it can be elected at the end of any epoch.
+        /// # <weight>
+        /// Weight of session pallet's `set_keys` dispatchable, 6 reads and 4 writes, and a read and a
+        /// write for each session key
+        /// # </weight>
+        #[weight = 200_000_000
+            + T::DbWeight::get().reads(6 + T::Keys::key_ids().len() as Weight)
+            + T::DbWeight::get().writes(4 + T::Keys::key_ids().len() as Weight)]
+        pub fn declare_candidacy(origin, bond: BalanceOf<T>, keys: T::Keys) -> dispatch::DispatchResult {
+            let candidate = ensure_signed(origin.clone())?;
+            ensure!(
+                !Candidates::<T>::contains_key(&candidate),
+                Error::<T>::AlreadyCandidate
+            );
+            ensure!(bond >= T::MinCandidateBond::get(), Error::<T>::BondTooLow);
+            ensure!(
+                Self::candidate_count() < T::MaxCandidates::get(),
+                Error::<T>::TooManyCandidates
+            );
+            Self::ensure_can_lock(&candidate, bond)?;
+            <pallet_session::Module<T>>::set_keys(origin, keys, [].to_vec())?;
+
+            Self::add_candidate(&candidate, bond);
+            Self::deposit_event(RawEvent::CandidacyDeclared(candidate, bond));
+            Ok(())
+        }
+
+        /// Increase the bond of a candidate
+        /// # <weight>
+        /// 3 reads and 2 writes
+        /// # </weight>
+        #[weight = T::DbWeight::get().reads_writes(3, 2)]
+        pub fn bond_extra(origin, amount: BalanceOf<T>) -> dispatch::DispatchResult {
+            let candidate = ensure_signed(origin)?;
+            ensure!(!amount.is_zero(), Error::<T>::InvalidBondAmount);
+            let bond = Self::candidate_bond(&candidate).ok_or(Error::<T>::NotCandidate)?;
+            let bond = bond.saturating_add(amount);
+            Self::ensure_can_lock(&candidate, bond)?;
+
+            Candidates::<T>::insert(&candidate, bond);
+            Self::update_lock(&candidate);
+            Self::deposit_event(RawEvent::Bonded(candidate, bond));
+            Ok(())
+        }
+
+        /// Unbond part or all of the bond. The remaining bond should either be 0, in which case the
+        /// account is no longer a candidate, or at least `MinCandidateBond`. The unbonded amount remains
+        /// locked for `UnbondingEpochs` epochs after the current one and is then withdrawn with
+        /// `withdraw_unbonded`. An active validator that unbonds all remains active till the end of the
+        /// current epoch.
+        /// # <weight>
+        /// 4 reads and 3 writes
+        /// # </weight>
+        #[weight = T::DbWeight::get().reads_writes(4, 3)]
+        pub fn unbond(origin, amount: BalanceOf<T>) -> dispatch::DispatchResult {
+            let candidate = ensure_signed(origin)?;
+            let bond = Self::candidate_bond(&candidate).ok_or(Error::<T>::NotCandidate)?;
+            ensure!(
+                !amount.is_zero() && amount <= bond,
+                Error::<T>::InvalidUnbondAmount
+            );
+            let remaining = bond - amount;
+            ensure!(
+                remaining.is_zero() || remaining >= T::MinCandidateBond::get(),
+                Error::<T>::BondTooLow
+            );
+
+            Self::unbond_(&candidate, remaining, amount);
+            Ok(())
+        }
+
+        /// Unlock the unbonded amount once `UnbondingEpochs` epochs have passed since the last unbond
+        /// # <weight>
+        /// 4 reads and 2 writes
+        /// # </weight>
+        #[weight = T::DbWeight::get().reads_writes(4, 2)]
+        pub fn withdraw_unbonded(origin) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            let (amount, unlock_epoch) = Self::unbonding(&who).ok_or(Error::<T>::NothingToWithdraw)?;
+            ensure!(
+                <poa::Module<T>>::epoch() > unlock_epoch,
+                Error::<T>::UnbondingNotOver
+            );
+
+            Unbonding::<T>::remove(&who);
+            Self::update_lock(&who);
+            Self::deposit_event(RawEvent::Withdrawn(who, amount));
+            Ok(())
+        }
+
+        /// Switch from Root managed validators to validators elected by bond. The current active
+        /// validators stay active till the end of the current epoch and each of them that can afford
+        /// `MinCandidateBond` from its free balance becomes a candidate with that bond. Queued additions,
+        /// removals and swaps of validators are dropped. Cannot be undone.
+        /// # <weight>
+        /// For each active validator, 4 reads and 2 writes besides 5 reads and 5 writes
+        /// # </weight>
+        #[weight = T::DbWeight::get().reads_writes(5, 5)
+            + T::DbWeight::get().reads_writes(4, 2) * <poa::Module<T>>::max_active_validators() as Weight]
+        pub fn switch_to_pos(origin) -> dispatch::DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            ensure!(
+                !<poa::Module<T>>::validators_elected_externally(),
+                Error::<T>::AlreadySwitched
+            );
+
+            let bond = T::MinCandidateBond::get();
+            let mut migrated = Vec::new();
+            for v in <poa::Module<T>>::hand_over_validator_set() {
+                if Candidates::<T>::contains_key(&v) {
+                    continue;
+                }
+                if Self::candidate_count() >= T::MaxCandidates::get() {
+                    print("Cannot make validator a candidate as max candidates reached");
+                    break;
+                }
+                if Self::ensure_can_lock(&v, bond).is_err() {
+                    print("Cannot make validator a candidate as free balance is insufficient");
+                    continue;
+                }
+                Self::add_candidate(&v, bond);
+                migrated.push(v);
+            }
+            Self::deposit_event(RawEvent::SwitchedToProofOfStake(migrated));
+            Ok(Pays::No.into())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Add a new candidate with the given bond and lock the bond
+    fn add_candidate(candidate: &T::AccountId, bond: BalanceOf<T>) {
+        Candidates::<T>::insert(candidate, bond);
+        CandidateCount::mutate(|c| *c += 1);
+        Self::update_lock(candidate);
+    }
+
+    /// Move `amount` of the candidate's bond to unbonding, leaving `remaining` bonded. The candidacy
+    /// ends when nothing remains bonded.
+    fn unbond_(candidate: &T::AccountId, remaining: BalanceOf<T>, amount: BalanceOf<T>) {
+        let unlock_epoch = <poa::Module<T>>::epoch().saturating_add(T::UnbondingEpochs::get());
+        let unbonding = Self::unbonding(candidate)
+            .map(|(unbonding, _)| unbonding)
+            .unwrap_or_else(Zero::zero);
+        Unbonding::<T>::insert(candidate, (unbonding.saturating_add(amount), unlock_epoch));
+        if remaining.is_zero() {
+            Candidates::<T>::remove(candidate);
+            CandidateCount::mutate(|c| *c -= 1);
+            Self::deposit_event(RawEvent::CandidacyWithdrawn(candidate.clone()));
+        } else {
+            Candidates::<T>::insert(candidate, remaining);
+        }
+        // The lock stays the same as the unbonded amount is still locked
+        Self::deposit_event(RawEvent::Unbonded(candidate.clone(), amount, unlock_epoch));
+    }
+
+    /// Unbond the whole bond of the validators the PoA pallet queued for removal for being offline so
+    /// that they are not elected again until they declare candidacy again. Called before electing.
+    pub fn chill_offline_validators() {
+        for v in <poa::Module<T>>::validators_to_remove() {
+            if let Some(bond) = Self::candidate_bond(&v) {
+                Self::unbond_(&v, Zero::zero(), bond);
+                Self::deposit_event(RawEvent::CandidateChilled(v));
+            }
+        }
+    }
+
+    /// Check that the free balance of the account can cover the given bond and any amount being unbonded
+    fn ensure_can_lock(who: &T::AccountId, bond: BalanceOf<T>) -> dispatch::DispatchResult {
+        let unbonding = Self::unbonding(who)
+            .map(|(unbonding, _)| unbonding)
+            .unwrap_or_else(Zero::zero);
+        ensure!(
+            <T as Trait>::Currency::free_balance(who) >= bond.saturating_add(unbonding),
+            Error::<T>::InsufficientFreeBalance
+        );
+        Ok(())
+    }
+
+    /// Lock the bond and the amount being unbonded, removing the lock when both are 0
+    fn update_lock(who: &T::AccountId) {
+        let bond = Self::candidate_bond(who).unwrap_or_else(Zero::zero);
+        let unbonding = Self::unbonding(who)
+            .map(|(unbonding, _)| unbonding)
+            .unwrap_or_else(Zero::zero);
+        let locked = bond.saturating_add(unbonding);
+        if locked.is_zero() {
+            <T as Trait>::Currency::remove_lock(STAKING_ID, who);
+        } else {
+            <T as Trait>::Currency::set_lock(STAKING_ID, who, locked, WithdrawReasons::all());
+        }
+    }
+
+    /// Whether the account has session keys set. Keys are set on declaring candidacy but can be
+    /// removed later with the session pallet's `purge_keys`.
+    fn has_session_keys(who: &T::AccountId) -> bool {
+        let id = match <T as pallet_session::Config>::ValidatorIdOf::convert(who.clone()) {
+            Some(id) => id,
+            None => return false,
+        };
+        // The session pallet does not expose its `NextKeys` map so the map is read from storage
+        // under the session pallet's name in the runtime. If the name is not known, the keys are
+        // assumed to be set rather than skipping every candidate.
+        match <T as system::Config>::PalletInfo::name::<pallet_session::Module<T>>() {
+            Some(pallet) => have_storage_value(
+                pallet.as_bytes(),
+                b"NextKeys",
+                &Twox64Concat::hash(&id.encode()),
+            ),
+            None => {
+                print("Cannot find the session pallet's name");
+                true
+            }
+        }
+    }
+
+    /// Top `max_validators` candidates by bond among the ones with session keys. Ties are broken in
+    /// favor of the smaller account id to keep the election deterministic.
+    pub fn elect(max_validators: u8) -> Vec<T::AccountId> {
+        let mut candidates = Candidates::<T>::iter()
+            .filter(|(v, _)| Self::has_session_keys(v))
+            .collect::<Vec<_>>();
+        candidates.sort_by(|(a, a_bond), (b, b_bond)| b_bond.cmp(a_bond).then_with(|| a.cmp(b)));
+        candidates
+            .into_iter()
+            .take(max_validators as usize)
+            .map(|(v, _)| v)
+            .collect()
+    }
+}
+
+/// Until the switch, defers to the PoA pallet. After the switch, ends the epoch like the PoA pallet does
+/// but the active validators for the next epoch are elected by bond rather than taken from the queues
+/// managed by Root.
+impl<T: Trait> pallet_session::ShouldEndSession<T::BlockNumber> for Module<T> {
+    fn should_end_session(now: T::BlockNumber) -> bool {
+        if !<poa::Module<T>>::validators_elected_externally() {
+            return <poa::Module<T> as pallet_session::ShouldEndSession<T::BlockNumber>>::should_end_session(now);
+        }
+
+        let current_slot_no = match <poa::Module<T>>::current_slot_no() {
+            Some(s) => s,
+            None => {
+                print("Cannot fetch slot number");
+                return false;
+            }
+        };
+
+        if current_slot_no > <poa::Module<T>>::epoch_ends_at() {
+            // Mint and disburse rewards to validators and treasury for the ending epoch
+            <poa::Module<T>>::update_details_for_ending_epoch(current_slot_no);
+
+            // Offline validators stop being candidates rather than missing only the next epoch
+            Self::chill_offline_validators();
+            let max_validators = <poa::Module<T>>::get_and_set_max_active_validators_on_epoch_end();
+            let active_validator_count =
+                <poa::Module<T>>::set_elected_validators(Self::elect(max_validators));
+
+            <poa::Module<T>>::set_next_epoch_end(current_slot_no, active_validator_count);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The PoA pallet feeds the active validators to the session pallet and tracks the beginning of
+/// epochs, both before and after the switch.
+impl<T: Trait> pallet_session::SessionManager<T::AccountId> for Module<T> {
+    fn new_session(session_idx: u32) -> Option<Vec<T::AccountId>> {
+        <poa::Module<T> as pallet_session::SessionManager<T::AccountId>>::new_session(session_idx)
+    }
+
+    fn end_session(session_idx: u32) {
+        <poa::Module<T> as pallet_session::SessionManager<T::AccountId>>::end_session(session_idx)
+    }
+
+    fn start_session(session_idx: u32) {
+        <poa::Module<T> as pallet_session::SessionManager<T::AccountId>>::start_session(session_idx)
+    }
+}
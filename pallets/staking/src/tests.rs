@@ -0,0 +1,410 @@
+#![cfg(test)]
+
+use super::*;
+use frame_support::{
+    assert_err, assert_ok, parameter_types,
+    sp_runtime::{
+        testing::{Header, UintAuthorityId},
+        traits::{BlakeTwo256, ConvertInto, IdentityLookup},
+        BuildStorage, KeyTypeId, Perbill,
+    },
+    storage::migration::put_storage_value,
+    weights::constants::WEIGHT_PER_SECOND,
+};
+use frame_system::RawOrigin;
+use sp_core::{crypto::key_types, H256};
+
+use crate as staking;
+
+type AccountId = u64;
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+    pub const MaximumBlockWeight: Weight = 2 * WEIGHT_PER_SECOND;
+    pub const MaximumBlockLength: u32 = 2 * 1024;
+    pub const AvailableBlockRatio: Perbill = Perbill::one();
+}
+
+impl system::Config for TestRuntime {
+    type BaseCallFilter = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = u64;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = ();
+    type BlockHashCount = BlockHashCount;
+    type DbWeight = ();
+    type BlockWeights = ();
+    type BlockLength = ();
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = balances::AccountData<u64>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+}
+
+impl balances::Config for TestRuntime {
+    type MaxLocks = ();
+    type Balance = u64;
+    type Event = ();
+    type DustRemoval = ();
+    type ExistentialDeposit = ();
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+/// Dummy session handler as the pallet's trait needs the session pallet's trait
+pub struct TestSessionHandler;
+impl pallet_session::SessionHandler<AccountId> for TestSessionHandler {
+    const KEY_TYPE_IDS: &'static [KeyTypeId] = &[key_types::DUMMY];
+
+    fn on_genesis_session<K: OpaqueKeys>(_validators: &[(AccountId, K)]) {}
+
+    fn on_new_session<K: OpaqueKeys>(
+        _changed: bool,
+        _validators: &[(AccountId, K)],
+        _queued_validators: &[(AccountId, K)],
+    ) {
+    }
+
+    fn on_disabled(_validator_index: usize) {}
+}
+
+impl pallet_session::Config for TestRuntime {
+    type Event = ();
+    type ValidatorId = AccountId;
+    type ValidatorIdOf = ConvertInto;
+    type ShouldEndSession = Staking;
+    type NextSessionRotation = ();
+    type SessionManager = Staking;
+    type SessionHandler = TestSessionHandler;
+    type Keys = UintAuthorityId;
+    type DisabledValidatorsThreshold = ();
+    type WeightInfo = ();
+}
+
+impl pallet_authorship::Config for TestRuntime {
+    type FindAuthor = ();
+    type UncleGenerations = ();
+    type FilterUncle = ();
+    type EventHandler = ();
+}
+
+impl poa::Trait for TestRuntime {
+    type Event = ();
+    type Currency = balances::Module<Self>;
+}
+
+parameter_types! {
+    pub const MinCandidateBond: u64 = 100;
+    pub const MaxCandidates: u32 = 4;
+    pub const UnbondingEpochs: EpochNo = 2;
+}
+
+impl super::Trait for TestRuntime {
+    type Event = ();
+    type Currency = balances::Module<Self>;
+    type MinCandidateBond = MinCandidateBond;
+    type MaxCandidates = MaxCandidates;
+    type UnbondingEpochs = UnbondingEpochs;
+}
+
+pub type Block = sp_runtime::generic::Block<Header, UncheckedExtrinsic>;
+pub type UncheckedExtrinsic = sp_runtime::generic::UncheckedExtrinsic<u32, u64, Call, ()>;
+
+frame_support::construct_runtime!(
+    pub enum TestRuntime where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic
+    {
+        System: system::{Module, Call},
+        Balances: balances::{Module, Call, Storage, Config<T>},
+        Session: pallet_session::{Module, Call, Storage},
+        PoAModule: poa::{Module, Call, Storage, Config<T>},
+        Staking: staking::{Module, Call, Storage},
+    }
+);
+
+fn new_test_ext() -> sp_io::TestExternalities {
+    let mut ext: sp_io::TestExternalities = GenesisConfig {
+        balances: Some(balances::GenesisConfig {
+            balances: vec![
+                (1, 1000),
+                (2, 1000),
+                (3, 50),
+                (4, 1000),
+                (5, 1000),
+                (6, 1000),
+            ],
+        }),
+        poa: Some(PoAModuleConfig {
+            min_epoch_length: 25,
+            max_active_validators: 2,
+            active_validators: vec![1, 2, 3],
+            emission_supply: 0,
+            max_emm_validator_epoch: 0,
+            treasury_reward_pc: 0,
+            validator_reward_lock_pc: 0,
+            emission_status: false,
+        }),
+    }
+    .build_storage()
+    .unwrap()
+    .into();
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
+
+fn set_epoch(epoch: EpochNo) {
+    put_storage_value(b"PoAModule", b"Epoch", &[], epoch);
+}
+
+fn locked(who: AccountId) -> u64 {
+    Balances::locks(&who)
+        .iter()
+        .find(|l| l.id == STAKING_ID)
+        .map(|l| l.amount)
+        .unwrap_or(0)
+}
+
+#[test]
+fn candidacy_and_unbonding() {
+    new_test_ext().execute_with(|| {
+        assert_err!(
+            Staking::declare_candidacy(Origin::signed(4), 99, UintAuthorityId(4)),
+            Error::<TestRuntime>::BondTooLow
+        );
+        assert_err!(
+            Staking::declare_candidacy(Origin::signed(4), 1001, UintAuthorityId(4)),
+            Error::<TestRuntime>::InsufficientFreeBalance
+        );
+        assert_ok!(Staking::declare_candidacy(
+            Origin::signed(4),
+            200,
+            UintAuthorityId(4)
+        ));
+        assert_err!(
+            Staking::declare_candidacy(Origin::signed(4), 200, UintAuthorityId(4)),
+            Error::<TestRuntime>::AlreadyCandidate
+        );
+        assert_eq!(Staking::candidate_bond(4), Some(200));
+        assert_eq!(Staking::candidate_count(), 1);
+        assert_eq!(locked(4), 200);
+
+        assert_err!(
+            Staking::bond_extra(Origin::signed(5), 100),
+            Error::<TestRuntime>::NotCandidate
+        );
+        assert_err!(
+            Staking::bond_extra(Origin::signed(4), 801),
+            Error::<TestRuntime>::InsufficientFreeBalance
+        );
+        assert_err!(
+            Staking::bond_extra(Origin::signed(4), 0),
+            Error::<TestRuntime>::InvalidBondAmount
+        );
+        assert_ok!(Staking::bond_extra(Origin::signed(4), 300));
+        assert_eq!(Staking::candidate_bond(4), Some(500));
+        assert_eq!(locked(4), 500);
+
+        // Remaining bond should be either 0 or at least the minimum
+        assert_err!(
+            Staking::unbond(Origin::signed(4), 0),
+            Error::<TestRuntime>::InvalidUnbondAmount
+        );
+        assert_err!(
+            Staking::unbond(Origin::signed(4), 501),
+            Error::<TestRuntime>::InvalidUnbondAmount
+        );
+        assert_err!(
+            Staking::unbond(Origin::signed(4), 401),
+            Error::<TestRuntime>::BondTooLow
+        );
+
+        set_epoch(3);
+        assert_ok!(Staking::unbond(Origin::signed(4), 200));
+        assert_eq!(Staking::candidate_bond(4), Some(300));
+        assert_eq!(Staking::unbonding(4), Some((200, 5)));
+        // Unbonded amount is still locked
+        assert_eq!(locked(4), 500);
+        // Unbonded amount counts towards the free balance needed for bonding
+        assert_err!(
+            Staking::bond_extra(Origin::signed(4), 501),
+            Error::<TestRuntime>::InsufficientFreeBalance
+        );
+
+        // Unbonding the rest ends the candidacy and moves the withdrawal epoch ahead
+        set_epoch(4);
+        assert_ok!(Staking::unbond(Origin::signed(4), 300));
+        assert_eq!(Staking::candidate_bond(4), None);
+        assert_eq!(Staking::candidate_count(), 0);
+        assert_eq!(Staking::unbonding(4), Some((500, 6)));
+        assert_eq!(locked(4), 500);
+
+        set_epoch(6);
+        assert_err!(
+            Staking::withdraw_unbonded(Origin::signed(4)),
+            Error::<TestRuntime>::UnbondingNotOver
+        );
+        set_epoch(7);
+        assert_ok!(Staking::withdraw_unbonded(Origin::signed(4)));
+        assert_eq!(Staking::unbonding(4), None);
+        assert_eq!(locked(4), 0);
+        assert_err!(
+            Staking::withdraw_unbonded(Origin::signed(4)),
+            Error::<TestRuntime>::NothingToWithdraw
+        );
+    });
+}
+
+#[test]
+fn election_by_bond() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Staking::declare_candidacy(
+            Origin::signed(4),
+            300,
+            UintAuthorityId(4)
+        ));
+        assert_ok!(Staking::declare_candidacy(
+            Origin::signed(5),
+            500,
+            UintAuthorityId(5)
+        ));
+        assert_ok!(Staking::declare_candidacy(
+            Origin::signed(6),
+            300,
+            UintAuthorityId(6)
+        ));
+        assert_ok!(Staking::declare_candidacy(
+            Origin::signed(2),
+            100,
+            UintAuthorityId(2)
+        ));
+        assert_err!(
+            Staking::declare_candidacy(Origin::signed(1), 100, UintAuthorityId(1)),
+            Error::<TestRuntime>::TooManyCandidates
+        );
+
+        // Ties are broken by account id
+        assert_eq!(Staking::elect(2), vec![5, 4]);
+        assert_eq!(Staking::elect(3), vec![5, 4, 6]);
+        assert_eq!(Staking::elect(10), vec![5, 4, 6, 2]);
+        assert_ok!(Staking::bond_extra(Origin::signed(6), 1));
+        assert_eq!(Staking::elect(2), vec![5, 6]);
+
+        // Candidates without session keys are skipped
+        assert_ok!(Session::purge_keys(Origin::signed(5)));
+        assert_eq!(Staking::elect(2), vec![6, 4]);
+    });
+}
+
+#[test]
+fn switch_to_pos() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoAModule::add_validator(RawOrigin::Root.into(), 5, false));
+        assert_eq!(PoAModule::validators_to_add(), vec![5]);
+
+        assert!(Staking::switch_to_pos(Origin::signed(1)).is_err());
+        assert_ok!(Staking::switch_to_pos(RawOrigin::Root.into()));
+        assert_err!(
+            Staking::switch_to_pos(RawOrigin::Root.into()),
+            Error::<TestRuntime>::AlreadySwitched
+        );
+
+        // Validators that could afford the minimum bond became candidates, the active ones are unchanged
+        assert!(PoAModule::validators_elected_externally());
+        assert_eq!(Staking::candidate_bond(1), Some(100));
+        assert_eq!(Staking::candidate_bond(2), Some(100));
+        assert_eq!(Staking::candidate_bond(3), None);
+        assert_eq!(Staking::candidate_count(), 2);
+        assert_eq!(locked(1), 100);
+        assert_eq!(PoAModule::active_validators(), vec![1, 2, 3]);
+        assert!(PoAModule::validators_to_add().is_empty());
+
+        // Root can no longer change the validators
+        assert_err!(
+            PoAModule::add_validator(RawOrigin::Root.into(), 4, false),
+            poa::Error::<TestRuntime>::ValidatorsElectedExternally
+        );
+        assert_err!(
+            PoAModule::remove_validator(RawOrigin::Root.into(), 1, false),
+            poa::Error::<TestRuntime>::ValidatorsElectedExternally
+        );
+        assert_err!(
+            PoAModule::swap_validator(RawOrigin::Root.into(), 1, 4),
+            poa::Error::<TestRuntime>::ValidatorsElectedExternally
+        );
+
+        assert_ok!(Staking::declare_candidacy(
+            Origin::signed(4),
+            400,
+            UintAuthorityId(4)
+        ));
+        assert_ok!(Staking::bond_extra(Origin::signed(2), 50));
+        assert_ok!(Session::set_keys(
+            Origin::signed(2),
+            UintAuthorityId(2),
+            vec![]
+        ));
+
+        // Migrated validator 1 has no session keys so it cannot be elected
+        assert_eq!(Staking::elect(3), vec![4, 2]);
+
+        // Top 2 by bond become active
+        let elected = Staking::elect(PoAModule::max_active_validators());
+        assert_eq!(elected, vec![4, 2]);
+        assert_eq!(PoAModule::set_elected_validators(elected), 2);
+        assert_eq!(PoAModule::active_validators(), vec![4, 2]);
+
+        // Same set in a different order is no change
+        assert_eq!(PoAModule::set_elected_validators(vec![2, 4]), 2);
+        assert_eq!(PoAModule::active_validators(), vec![4, 2]);
+
+        // Empty election keeps the current validators
+        assert_eq!(PoAModule::set_elected_validators(vec![]), 2);
+        assert_eq!(PoAModule::active_validators(), vec![4, 2]);
+    });
+}
+
+#[test]
+fn offline_validators_chilled() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Staking::declare_candidacy(
+            Origin::signed(4),
+            300,
+            UintAuthorityId(4)
+        ));
+        assert_ok!(Staking::declare_candidacy(
+            Origin::signed(5),
+            500,
+            UintAuthorityId(5)
+        ));
+        assert_eq!(Staking::elect(2), vec![5, 4]);
+
+        // Validator 5 was found offline, 3 is not a candidate
+        set_epoch(3);
+        put_storage_value(b"PoAModule", b"RemoveValidators", &[], vec![5u64, 3]);
+        Staking::chill_offline_validators();
+        assert_eq!(Staking::candidate_bond(5), None);
+        assert_eq!(Staking::candidate_count(), 1);
+        assert_eq!(Staking::unbonding(5), Some((500, 5)));
+        assert_eq!(locked(5), 500);
+        assert_eq!(Staking::elect(2), vec![4]);
+
+        // It has to declare candidacy again to be elected
+        assert_ok!(Staking::declare_candidacy(
+            Origin::signed(5),
+            100,
+            UintAuthorityId(5)
+        ));
+        assert_eq!(Staking::elect(2), vec![4, 5]);
+    });
+}
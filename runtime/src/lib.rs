@@ -27,6 +27,7 @@ pub use core_mods::blob;
 pub use core_mods::did;
 pub use core_mods::master;
 pub use core_mods::revoke;
+pub mod precompiles;
 pub mod weight_to_fee;
 
 pub use poa;
@@ -80,7 +81,7 @@ use pallet_evm::{
     HashedAddressMapping, Runner,
 };
 
-use crate::weight_to_fee::TxnFee;
+use crate::weight_to_fee::{charged_fee, usd_fee_of_call, TxnFee, UsdFeeAdapter, UsdFeeConfig};
 use sp_std::{marker::PhantomData, prelude::*};
 #[cfg(feature = "std")]
 use sp_version::NativeVersion;
@@ -109,6 +110,9 @@ pub type Index = u32;
 /// A hash of some data used by the chain.
 pub type Hash = H256;
 
+/// A timestamp: milliseconds since the unix epoch.
+pub type Moment = u64;
+
 /// The token has 6 decimal places
 pub const DOCK: Balance = 1_000_000;
 
@@ -141,7 +145,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("dock-main-runtime"),
     impl_name: create_runtime_str!("dock-main-runtime"),
     authoring_version: 1,
-    spec_version: 19,
+    spec_version: 20,
     impl_version: 1,
     transaction_version: 1,
     apis: RUNTIME_API_VERSIONS,
@@ -288,8 +292,7 @@ parameter_types! {
 }
 
 impl timestamp::Config for Runtime {
-    /// A timestamp: milliseconds since the unix epoch.
-    type Moment = u64;
+    type Moment = Moment;
     type OnTimestampSet = Aura;
     type MinimumPeriod = MinimumPeriod;
     type WeightInfo = ();
@@ -317,9 +320,66 @@ parameter_types! {
     pub const TransactionByteFee: Balance = DOCK / 100;
 }
 
+parameter_types! {
+    /// Fee of DID writes, 0.05 USD
+    pub const DidUsdFee: u64 = 50_000;
+    /// Fee of revocations and unrevocations, 0.02 USD
+    pub const RevokeUsdFee: u64 = 20_000;
+    /// Fee of blob writes, 0.05 USD
+    pub const BlobUsdFee: u64 = 50_000;
+    /// Fee of anchors, 0.01 USD
+    pub const AnchorUsdFee: u64 = 10_000;
+    /// .01 token
+    pub const MinUsdTxnFee: Balance = DOCK / 100;
+    /// 100 tokens
+    pub const MaxUsdTxnFee: Balance = 100 * DOCK;
+}
+
+/// DID, revoke, blob and anchor extrinsics are charged a fee in USD, converted using the DOCK/USD price of the price feed.
+/// The weight and length based fee is charged instead when it is higher. A batch is charged the sum of the USD fees
+/// of its calls, plus the weight based fee if it has other calls as well.
+pub struct DockUsdFees;
+
+impl UsdFeeConfig<Call> for DockUsdFees {
+    type PriceProvider = PriceFeedModule;
+    type MinFee = MinUsdTxnFee;
+    type MaxFee = MaxUsdTxnFee;
+
+    fn usd_fee(call: &Call) -> Option<u64> {
+        match call {
+            Call::DIDModule(_) => Some(DidUsdFee::get()),
+            Call::Revoke(_) => Some(RevokeUsdFee::get()),
+            Call::BlobStore(_) => Some(BlobUsdFee::get()),
+            Call::Anchor(_) => Some(AnchorUsdFee::get()),
+            Call::Utility(pallet_utility::Call::batch(calls))
+            | Call::Utility(pallet_utility::Call::batch_all(calls)) => calls
+                .iter()
+                .filter_map(Self::usd_fee)
+                .fold(None, |total, fee| {
+                    Some(total.unwrap_or(0u64).saturating_add(fee))
+                }),
+            Call::Utility(pallet_utility::Call::as_derivative(_, call)) => Self::usd_fee(call),
+            _ => None,
+        }
+    }
+
+    fn charges_weight_fee(call: &Call) -> bool {
+        match call {
+            Call::Utility(pallet_utility::Call::batch(calls))
+            | Call::Utility(pallet_utility::Call::batch_all(calls)) => calls
+                .iter()
+                .any(|call| Self::usd_fee(call).is_none() || Self::charges_weight_fee(call)),
+            Call::Utility(pallet_utility::Call::as_derivative(_, call)) => {
+                Self::charges_weight_fee(call)
+            }
+            _ => false,
+        }
+    }
+}
+
 impl transaction_payment::Config for Runtime {
-    /// Transaction fees is handled by PoA module
-    type OnChargeTransaction = CurrencyAdapter<Balances, PoAModule>;
+    /// Transaction fees is handled by PoA module. DID, revoke, blob and anchor extrinsics are charged in USD
+    type OnChargeTransaction = UsdFeeAdapter<CurrencyAdapter<Balances, PoAModule>, DockUsdFees>;
     type TransactionByteFee = TransactionByteFee;
     type WeightToFee = TxnFee<Balance>;
     type FeeMultiplierUpdate = ();
@@ -345,9 +405,9 @@ impl pallet_session::Config for Runtime {
     type Event = Event;
     type ValidatorId = <Self as system::Config>::AccountId;
     type ValidatorIdOf = ConvertInto;
-    type ShouldEndSession = PoAModule;
+    type ShouldEndSession = Staking;
     type NextSessionRotation = ();
-    type SessionManager = PoAModule;
+    type SessionManager = Staking;
     type SessionHandler = <opaque::SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
     type Keys = opaque::SessionKeys;
     type DisabledValidatorsThreshold = ();
@@ -359,6 +419,21 @@ impl poa::Trait for Runtime {
     type Currency = balances::Module<Runtime>;
 }
 
+parameter_types! {
+    /// 10000 tokens
+    pub const MinCandidateBond: Balance = 10_000 * DOCK;
+    pub const MaxCandidates: u32 = 100;
+    pub const UnbondingEpochs: poa::EpochNo = 28;
+}
+
+impl staking::Trait for Runtime {
+    type Event = Event;
+    type Currency = balances::Module<Runtime>;
+    type MinCandidateBond = MinCandidateBond;
+    type MaxCandidates = MaxCandidates;
+    type UnbondingEpochs = UnbondingEpochs;
+}
+
 parameter_types! {
     /// Number of vesting milestones
     pub const VestingMilestones: u8 = 3;
@@ -397,9 +472,33 @@ impl pallet_utility::Config for Runtime {
     type WeightInfo = ();
 }
 
+parameter_types! {
+    pub const MasterProposalLifetime: BlockNumber = 7 * DAYS;
+}
+
 impl master::Trait for Runtime {
     type Event = Event;
     type Call = Call;
+    type ProposalLifetime = MasterProposalLifetime;
+    type Scheduler = Scheduler;
+    type PalletsOrigin = OriginCaller;
+    type WrappedCalls = UtilityWrappedCalls;
+}
+
+/// Calls wrapped by utility calls, so that Master's timelocks apply to the calls of a batch as well
+pub struct UtilityWrappedCalls;
+
+impl master::WrappedCalls<Call> for UtilityWrappedCalls {
+    fn wrapped_calls(call: &Call) -> Vec<&Call> {
+        match call {
+            Call::Utility(pallet_utility::Call::batch(calls))
+            | Call::Utility(pallet_utility::Call::batch_all(calls)) => calls.iter().collect(),
+            Call::Utility(pallet_utility::Call::as_derivative(_, call)) => {
+                sp_std::iter::once(&**call).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
 }
 
 impl sudo::Config for Runtime {
@@ -411,8 +510,20 @@ impl anchor::Trait for Runtime {
     type Event = Event;
 }
 
+parameter_types! {
+    pub const MaxAttestationHistory: u32 = 16;
+    pub const MaxIriSize: u32 = 1024;
+    pub const MaxDataIriSize: u32 = 8192;
+    pub const AllowedIriSchemes: &'static [&'static [u8]] = &[b"ipfs", b"https", b"data", b"did"];
+}
+
 impl attest::Trait for Runtime {
+    type Event = Event;
     type StorageWeight = StorageWeight;
+    type MaxAttestationHistory = MaxAttestationHistory;
+    type MaxIriSize = MaxIriSize;
+    type MaxDataIriSize = MaxDataIriSize;
+    type AllowedIriSchemes = AllowedIriSchemes;
 }
 
 /// This origin indicates that either >50% (simple majority) of Council members approved some dispatch (through a proposal)
@@ -617,7 +728,7 @@ impl pallet_evm::Config for Runtime {
     type Currency = Balances;
     type Event = Event;
     type Runner = pallet_evm::runner::stack::Runner<Self>;
-    type Precompiles = ();
+    type Precompiles = precompiles::DockPrecompiles<Self>;
     type ChainId = DockChainId;
     /// Deducted fee will be handled by the PoA module
     type OnChargeTransaction = EVMCurrencyAdapter<Balances, PoAModule>;
@@ -632,8 +743,13 @@ impl pallet_evm::Config for Runtime {
     }
 }
 
+parameter_types! {
+    pub const MaxPriceHistory: u32 = 100;
+}
+
 impl price_feed::Config for Runtime {
     type Event = Event;
+    type MaxPriceHistory = MaxPriceHistory;
 }
 
 pub struct BaseFilter;
@@ -680,10 +796,11 @@ construct_runtime!(
         TechnicalCommittee: pallet_collective::<Instance2>::{Module, Call, Storage, Origin<T>, Event<T>, Config<T>},
         TechnicalCommitteeMembership: pallet_membership::<Instance2>::{Module, Call, Storage, Event<T>, Config<T>},
         Scheduler: pallet_scheduler::{Module, Call, Storage, Event<T>},
-        Attest: attest::{Module, Call, Storage},
+        Attest: attest::{Module, Call, Storage, Event},
         Ethereum: pallet_ethereum::{Module, Call, Storage, Event, Config, ValidateUnsigned},
         EVM: pallet_evm::{Module, Config, Call, Storage, Event<T>},
         PriceFeedModule: price_feed::{Module, Call, Storage, Event, Config},
+        Staking: staking::{Module, Call, Storage, Event<T>},
     }
 );
 
@@ -970,9 +1087,15 @@ impl_runtime_apis! {
             uxt: <Block as BlockT>::Extrinsic,
             len: u32,
         ) -> pallet_transaction_payment_rpc_runtime_api::RuntimeDispatchInfo<Balance> {
-            TransactionPayment::query_info(uxt, len)
+            // Report the fee that is charged, which is in USD for some calls
+            let usd_fee = usd_fee_of_call::<Call, DockUsdFees>(&uxt.function);
+            let mut info = TransactionPayment::query_info(uxt, len);
+            info.partial_fee = charged_fee(usd_fee, info.partial_fee, 0);
+            info
         }
 
+        // The details break down the weight and length based fee, calls charged in USD are charged differently as
+        // reported by `query_info`
         fn query_fee_details(uxt: <Block as BlockT>::Extrinsic, len: u32) -> pallet_transaction_payment_rpc_runtime_api::FeeDetails<Balance> {
             TransactionPayment::query_fee_details(uxt, len)
         }
@@ -990,15 +1113,123 @@ impl_runtime_apis! {
         fn get_total_emission_in_epoch(epoch_no: poa::EpochNo) -> Balance {
             PoAModule::get_total_emission_in_epoch(epoch_no)
         }
+
+        fn get_current_epoch() -> poa::EpochNo {
+            PoAModule::epoch()
+        }
+
+        fn get_epoch_ends_at() -> poa::SlotNo {
+            PoAModule::epoch_ends_at()
+        }
+
+        fn get_epoch_detail(epoch_no: poa::EpochNo) -> Option<poa::EpochDetail<Balance>> {
+            PoAModule::epoch_detail(epoch_no)
+        }
+
+        fn get_epoch_details_in_range(
+            from: poa::EpochNo,
+            to: poa::EpochNo,
+        ) -> Vec<(poa::EpochNo, poa::EpochDetail<Balance>)> {
+            PoAModule::epoch_details_in_range(from, to)
+        }
+
+        fn get_validator_stats(
+            epoch_no: poa::EpochNo,
+            validator: AccountId,
+        ) -> Option<poa::ValidatorStatsPerEpoch<Balance>> {
+            PoAModule::validator_stats(epoch_no, &validator)
+        }
+
+        fn get_validator_stats_in_range(
+            validator: AccountId,
+            from: poa::EpochNo,
+            to: poa::EpochNo,
+        ) -> Vec<(poa::EpochNo, poa::ValidatorStatsPerEpoch<Balance>)> {
+            PoAModule::validator_stats_in_range(&validator, from, to)
+        }
+
+        fn get_queued_validators() -> Vec<AccountId> {
+            PoAModule::validators_to_add()
+        }
+
+        fn get_validators_to_remove() -> Vec<AccountId> {
+            PoAModule::validators_to_remove()
+        }
+
+        fn get_hot_swap() -> Option<(AccountId, AccountId)> {
+            PoAModule::hot_swap()
+        }
     }
 
     impl price_feed::runtime_api::PriceFeedApi<Block> for Runtime {
         fn token_usd_price() -> Option<u32> {
-            PriceFeedModule::price()
+            PriceFeedModule::price(price_feed::CurrencySymbolPair::dock_usd())
+                .and_then(price_feed::to_legacy_price)
         }
 
         fn token_usd_price_from_contract() -> Option<u32> {
-            PriceFeedModule::get_price_from_contract().map_or(None, |(v, _)| Some(v))
+            PriceFeedModule::get_price_from_contract(&price_feed::CurrencySymbolPair::dock_usd())
+                .map_or(None, |(v, _)| price_feed::to_legacy_price(v))
+        }
+
+        fn price(pair: price_feed::CurrencySymbolPair) -> Option<price_feed::FixedPrice> {
+            PriceFeedModule::price(pair)
+        }
+
+        fn price_from_contract(pair: price_feed::CurrencySymbolPair) -> Option<price_feed::FixedPrice> {
+            PriceFeedModule::get_price_from_contract(&pair).map_or(None, |(v, _)| Some(v))
+        }
+
+        fn twap_price(pair: price_feed::CurrencySymbolPair) -> Option<price_feed::FixedPrice> {
+            PriceFeedModule::twap_price(&pair)
+        }
+
+        fn median_price(pair: price_feed::CurrencySymbolPair) -> Option<price_feed::FixedPrice> {
+            PriceFeedModule::median_price(&pair)
+        }
+    }
+
+    impl core_mods::runtime_api::AnchorApi<Block, Hash, BlockNumber, Moment> for Runtime {
+        fn anchor_lookup(
+            query: anchor::AnchorQuery<Hash>,
+        ) -> Option<(BlockNumber, Option<did::Did>)> {
+            Anchor::lookup(query)
+        }
+
+        fn timestamp() -> Moment {
+            Timestamp::now()
+        }
+    }
+
+    impl core_mods::runtime_api::AttestApi<Block, BlockNumber> for Runtime {
+        fn attestation(did: did::Did, topic: attest::Topic) -> attest::Attestation {
+            Attest::attestation(&did, &topic)
+        }
+
+        fn attestation_history(
+            did: did::Did,
+            topic: attest::Topic,
+        ) -> Vec<attest::AttestationRecord<BlockNumber>> {
+            Attest::attestation_history(did, topic)
+        }
+    }
+
+    impl core_mods::runtime_api::MasterApi<Block, Event> for Runtime {
+        fn dry_run(proposal: Vec<u8>) -> Option<master::DryRun<Event>> {
+            Master::dry_run(proposal)
+        }
+    }
+
+    impl core_mods::runtime_api::MasterVoteApi<Block> for Runtime {
+        fn vote_payload(proposal: Vec<u8>) -> Result<Vec<u8>, sp_runtime::DispatchError> {
+            Master::vote_payload(proposal)
+        }
+
+        fn verify_auth(
+            proposal: Vec<u8>,
+            auth: master::PMAuth,
+        ) -> Result<u64, sp_runtime::DispatchError> {
+            Master::verify_auth(proposal, &auth)
         }
     }
 
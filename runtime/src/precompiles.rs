@@ -0,0 +1,577 @@
+//! Precompiled contracts available to contracts running on the EVM.
+//!
+//! The first five standard precompiles of Ethereum live at their usual addresses: `ecrecover` at
+//! `0x01`, `sha256` at `0x02`, `ripemd160` at `0x03`, `identity` at `0x04` and `modexp` at `0x05`.
+//! Ported Solidity code relies on them. The bn128 and blake2f precompiles of the Istanbul release
+//! (`0x06` to `0x09`) are not available.
+//!
+//! Dock's precompiles make Dock's pallets available to contracts. They live at fixed addresses
+//! starting from `0x0400` (1024). Their input and output are raw bytes rather than Solidity ABI
+//! encoded values:
+//!
+//! - `0x0400` DID resolution. Input is the 32 byte DID. Output is the SCALE encoding of the DID's
+//!   current `PublicKey`, i.e. 1 byte for the key type (0 for Sr25519, 1 for Ed25519 and 2 for
+//!   Secp256k1) followed by the key. Fails if the DID does not exist.
+//! - `0x0401` DID signature verification. Input is the 32 byte DID, followed by the SCALE encoded
+//!   `DidSignature` and the signed message. Output is a 32 byte word, 1 if the signature is valid
+//!   and 0 otherwise. Fails if the DID does not exist.
+//! - `0x0402` DID existence. Input is the 32 byte DID. Output is a 32 byte word, 1 if the DID
+//!   exists and 0 otherwise.
+//! - `0x0403` Revocation status. Input is the 32 byte registry id followed by the 32 byte revoke
+//!   id. Output is a 32 byte word, 1 if the id is revoked in the registry and 0 otherwise.
+//! - `0x0404` Anchor lookup. Input is the 32 byte hash of the anchored data. Output is two 32 byte
+//!   words, the first 1 if the anchor exists and 0 otherwise, the second the number of the block
+//!   the anchor was posted in as a big-endian word, 0 if there is no such anchor.
+//!
+//! The gas cost of a precompile is the weight of its storage reads and signature verification
+//! converted by the EVM's `GasWeightMapping`, but never less than the EVM charges for similar
+//! work: `STORAGE_READ_GAS` per storage read, like a cold `SLOAD`, and `VERIFY_SIG_GAS` per
+//! signature. Verifying a signature also costs `VERIFY_SIG_GAS_PER_BYTE` per byte of the message.
+
+use codec::{Decode, Encode};
+use core_mods::{
+    anchor::{self, AnchorQuery},
+    did::{self, Did, DidSignature},
+    revoke::{self, RegistryId, RevokeId},
+};
+use evm::{Context, ExitError, ExitSucceed};
+use frame_support::weights::Weight;
+use pallet_evm::{GasWeightMapping, Precompile, PrecompileSet};
+use pallet_evm_precompile_modexp::Modexp;
+use pallet_evm_precompile_simple::{ECRecover, Identity, Ripemd160, Sha256};
+use sp_core::{H160, U256};
+use sp_runtime::traits::UniqueSaturatedInto;
+use sp_std::{marker::PhantomData, prelude::*};
+
+/// Address of the DID resolution precompile.
+pub const DID_RESOLVE: u64 = 0x0400;
+/// Address of the DID signature verification precompile.
+pub const DID_VERIFY_SIG: u64 = 0x0401;
+/// Address of the DID existence precompile.
+pub const DID_EXISTS: u64 = 0x0402;
+/// Address of the revocation status precompile.
+pub const REVOCATION_STATUS: u64 = 0x0403;
+/// Address of the anchor lookup precompile.
+pub const ANCHOR_LOOKUP: u64 = 0x0404;
+
+/// Least gas charged per storage read, the cost of a cold `SLOAD` (EIP-2929).
+pub const STORAGE_READ_GAS: u64 = 2100;
+/// Least gas charged for verifying a signature, the cost of the `ecrecover` precompile.
+pub const VERIFY_SIG_GAS: u64 = 3000;
+/// Gas charged per byte of the message whose signature is verified, for hashing it.
+pub const VERIFY_SIG_GAS_PER_BYTE: u64 = 3;
+
+type PrecompileResult = Result<(ExitSucceed, Vec<u8>, u64), ExitError>;
+
+/// The standard Ethereum precompiles at `0x01` to `0x05`. The tuple's implementation of
+/// `PrecompileSet` puts the nth precompile at address n.
+pub type StandardPrecompiles = (ECRecover, Sha256, Ripemd160, Identity, Modexp);
+
+/// The precompiles available to the EVM of the runtime `R`.
+pub struct DockPrecompiles<R>(PhantomData<R>);
+
+impl<R> PrecompileSet for DockPrecompiles<R>
+where
+    R: pallet_evm::Config + did::Trait + revoke::Trait + anchor::Trait,
+{
+    fn execute(
+        address: H160,
+        input: &[u8],
+        target_gas: Option<u64>,
+        context: &Context,
+    ) -> Option<PrecompileResult> {
+        if let Some(result) = StandardPrecompiles::execute(address, input, target_gas, context) {
+            Some(result)
+        } else if address == H160::from_low_u64_be(DID_RESOLVE) {
+            Some(DidResolve::<R>::execute(input, target_gas, context))
+        } else if address == H160::from_low_u64_be(DID_VERIFY_SIG) {
+            Some(DidVerifySig::<R>::execute(input, target_gas, context))
+        } else if address == H160::from_low_u64_be(DID_EXISTS) {
+            Some(DidExists::<R>::execute(input, target_gas, context))
+        } else if address == H160::from_low_u64_be(REVOCATION_STATUS) {
+            Some(RevocationStatus::<R>::execute(input, target_gas, context))
+        } else if address == H160::from_low_u64_be(ANCHOR_LOOKUP) {
+            Some(AnchorLookup::<R>::execute(input, target_gas, context))
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the current public key of a DID.
+pub struct DidResolve<R>(PhantomData<R>);
+
+impl<R> Precompile for DidResolve<R>
+where
+    R: pallet_evm::Config + did::Trait,
+{
+    fn execute(input: &[u8], target_gas: Option<u64>, _context: &Context) -> PrecompileResult {
+        let cost = charge(reads::<R>(1), target_gas)?;
+        let did = read_did(input)?;
+        let (detail, _) = did::Module::<R>::get_key_detail(&did)
+            .map_err(|_| ExitError::Other("DID does not exist".into()))?;
+        Ok((ExitSucceed::Returned, detail.public_key().encode(), cost))
+    }
+}
+
+/// Checks a signature over a message against the current public key of a DID.
+pub struct DidVerifySig<R>(PhantomData<R>);
+
+impl<R> Precompile for DidVerifySig<R>
+where
+    R: pallet_evm::Config + did::Trait,
+{
+    fn execute(input: &[u8], target_gas: Option<u64>, _context: &Context) -> PrecompileResult {
+        let did = read_did(input)?;
+        let mut rest = &input[32..];
+        let signature = DidSignature::decode(&mut rest)
+            .map_err(|_| ExitError::Other("Invalid DID signature".into()))?;
+        let cost = charge(
+            reads::<R>(1).saturating_add(verify_sig::<R>(&signature, rest.len())),
+            target_gas,
+        )?;
+        let valid =
+            did::Module::<R>::verify_sig_from_did(&signature, rest, &did).map_err(|_| {
+                ExitError::Other("DID does not exist or signature type mismatch".into())
+            })?;
+        Ok((ExitSucceed::Returned, bool_word(valid), cost))
+    }
+}
+
+/// Tells whether a DID exists.
+pub struct DidExists<R>(PhantomData<R>);
+
+impl<R> Precompile for DidExists<R>
+where
+    R: pallet_evm::Config + did::Trait,
+{
+    fn execute(input: &[u8], target_gas: Option<u64>, _context: &Context) -> PrecompileResult {
+        let cost = charge(reads::<R>(1), target_gas)?;
+        let did = read_did(input)?;
+        let exists = did::Dids::<R>::contains_key(&did);
+        Ok((ExitSucceed::Returned, bool_word(exists), cost))
+    }
+}
+
+/// Tells whether an id is revoked in a revocation registry.
+pub struct RevocationStatus<R>(PhantomData<R>);
+
+impl<R> Precompile for RevocationStatus<R>
+where
+    R: pallet_evm::Config + revoke::Trait,
+{
+    fn execute(input: &[u8], target_gas: Option<u64>, _context: &Context) -> PrecompileResult {
+        let cost = charge(reads::<R>(1), target_gas)?;
+        let (registry_id, revoke_id) =
+            <(RegistryId, RevokeId)>::decode(&mut &input[..]).map_err(|_| {
+                ExitError::Other("Input must be a 32 byte registry id and 32 byte revoke id".into())
+            })?;
+        let revoked = revoke::Module::<R>::get_revocation_status(registry_id, revoke_id).is_some();
+        Ok((ExitSucceed::Returned, bool_word(revoked), cost))
+    }
+}
+
+/// Tells whether an anchor exists and returns the block in which it was posted.
+pub struct AnchorLookup<R>(PhantomData<R>);
+
+impl<R> Precompile for AnchorLookup<R>
+where
+    R: pallet_evm::Config + anchor::Trait,
+{
+    fn execute(input: &[u8], target_gas: Option<u64>, _context: &Context) -> PrecompileResult {
+        // The anchor and its author are read
+        let cost = charge(reads::<R>(2), target_gas)?;
+        let hash = <R as frame_system::Config>::Hash::decode(&mut &input[..])
+            .map_err(|_| ExitError::Other("Input must be the hash of an anchor".into()))?;
+        let found = anchor::Module::<R>::lookup(AnchorQuery::Hash(hash));
+        let block: u64 = found
+            .as_ref()
+            .map(|(block, _)| (*block).unique_saturated_into())
+            .unwrap_or(0);
+        let mut output = bool_word(found.is_some());
+        let mut word = [0u8; 32];
+        U256::from(block).to_big_endian(&mut word);
+        output.extend_from_slice(&word);
+        Ok((ExitSucceed::Returned, output, cost))
+    }
+}
+
+/// Convert `weight` to gas, charging at least `floor`.
+fn gas_for<R: pallet_evm::Config>(weight: Weight, floor: u64) -> u64 {
+    R::GasWeightMapping::weight_to_gas(weight).max(floor)
+}
+
+/// Gas of `n` storage reads in the runtime `R`.
+fn reads<R: pallet_evm::Config>(n: u64) -> u64 {
+    use frame_support::traits::Get;
+    gas_for::<R>(
+        <R as frame_system::Config>::DbWeight::get().reads(n),
+        n.saturating_mul(STORAGE_READ_GAS),
+    )
+}
+
+/// Gas of verifying `signature` over a message of `message_len` bytes in the runtime `R`.
+fn verify_sig<R: pallet_evm::Config>(signature: &DidSignature, message_len: usize) -> u64 {
+    gas_for::<R>(signature.weight(), VERIFY_SIG_GAS)
+        .saturating_add((message_len as u64).saturating_mul(VERIFY_SIG_GAS_PER_BYTE))
+}
+
+/// Charge `cost` gas, failing if it exceeds `target_gas`.
+fn charge(cost: u64, target_gas: Option<u64>) -> Result<u64, ExitError> {
+    match target_gas {
+        Some(gas) if cost > gas => Err(ExitError::OutOfGas),
+        _ => Ok(cost),
+    }
+}
+
+/// Read the DID from the first 32 bytes of the input.
+fn read_did(input: &[u8]) -> Result<Did, ExitError> {
+    if input.len() < 32 {
+        return Err(ExitError::Other(
+            "Input must start with a 32 byte DID".into(),
+        ));
+    }
+    let mut did = Did::default();
+    did.copy_from_slice(&input[..32]);
+    Ok(did)
+}
+
+/// A bool as a 32 byte big-endian word, like Solidity's ABI.
+fn bool_word(value: bool) -> Vec<u8> {
+    let mut word = vec![0u8; 32];
+    word[31] = value as u8;
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AccountId, Origin, Runtime};
+    use core_mods::did::{Bytes32, Bytes64, KeyDetail, PublicKey};
+    use core_mods::revoke::{Policy, Registry, Revoke};
+    use core_mods::StateChange;
+    use evm::{
+        backend::{MemoryBackend, MemoryVicinity},
+        executor::StackExecutor,
+        CreateScheme, ExitReason,
+    };
+    use sp_core::{ecdsa, H256};
+    use sp_core::{sr25519, Pair};
+    use sp_runtime::traits::{BlakeTwo256, Hash};
+    use sp_std::{collections::btree_map::BTreeMap, convert::TryFrom};
+
+    type Precompiles = DockPrecompiles<Runtime>;
+
+    fn ext() -> sp_io::TestExternalities {
+        frame_system::GenesisConfig::default()
+            .build_storage::<Runtime>()
+            .unwrap()
+            .into()
+    }
+
+    fn context() -> Context {
+        Context {
+            address: Default::default(),
+            caller: Default::default(),
+            apparent_value: Default::default(),
+        }
+    }
+
+    fn call(address: u64, input: &[u8]) -> Option<PrecompileResult> {
+        Precompiles::execute(H160::from_low_u64_be(address), input, None, &context())
+    }
+
+    /// Register `did` with a new Sr25519 key and return the key.
+    fn new_did(did: Did) -> sr25519::Pair {
+        let kp = sr25519::Pair::generate().0;
+        did::Module::<Runtime>::new(
+            Origin::signed(AccountId::from([0; 32])),
+            did,
+            KeyDetail::new(
+                did,
+                PublicKey::Sr25519(Bytes32 {
+                    value: kp.public().0,
+                }),
+            ),
+        )
+        .unwrap();
+        kp
+    }
+
+    /// Gas follows the runtime's DB weights and is never less than the EVM's cost.
+    #[test]
+    fn gas_tracks_db_weights() {
+        use frame_support::traits::Get;
+        let to_gas =
+            |weight| <Runtime as pallet_evm::Config>::GasWeightMapping::weight_to_gas(weight);
+        let read = <Runtime as frame_system::Config>::DbWeight::get().reads(1);
+        assert_eq!(reads::<Runtime>(1), to_gas(read).max(STORAGE_READ_GAS));
+        assert_eq!(
+            reads::<Runtime>(3),
+            to_gas(3 * read).max(3 * STORAGE_READ_GAS)
+        );
+        assert_eq!(gas_for::<Runtime>(read, 0), to_gas(read));
+        assert_eq!(gas_for::<Runtime>(0, STORAGE_READ_GAS), STORAGE_READ_GAS);
+
+        let signature = DidSignature::Sr25519(Bytes64 { value: [0; 64] });
+        assert_eq!(
+            verify_sig::<Runtime>(&signature, 0),
+            to_gas(signature.weight()).max(VERIFY_SIG_GAS)
+        );
+        assert_eq!(
+            verify_sig::<Runtime>(&signature, 100),
+            verify_sig::<Runtime>(&signature, 0) + 100 * VERIFY_SIG_GAS_PER_BYTE
+        );
+    }
+
+    #[test]
+    fn did_precompiles() {
+        ext().execute_with(|| {
+            let did = [1; 32];
+            let unknown = [2; 32];
+            let kp = new_did(did);
+
+            let (_, output, cost) = call(DID_EXISTS, &did).unwrap().unwrap();
+            assert_eq!(output, bool_word(true));
+            assert_eq!(cost, reads::<Runtime>(1));
+            let (_, output, _) = call(DID_EXISTS, &unknown).unwrap().unwrap();
+            assert_eq!(output, bool_word(false));
+
+            let (_, output, _) = call(DID_RESOLVE, &did).unwrap().unwrap();
+            let mut expected = vec![0];
+            expected.extend_from_slice(&kp.public().0);
+            assert_eq!(output, expected);
+            assert!(call(DID_RESOLVE, &unknown).unwrap().is_err());
+
+            let message = b"credential".to_vec();
+            let signature = DidSignature::Sr25519(Bytes64 {
+                value: kp.sign(&message).0,
+            });
+            let mut input = did.to_vec();
+            input.extend(signature.encode());
+            input.extend(&message);
+            let (_, output, cost) = call(DID_VERIFY_SIG, &input).unwrap().unwrap();
+            assert_eq!(output, bool_word(true));
+            assert_eq!(
+                cost,
+                reads::<Runtime>(1) + verify_sig::<Runtime>(&signature, 10)
+            );
+            input.push(0);
+            let (_, output, cost) = call(DID_VERIFY_SIG, &input).unwrap().unwrap();
+            assert_eq!(output, bool_word(false));
+            // longer messages cost more
+            assert_eq!(
+                cost,
+                reads::<Runtime>(1)
+                    + verify_sig::<Runtime>(&signature, 10)
+                    + VERIFY_SIG_GAS_PER_BYTE
+            );
+
+            // malformed input and insufficient gas
+            assert!(call(DID_EXISTS, &did[..31]).unwrap().is_err());
+            assert!(call(DID_VERIFY_SIG, &did).unwrap().is_err());
+            assert_eq!(
+                Precompiles::execute(H160::from_low_u64_be(DID_EXISTS), &did, Some(1), &context()),
+                Some(Err(ExitError::OutOfGas))
+            );
+
+            // other addresses are not handled
+            assert!(call(DID_EXISTS + 0x100, &did).is_none());
+        });
+    }
+
+    #[test]
+    fn revocation_status() {
+        ext().execute_with(|| {
+            let did = [1; 32];
+            let kp = new_did(did);
+            let registry_id = [3; 32];
+            let (revoked, unrevoked) = ([4; 32], [5; 32]);
+            revoke::Module::<Runtime>::new_registry(
+                Origin::signed(AccountId::from([0; 32])),
+                registry_id,
+                Registry {
+                    policy: Policy::OneOf(vec![did].into_iter().collect()),
+                    add_only: false,
+                },
+            )
+            .unwrap();
+            let revoke = Revoke {
+                registry_id,
+                revoke_ids: vec![revoked].into_iter().collect(),
+                last_modified: frame_system::Module::<Runtime>::block_number(),
+            };
+            let signature = DidSignature::Sr25519(Bytes64 {
+                value: kp.sign(&StateChange::Revoke(revoke.clone()).encode()).0,
+            });
+            revoke::Module::<Runtime>::revoke(
+                Origin::signed(AccountId::from([0; 32])),
+                revoke,
+                vec![(did, signature)].into_iter().collect(),
+            )
+            .unwrap();
+
+            let input = [registry_id, revoked].concat();
+            let (_, output, cost) = call(REVOCATION_STATUS, &input).unwrap().unwrap();
+            assert_eq!(output, bool_word(true));
+            assert_eq!(cost, reads::<Runtime>(1));
+            let input = [registry_id, unrevoked].concat();
+            let (_, output, _) = call(REVOCATION_STATUS, &input).unwrap().unwrap();
+            assert_eq!(output, bool_word(false));
+            assert!(call(REVOCATION_STATUS, &registry_id).unwrap().is_err());
+        });
+    }
+
+    #[test]
+    fn anchor_lookup() {
+        ext().execute_with(|| {
+            frame_system::Module::<Runtime>::set_block_number(7);
+            let data = b"notarized".to_vec();
+            anchor::Module::<Runtime>::deploy(
+                Origin::signed(AccountId::from([0; 32])),
+                data.clone(),
+            )
+            .unwrap();
+
+            let hash = BlakeTwo256::hash(&data);
+            let (_, output, cost) = call(ANCHOR_LOOKUP, hash.as_bytes()).unwrap().unwrap();
+            let mut expected = bool_word(true);
+            expected.extend(word(&[7]));
+            assert_eq!(output, expected);
+            assert_eq!(cost, reads::<Runtime>(2));
+            let (_, output, _) = call(ANCHOR_LOOKUP, H256::zero().as_bytes())
+                .unwrap()
+                .unwrap();
+            // a missing anchor is told apart by the flag, not the block number
+            assert_eq!(output, vec![0u8; 64]);
+            assert!(call(ANCHOR_LOOKUP, &[0; 31]).unwrap().is_err());
+
+            // an anchor posted in block 0 is found
+            frame_system::Module::<Runtime>::set_block_number(0);
+            let data = b"genesis".to_vec();
+            anchor::Module::<Runtime>::deploy(
+                Origin::signed(AccountId::from([0; 32])),
+                data.clone(),
+            )
+            .unwrap();
+            let hash = BlakeTwo256::hash(&data);
+            let (_, output, _) = call(ANCHOR_LOOKUP, hash.as_bytes()).unwrap().unwrap();
+            let mut expected = bool_word(true);
+            expected.extend(word(&[0]));
+            assert_eq!(output, expected);
+        });
+    }
+
+    /// Runtime code of a contract that calls the precompile whose address is in the first 32 bytes
+    /// of the calldata with the rest of the calldata and returns the precompile's output.
+    const FORWARDER: &[u8] = &[
+        0x60, 0x20, 0x36, 0x03, // calldatasize - 32
+        0x60, 0x20, 0x60, 0x00, 0x37, // calldatacopy(0, 32, calldatasize - 32)
+        0x60, 0x00, 0x60, 0x00, // retSize, retOffset
+        0x60, 0x20, 0x36, 0x03, 0x60, 0x00, // argsSize, argsOffset
+        0x60, 0x00, 0x35, 0x5a, 0xfa, // staticcall(gas, calldataload(0), ...)
+        0x50, // pop
+        0x3d, 0x60, 0x00, 0x60, 0x00, 0x3e, // returndatacopy(0, 0, returndatasize)
+        0x3d, 0x60, 0x00, 0xf3, // return(0, returndatasize)
+    ];
+
+    /// Init code that returns the code following it, the forwarder, as the runtime code.
+    const DEPLOYER: &[u8] = &[
+        0x60, 0x23, 0x80, // len of the forwarder, twice
+        0x60, 0x0b, 0x60, 0x00, 0x39, // codecopy(0, 11, len)
+        0x60, 0x00, 0xf3, // return(0, len)
+    ];
+
+    /// Deploy the forwarder and call the precompile at `address` with `input` through it.
+    fn call_through_contract(address: u64, input: &[u8]) -> Vec<u8> {
+        let vicinity = MemoryVicinity {
+            gas_price: U256::zero(),
+            origin: H160::zero(),
+            chain_id: U256::one(),
+            block_hashes: Vec::new(),
+            block_number: U256::zero(),
+            block_coinbase: H160::zero(),
+            block_timestamp: U256::zero(),
+            block_difficulty: U256::zero(),
+            block_gas_limit: U256::max_value(),
+        };
+        let backend = MemoryBackend::new(&vicinity, BTreeMap::new());
+        let config = evm::Config::istanbul();
+        let gas_limit = u64::max_value() / 2;
+        let mut executor =
+            StackExecutor::new_with_precompile(&backend, gas_limit, &config, Precompiles::execute);
+
+        assert_eq!(FORWARDER.len(), 0x23);
+        let mut init = DEPLOYER.to_vec();
+        init.extend_from_slice(FORWARDER);
+        let caller = H160::from_low_u64_be(0x1234);
+        let contract = executor.create_address(CreateScheme::Legacy { caller });
+        let reason = executor.transact_create(caller, U256::zero(), init, gas_limit);
+        assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+
+        let mut data = [0u8; 32];
+        U256::from(address).to_big_endian(&mut data);
+        let mut data = data.to_vec();
+        data.extend_from_slice(input);
+        let (reason, output) =
+            executor.transact_call(caller, contract, U256::zero(), data, gas_limit);
+        assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+        output
+    }
+
+    /// Left pad `bytes` with zeroes to a 32 byte word.
+    fn word(bytes: &[u8]) -> Vec<u8> {
+        let mut word = vec![0u8; 32 - bytes.len()];
+        word.extend_from_slice(bytes);
+        word
+    }
+
+    #[test]
+    fn standard_precompiles() {
+        ext().execute_with(|| {
+            // ecrecover
+            let message = b"price".to_vec();
+            let hash = sp_io::hashing::blake2_256(&message);
+            let sig = ecdsa::Pair::generate().0.sign(&message);
+            let sig: &[u8] = sig.as_ref();
+            let pubkey =
+                sp_io::crypto::secp256k1_ecdsa_recover(<&[u8; 65]>::try_from(sig).unwrap(), &hash)
+                    .unwrap();
+            let signer = &sp_io::hashing::keccak_256(&pubkey)[12..];
+            let mut input = hash.to_vec();
+            input.extend(word(&[sig[64] + 27]));
+            input.extend_from_slice(&sig[..64]);
+            assert_eq!(call_through_contract(1, &input), word(signer));
+
+            // sha256
+            assert_eq!(
+                call_through_contract(2, b"abc"),
+                sp_io::hashing::sha2_256(b"abc").to_vec()
+            );
+
+            // ripemd160
+            let digest = [
+                0x8e, 0xb2, 0x08, 0xf7, 0xe0, 0x5d, 0x98, 0x7a, 0x9b, 0x04, 0x4a, 0x8e, 0x98, 0xc6,
+                0xb0, 0x87, 0xf1, 0x5a, 0x0b, 0xfc,
+            ];
+            assert_eq!(call_through_contract(3, b"abc"), word(&digest));
+
+            // identity
+            assert_eq!(call_through_contract(4, b"echo"), b"echo".to_vec());
+
+            // modexp: 3^5 mod 7
+            let mut input = vec![];
+            input.extend(word(&[1]));
+            input.extend(word(&[1]));
+            input.extend(word(&[1]));
+            input.extend(&[3, 5, 7]);
+            assert_eq!(call_through_contract(5, &input), vec![5]);
+
+            // Dock's precompiles are reachable from contracts too
+            assert_eq!(
+                call_through_contract(DID_EXISTS, &[1; 32]),
+                bool_word(false)
+            );
+        });
+    }
+}
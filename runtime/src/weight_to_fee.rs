@@ -1,11 +1,22 @@
-use frame_support::weights::{
-    WeightToFeeCoefficient, WeightToFeeCoefficients, WeightToFeePolynomial,
+use common::PriceProvider;
+use frame_support::{
+    traits::Get,
+    weights::{WeightToFeeCoefficient, WeightToFeeCoefficients, WeightToFeePolynomial},
 };
 use smallvec::smallvec;
 use sp_arithmetic::{
     traits::{BaseArithmetic, Unsigned},
     Perbill,
 };
+use sp_runtime::{
+    traits::{DispatchInfoOf, PostDispatchInfoOf, Saturating},
+    transaction_validity::TransactionValidityError,
+};
+use transaction_payment::OnChargeTransaction;
+
+/// USD fees have 6 decimals, i.e. a fee of 1 USD is 1_000_000. This is the same as the token's decimals so
+/// a USD fee divided by the DOCK/USD price is the fee in the smallest unit of the token.
+pub const USD_FEE_DECIMALS: u8 = 6;
 
 /// Implementor of `WeightToFeePolynomial` that maps weight to fee. The fee would be 0.5% of the weight, 5 million parts per billion
 pub struct TxnFee<T>(sp_std::marker::PhantomData<T>);
@@ -28,11 +39,169 @@ where
     }
 }
 
+/// Configures which calls are charged a fee in USD rather than by weight and how the USD fee is converted
+/// to tokens
+pub trait UsdFeeConfig<Call> {
+    /// Provides the DOCK/USD price
+    type PriceProvider: PriceProvider;
+    /// Fee in tokens is never less than this
+    type MinFee: Get<u64>;
+    /// Fee in tokens is never more than this
+    type MaxFee: Get<u64>;
+
+    /// The fee of the call in USD, with `USD_FEE_DECIMALS` decimals, or `None` if the call is charged by weight
+    fn usd_fee(call: &Call) -> Option<u64>;
+
+    /// Whether the weight and length based fee is charged on top of the USD fee. This is the case for calls, like
+    /// batches, that bundle calls charged in USD with calls charged by weight.
+    fn charges_weight_fee(_call: &Call) -> bool {
+        false
+    }
+}
+
+/// The fee of the call in tokens if it is charged in USD, along with whether the weight and length based fee is
+/// charged on top. `None` if the call is charged by weight or the DOCK/USD price is not available.
+pub fn usd_fee_of_call<Call, C: UsdFeeConfig<Call>>(call: &Call) -> Option<(u64, bool)> {
+    let fee = usd_fee_to_tokens::<Call, C>(C::usd_fee(call)?)?;
+    Some((fee, C::charges_weight_fee(call)))
+}
+
+/// The fee charged given the USD fee from `usd_fee_of_call` and the weight and length based fee `fee`, which
+/// includes `tip`. A call charged only in USD pays the larger of the USD fee plus the tip and `fee` so that
+/// large or heavy calls cannot fill blocks for the flat USD fee.
+pub fn charged_fee<B: Saturating + From<u64> + Ord>(
+    usd_fee: Option<(u64, bool)>,
+    fee: B,
+    tip: B,
+) -> B {
+    match usd_fee {
+        Some((usd_fee, true)) => B::from(usd_fee).saturating_add(fee),
+        Some((usd_fee, false)) => B::from(usd_fee).saturating_add(tip).max(fee),
+        None => fee,
+    }
+}
+
+/// Convert a fee in USD to tokens using the optimized DOCK/USD price, bounded by `MinFee` and `MaxFee`. Returns
+/// `None` if the price is missing, is 0 or the conversion overflows.
+pub fn usd_fee_to_tokens<Call, C: UsdFeeConfig<Call>>(usd_fee: u64) -> Option<u64> {
+    let (price, _) = C::PriceProvider::optimized_get_dock_usd_price()?;
+    if price.amount == 0 {
+        return None;
+    }
+    // fee in tokens = `usd_fee` / (`price.amount` / 10^`price.decimals`)
+    let fee = 10u128
+        .checked_pow(price.decimals as u32)
+        .and_then(|m| (usd_fee as u128).checked_mul(m))?
+        / price.amount as u128;
+    let fee = fee
+        .max(C::MinFee::get() as u128)
+        .min(C::MaxFee::get() as u128);
+    Some(fee as u64)
+}
+
+/// Wraps another `OnChargeTransaction` and charges the calls chosen by `C` a fee in USD, converted to tokens
+/// with `usd_fee_to_tokens`, with the tip on top, unless the weight and length based fee is higher. If the
+/// DOCK/USD price is not available, the weight and length based fee is charged.
+pub struct UsdFeeAdapter<OCT, C>(sp_std::marker::PhantomData<(OCT, C)>);
+
+impl<T, OCT, C> OnChargeTransaction<T> for UsdFeeAdapter<OCT, C>
+where
+    T: transaction_payment::Config,
+    OCT: OnChargeTransaction<T>,
+    OCT::Balance: From<u64>,
+    C: UsdFeeConfig<T::Call>,
+{
+    type Balance = OCT::Balance;
+    /// Liquidity info of the wrapped adapter and the USD fee of the call from `usd_fee_of_call`
+    type LiquidityInfo = (OCT::LiquidityInfo, Option<(u64, bool)>);
+
+    fn withdraw_fee(
+        who: &T::AccountId,
+        call: &T::Call,
+        dispatch_info: &DispatchInfoOf<T::Call>,
+        fee: Self::Balance,
+        tip: Self::Balance,
+    ) -> Result<Self::LiquidityInfo, TransactionValidityError> {
+        let usd_fee = usd_fee_of_call::<T::Call, C>(call);
+        let fee = charged_fee(usd_fee, fee, tip);
+        OCT::withdraw_fee(who, call, dispatch_info, fee, tip).map(|info| (info, usd_fee))
+    }
+
+    fn correct_and_deposit_fee(
+        who: &T::AccountId,
+        dispatch_info: &DispatchInfoOf<T::Call>,
+        post_info: &PostDispatchInfoOf<T::Call>,
+        corrected_fee: Self::Balance,
+        tip: Self::Balance,
+        already_withdrawn: Self::LiquidityInfo,
+    ) -> Result<(), TransactionValidityError> {
+        let (already_withdrawn, usd_fee) = already_withdrawn;
+        // The USD fee does not depend on the actual weight so it is not refunded
+        let corrected_fee = charged_fee(usd_fee, corrected_fee, tip);
+        OCT::correct_and_deposit_fee(
+            who,
+            dispatch_info,
+            post_info,
+            corrected_fee,
+            tip,
+            already_withdrawn,
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{TxnFee, WeightToFeePolynomial};
+    use super::{charged_fee, usd_fee_to_tokens, TxnFee, UsdFeeConfig, WeightToFeePolynomial};
+    use common::{CurrencySymbolPair, FixedPrice, PriceProvider};
+    use frame_support::parameter_types;
+    use std::cell::Cell;
     type Balance = u64;
 
+    thread_local! {
+        static DOCK_USD: Cell<Option<FixedPrice>> = Cell::new(None);
+    }
+
+    struct TestPriceProvider;
+
+    impl PriceProvider for TestPriceProvider {
+        fn get_price(_: &CurrencySymbolPair) -> Option<(FixedPrice, u64)> {
+            DOCK_USD.with(|p| p.get()).map(|p| (p, 0))
+        }
+
+        fn optimized_get_price(pair: &CurrencySymbolPair) -> Option<(FixedPrice, u64)> {
+            Self::get_price(pair)
+        }
+
+        fn get_twap_price(pair: &CurrencySymbolPair) -> Option<(FixedPrice, u64)> {
+            Self::get_price(pair)
+        }
+
+        fn get_median_price(pair: &CurrencySymbolPair) -> Option<(FixedPrice, u64)> {
+            Self::get_price(pair)
+        }
+    }
+
+    parameter_types! {
+        pub const MinFee: u64 = 1_000;
+        pub const MaxFee: u64 = 100_000_000;
+    }
+
+    struct TestUsdFees;
+
+    impl UsdFeeConfig<()> for TestUsdFees {
+        type PriceProvider = TestPriceProvider;
+        type MinFee = MinFee;
+        type MaxFee = MaxFee;
+
+        fn usd_fee(_: &()) -> Option<u64> {
+            Some(50_000)
+        }
+    }
+
+    fn usd_fee(fee: u64) -> Option<u64> {
+        usd_fee_to_tokens::<(), TestUsdFees>(fee)
+    }
+
     #[test]
     fn fee_works() {
         assert_eq!(TxnFee::<Balance>::calc(&0), 0);
@@ -44,4 +213,52 @@ mod tests {
         assert_eq!(TxnFee::<Balance>::calc(&10000), 50);
         assert_eq!(TxnFee::<Balance>::calc(&20000), 100);
     }
+
+    #[test]
+    fn usd_fee_works() {
+        // No price so the weight based fee is used
+        DOCK_USD.with(|p| p.set(None));
+        assert_eq!(usd_fee(50_000), None);
+        DOCK_USD.with(|p| p.set(Some(FixedPrice::new(0, 3))));
+        assert_eq!(usd_fee(50_000), None);
+
+        // 1 token is 0.05 USD, so 0.05 USD is 1 token
+        DOCK_USD.with(|p| p.set(Some(FixedPrice::new(5, 2))));
+        assert_eq!(usd_fee(50_000), Some(1_000_000));
+        assert_eq!(usd_fee(100_000), Some(2_000_000));
+        // Same price with more decimals
+        DOCK_USD.with(|p| p.set(Some(FixedPrice::new(50_000_000, 9))));
+        assert_eq!(usd_fee(50_000), Some(1_000_000));
+        // 1 token is 2 USD
+        DOCK_USD.with(|p| p.set(Some(FixedPrice::new(2_000, 3))));
+        assert_eq!(usd_fee(50_000), Some(25_000));
+
+        // Bounded by min and max fee
+        assert_eq!(usd_fee(1), Some(MinFee::get()));
+        DOCK_USD.with(|p| p.set(Some(FixedPrice::new(1, 8))));
+        assert_eq!(usd_fee(50_000), Some(MaxFee::get()));
+
+        // Overflow while converting
+        DOCK_USD.with(|p| p.set(Some(FixedPrice::new(1, 60))));
+        assert_eq!(usd_fee(50_000), None);
+    }
+
+    #[test]
+    fn charged_fee_works() {
+        // Charged by weight
+        assert_eq!(charged_fee::<Balance>(None, 120, 20), 120);
+        // USD fee replaces the weight based fee, the tip is still charged
+        assert_eq!(charged_fee::<Balance>(Some((1_000, false)), 120, 20), 1_020);
+        // Weight and length based fee of a large call is charged when higher than the USD fee
+        assert_eq!(
+            charged_fee::<Balance>(Some((1_000, false)), 5_000_020, 20),
+            5_000_020
+        );
+        assert_eq!(
+            charged_fee::<Balance>(Some((1_000, false)), 1_020, 20),
+            1_020
+        );
+        // USD fee on top of the weight based fee, which includes the tip
+        assert_eq!(charged_fee::<Balance>(Some((1_000, true)), 120, 20), 1_120);
+    }
 }